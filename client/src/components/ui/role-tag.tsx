@@ -7,6 +7,10 @@ export const ROLE_CONFIG: Record<
   MemberRole,
   { label: string; colour: string }
 > = {
+  guest: {
+    label: "Guest",
+    colour: "bg-amber-100 text-amber-700 border-amber-200",
+  },
   viewer: {
     label: "Viewer",
     colour: "bg-gray-100 text-gray-700 border-gray-200",
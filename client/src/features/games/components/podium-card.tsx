@@ -31,7 +31,7 @@ type PodiumStats = {
   avatarColour: AvatarColour;
   name: string;
   winRate: number;
-  pointsPerGame: number;
+  pointsPerGame: number | null;
 };
 
 export const PodiumCard: Component<{
@@ -105,7 +105,7 @@ export const PodiumCard: Component<{
             >
               {(stats) => (
                 <p class="font-mono-nums text-lg">
-                  {stats().pointsPerGame.toFixed(2)}
+                  {stats().pointsPerGame?.toFixed(2) ?? "-"}
                 </p>
               )}
             </Show>
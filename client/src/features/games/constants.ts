@@ -1,6 +1,7 @@
 export const SCORING_METRIC_LABELS = {
   win_rate: "Win rate",
   average_score: "Average score",
+  rating: "Rating",
 };
 
 // TODO: reduce duplication with other stat card
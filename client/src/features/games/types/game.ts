@@ -4,12 +4,21 @@ import {
   numericStringSchema,
 } from "@/lib/zod/schemas";
 
-export const scoringMetrics = ["win_rate", "average_score"] as const;
+export const scoringMetrics = ["win_rate", "average_score", "rating"] as const;
 export type ScoringMetric = (typeof scoringMetrics)[number];
 
-export const durationUnits = ["days", "months"] as const;
+export const resultTypes = ["score", "placement", "win_loss"] as const;
+export type ResultType = (typeof resultTypes)[number];
+
+export const scoreDirections = ["higher_is_better", "lower_is_better"] as const;
+export type ScoreDirection = (typeof scoreDirections)[number];
+
+export const durationUnits = ["days", "weeks", "months"] as const;
 export type DurationUnit = (typeof durationUnits)[number];
 
+export const seasonAlignments = ["none", "month", "iso_week", "quarter"] as const;
+export type SeasonAlignment = (typeof seasonAlignments)[number];
+
 export type GameRouteParams = {
   gameId: string;
 };
@@ -20,12 +29,18 @@ export type Game = {
   created_at: string;
   min_players_per_match: number;
   max_players_per_match: number;
+  min_players_per_team: number;
+  max_players_per_team: number;
   metric: ScoringMetric;
+  score_direction: ScoreDirection;
+  result_type: ResultType;
 
   season_duration: {
     value: number;
     unit: DurationUnit;
   } | null;
+  season_alignment: SeasonAlignment;
+  season_timezone: string;
 
   star_threshold: number | null;
   gold_threshold: number | null;
@@ -130,5 +145,10 @@ export const updateGameSchema = z
     path: ["max_players_per_match"],
   });
 export type UpdateGameRequest = z.output<typeof updateGameSchema> & {
+  min_players_per_team?: number;
+  max_players_per_team?: number;
+  score_direction?: ScoreDirection;
   next_season_start?: string | undefined;
+  season_alignment?: SeasonAlignment;
+  season_timezone?: string;
 };
@@ -13,7 +13,9 @@ export type HighlightType =
   | "highest_win_rate"
   | "highest_average_score"
   | "highest_single_score"
-  | "most_games_played";
+  | "most_games_played"
+  | "longest_win_streak"
+  | "most_consistent";
 
 export type HighlightDetail = {
   user_id: string;
@@ -26,6 +28,8 @@ export type Highlights = {
   highest_average_score: HighlightDetail[];
   highest_single_score: HighlightDetail[];
   most_games_played: HighlightDetail[];
+  longest_win_streak: HighlightDetail[];
+  most_consistent: HighlightDetail[];
 };
 
 export type ScoreboardEntry = {
@@ -34,7 +38,9 @@ export type ScoreboardEntry = {
   user_avatar: AvatarIcon;
   user_avatar_colour: AvatarColour;
   win_rate: number;
-  average_score: number;
+  average_score: number | null;
+  rating: number;
+  current_streak: number;
   matches_played: number;
   wins: number;
   rank: number;
@@ -45,8 +51,9 @@ export type ScoreboardEntry = {
   bronze_medals: number;
 
   rank_diff: number;
-  average_score_diff: number;
+  average_score_diff: number | null;
   win_rate_diff: number;
+  rating_diff: number;
 };
 
 export type Season = {
@@ -1,5 +1,5 @@
 import { useNavigate, useParams } from "@solidjs/router";
-import { addDays, addMonths, startOfDay } from "date-fns";
+import { addDays, addMonths, addWeeks, startOfDay } from "date-fns";
 import TrashIcon from "lucide-solid/icons/trash-2";
 import type { Component } from "solid-js";
 import { createMemo, createSignal, For, Show } from "solid-js";
@@ -40,12 +40,14 @@ function getCalculatedNextSeasonStart(
     return null;
   }
 
-  const nextSeasonStart =
-    seasonDuration.unit === "days"
-      ? addDays(currentSeasonStart, seasonDuration.value)
-      : addMonths(currentSeasonStart, seasonDuration.value);
-
-  return nextSeasonStart;
+  switch (seasonDuration.unit) {
+    case "days":
+      return addDays(currentSeasonStart, seasonDuration.value);
+    case "weeks":
+      return addWeeks(currentSeasonStart, seasonDuration.value);
+    case "months":
+      return addMonths(currentSeasonStart, seasonDuration.value);
+  }
 }
 
 // TODO: reduce duplication with create?
@@ -170,6 +172,11 @@ const EditGameForm: Component<Props> = (props) => {
         gameId: props.initialData.id,
         payload: {
           ...validData,
+          min_players_per_team: props.initialData.min_players_per_team,
+          max_players_per_team: props.initialData.max_players_per_team,
+          score_direction: props.initialData.score_direction,
+          season_alignment: props.initialData.season_alignment,
+          season_timezone: props.initialData.season_timezone,
           next_season_start: nextSeasonStart
             ? new Date(nextSeasonStart).toISOString()
             : undefined,
@@ -436,8 +436,8 @@ export const Scoreboard = () => {
 
                       <TableCell>
                         <span class="flex items-center gap-2">
-                          {score.average_score.toFixed(2)}
-                          <Change value={score.average_score_diff} />
+                          {score.average_score?.toFixed(2) ?? "-"}
+                          <Change value={score.average_score_diff ?? 0} />
                         </span>
                       </TableCell>
                     </TableRow>
@@ -9,7 +9,13 @@ export type Group = {
   my_role: MemberRole;
 };
 
-export const MEMBER_ROLES = ["viewer", "member", "admin", "owner"] as const;
+export const MEMBER_ROLES = [
+  "guest",
+  "viewer",
+  "member",
+  "admin",
+  "owner",
+] as const;
 export type MemberRole = (typeof MEMBER_ROLES)[number];
 
 export type GroupMember = {
@@ -33,6 +39,7 @@ export const createGroupSchema = z.object({
 export type CreateGroupRequest = z.output<typeof createGroupSchema>;
 
 export const ROLE_HIERARCHY: Record<MemberRole, number> = {
+  guest: -1,
   viewer: 0,
   member: 1,
   admin: 2,
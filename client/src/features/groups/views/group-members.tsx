@@ -99,6 +99,7 @@ export const GroupMembers = () => {
     MEMBER_ROLES.filter(
       (role) =>
         role !== "owner" &&
+        role !== "guest" &&
         hasPermission(group.userRole(), role, auth.user()?.email_verified),
     ),
   );
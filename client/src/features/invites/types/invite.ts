@@ -14,6 +14,8 @@ export type InviteDetail = {
   group_name: string;
   role: MemberRole;
 
+  guest_name: string | null;
+
   is_current_user_member: boolean;
 };
 
@@ -25,6 +27,7 @@ export type InviteSummary = {
   uses: number;
   role: MemberRole;
   email_whitelist: string[];
+  guest_id: string | null;
   created_at: string;
   expires_at: string;
 };
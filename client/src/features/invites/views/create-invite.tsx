@@ -72,7 +72,7 @@ export const CreateInvite = () => {
         onChange={(val) => setField("role", val)}
         error={errors.role}
         options={Object.entries(ROLE_CONFIG)
-          .filter(([role]) => role !== "owner")
+          .filter(([role]) => role !== "owner" && role !== "guest")
           .map(([role, { label }]) => ({
             label,
             value: role,
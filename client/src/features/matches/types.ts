@@ -16,6 +16,7 @@ export const createMatchSchema = (
       .array(matchScoreSchema)
       .min(minPlayersPerMatch)
       .max(maxPlayersPerMatch),
+    played_at: z.string().optional(),
   });
 export type CreateMatchRequest = z.output<ReturnType<typeof createMatchSchema>>;
 
@@ -88,13 +88,13 @@ export const LIFETIME_STATS: StatData[] = [
     icon: ChartColumnIcon,
     colour: "green",
     label: "Average Score",
-    getValue: (d) => d.lifetime.average_score.toFixed(2),
+    getValue: (d) => d.lifetime.average_score?.toFixed(2) ?? "-",
   },
   {
     icon: StarIcon,
     colour: "purple",
     label: "Best Score",
-    getValue: (d) => d.lifetime.best_score.toFixed(0),
+    getValue: (d) => d.lifetime.best_score?.toFixed(0) ?? "-",
   },
   {
     icon: Layers2Icon,
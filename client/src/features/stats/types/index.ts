@@ -2,9 +2,12 @@ import type { AvatarColour, AvatarIcon } from "@/components/ui/avatar";
 import type { Icon } from "@/lib/icons";
 import type { Colour } from "../constants";
 
+export type MatchOutcome = "win" | "draw" | "loss";
+
 export type MatchStats = {
   match_id: string;
-  score: number;
+  score: number | null;
+  outcome: MatchOutcome | null;
   played_at: string;
   rank_in_match: number;
 };
@@ -35,8 +38,8 @@ export type PlayerHistory = {
 
 // TODO: limit to season?
 export type HighlightStatsLifetime = {
-  average_score: number;
-  best_score: number;
+  average_score: number | null;
+  best_score: number | null;
   total_games: number;
   win_rate: number;
   rank: number;
@@ -142,7 +142,11 @@ export const PlayerStats = () => {
             <PlayerHistoryChart
               data={
                 history.data?.matches
-                  .map((s) => ({ score: s.score, rank: s.rank_in_match }))
+                  .flatMap((s) =>
+                    s.score === null
+                      ? []
+                      : [{ score: s.score, rank: s.rank_in_match }],
+                  )
                   .toReversed() ?? []
               }
             />
@@ -170,7 +174,7 @@ export const PlayerStats = () => {
                             {ordinalSuffix(s.rank_in_match)}
                           </span>
                         </TableCell>
-                        <TableCell>{s.score}</TableCell>
+                        <TableCell>{s.score ?? s.outcome ?? "-"}</TableCell>
                       </TableRow>
                     ))
                   }
@@ -0,0 +1,26 @@
+CREATE TYPE match_revision_action AS ENUM ('update', 'delete');
+
+-- Audit trail of corrections made to recorded matches
+-- NOTE: match_id intentionally has no foreign key, so history is kept after a match is deleted
+CREATE TABLE IF NOT EXISTS match_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    match_id UUID NOT NULL,
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+
+    action match_revision_action NOT NULL,
+    changed_by UUID NOT NULL REFERENCES users(id),
+    changed_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+-- Score changes for each revision. A NULL score means the player was not in the match before/after
+CREATE TABLE IF NOT EXISTS match_revision_scores (
+    revision_id UUID NOT NULL REFERENCES match_revisions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id),
+    previous_score INT,
+    new_score INT,
+
+    PRIMARY KEY (revision_id, user_id)
+);
+
+CREATE INDEX idx_match_revisions_match ON match_revisions (match_id, changed_at DESC);
+CREATE INDEX idx_match_revisions_game ON match_revisions (game_id, changed_at DESC);
@@ -0,0 +1 @@
+ALTER TYPE scoring_metric ADD VALUE 'rating';
@@ -0,0 +1,34 @@
+-- Team size constraints (defaults to individual games)
+ALTER TABLE games
+    ADD COLUMN min_players_per_team INT NOT NULL DEFAULT 1,
+    ADD COLUMN max_players_per_team INT NOT NULL DEFAULT 1;
+
+ALTER TABLE games ADD CONSTRAINT check_team_size_logic CHECK (min_players_per_team <= max_players_per_team);
+
+-- Players in the same team share the team's score. NULL means the player is on their own
+ALTER TABLE match_scores ADD COLUMN team INT;
+
+ALTER TABLE match_revision_scores
+    ADD COLUMN previous_team INT,
+    ADD COLUMN new_team INT;
+
+-- Rank teams rather than individual rows, so a team of 2 in second place is ranked 2nd, not 3rd
+DROP VIEW match_leaderboards;
+
+CREATE VIEW match_leaderboards AS
+WITH entries AS (
+    SELECT DISTINCT match_id, COALESCE(team::TEXT, user_id::TEXT) AS entry, score
+    FROM match_scores
+),
+ranked_entries AS (
+    SELECT
+        match_id,
+        entry,
+        RANK() OVER (PARTITION BY match_id ORDER BY score DESC) AS rank
+    FROM entries
+)
+SELECT ms.*, re.rank
+FROM match_scores ms
+JOIN ranked_entries re
+    ON re.match_id = ms.match_id
+    AND re.entry = COALESCE(ms.team::TEXT, ms.user_id::TEXT);
@@ -0,0 +1,36 @@
+CREATE TYPE score_direction AS ENUM ('higher_is_better', 'lower_is_better');
+
+ALTER TABLE games ADD COLUMN score_direction score_direction NOT NULL DEFAULT 'higher_is_better';
+
+-- Rank using the game's score direction
+DROP VIEW match_leaderboards;
+
+CREATE VIEW match_leaderboards AS
+WITH entries AS (
+    SELECT DISTINCT
+        ms.match_id,
+        COALESCE(ms.team::TEXT, ms.user_id::TEXT) AS entry,
+        ms.score,
+        g.score_direction
+    FROM match_scores ms
+    JOIN matches m ON m.id = ms.match_id
+    JOIN games g ON g.id = m.game_id
+),
+ranked_entries AS (
+    SELECT
+        match_id,
+        entry,
+        RANK() OVER (
+            PARTITION BY match_id
+            ORDER BY CASE
+                WHEN score_direction = 'lower_is_better' THEN score::BIGINT
+                ELSE -score::BIGINT
+            END
+        ) AS rank
+    FROM entries
+)
+SELECT ms.*, re.rank
+FROM match_scores ms
+JOIN ranked_entries re
+    ON re.match_id = ms.match_id
+    AND re.entry = COALESCE(ms.team::TEXT, ms.user_id::TEXT);
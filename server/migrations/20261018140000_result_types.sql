@@ -0,0 +1,60 @@
+-- How the result of each player in a match is recorded
+CREATE TYPE match_result_type AS ENUM ('score', 'placement', 'win_loss');
+CREATE TYPE match_outcome AS ENUM ('win', 'draw', 'loss');
+
+ALTER TABLE games ADD COLUMN result_type match_result_type NOT NULL DEFAULT 'score';
+
+-- Only one of score, placement or outcome is set, depending on the game's result type
+ALTER TABLE match_scores ALTER COLUMN score DROP NOT NULL;
+ALTER TABLE match_scores ADD COLUMN placement INT CHECK (placement >= 1);
+ALTER TABLE match_scores ADD COLUMN outcome match_outcome;
+
+ALTER TABLE match_revision_scores ADD COLUMN previous_placement INT;
+ALTER TABLE match_revision_scores ADD COLUMN new_placement INT;
+ALTER TABLE match_revision_scores ADD COLUMN previous_outcome match_outcome;
+ALTER TABLE match_revision_scores ADD COLUMN new_outcome match_outcome;
+
+-- Rank using whichever result the game records
+DROP VIEW match_leaderboards;
+
+CREATE VIEW match_leaderboards AS
+WITH entries AS (
+    SELECT DISTINCT
+        ms.match_id,
+        COALESCE(ms.team::TEXT, ms.user_id::TEXT) AS entry,
+        ms.score,
+        ms.placement,
+        ms.outcome,
+        g.score_direction,
+        g.result_type
+    FROM match_scores ms
+    JOIN matches m ON m.id = ms.match_id
+    JOIN games g ON g.id = m.game_id
+),
+ranked_entries AS (
+    SELECT
+        match_id,
+        entry,
+        CASE result_type
+            WHEN 'placement' THEN RANK() OVER (PARTITION BY match_id ORDER BY placement)
+            -- Nobody is ranked first in a draw, and losers always rank below those who drew
+            WHEN 'win_loss' THEN CASE outcome
+                WHEN 'win' THEN 1
+                WHEN 'draw' THEN 2
+                ELSE CASE WHEN BOOL_OR(outcome = 'draw') OVER (PARTITION BY match_id) THEN 3 ELSE 2 END
+            END
+            ELSE RANK() OVER (
+                PARTITION BY match_id
+                ORDER BY CASE
+                    WHEN score_direction = 'lower_is_better' THEN score::BIGINT
+                    ELSE -score::BIGINT
+                END
+            )
+        END AS rank
+    FROM entries
+)
+SELECT ms.*, re.rank
+FROM match_scores ms
+JOIN ranked_entries re
+    ON re.match_id = ms.match_id
+    AND re.entry = COALESCE(ms.team::TEXT, ms.user_id::TEXT);
@@ -0,0 +1,34 @@
+-- Final standings of each season, frozen when it ends
+CREATE TABLE season_results (
+    season_id UUID PRIMARY KEY REFERENCES seasons(id) ON DELETE CASCADE,
+    champion_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    finalised_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_season_results_champion ON season_results(champion_id);
+
+CREATE TABLE season_result_entries (
+    season_id UUID NOT NULL REFERENCES season_results(season_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rank INT NOT NULL,
+    matches_played BIGINT NOT NULL,
+    wins BIGINT NOT NULL,
+    win_rate FLOAT8 NOT NULL,
+    average_score FLOAT8,
+    best_score INT,
+    rating FLOAT8 NOT NULL,
+    star_medals INT NOT NULL,
+    gold_medals INT NOT NULL,
+    silver_medals INT NOT NULL,
+    bronze_medals INT NOT NULL,
+    PRIMARY KEY (season_id, user_id)
+);
+
+CREATE TABLE season_result_highlights (
+    season_id UUID NOT NULL REFERENCES season_results(season_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    stat_type TEXT NOT NULL,
+    value FLOAT8 NOT NULL
+);
+
+CREATE INDEX idx_season_result_highlights_season ON season_result_highlights(season_id);
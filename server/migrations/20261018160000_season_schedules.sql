@@ -0,0 +1,56 @@
+CREATE TYPE season_alignment AS ENUM ('none', 'month', 'iso_week', 'quarter');
+
+ALTER TABLE games
+    ADD COLUMN season_alignment season_alignment NOT NULL DEFAULT 'none',
+    ADD COLUMN season_timezone TEXT NOT NULL DEFAULT 'UTC';
+
+-- When a season starting at the given time should end, using the game's schedule. Durations are
+-- added in the game's time zone so seasons keep the same local start time across DST changes.
+-- Aligned seasons end on the calendar boundary closest to when the duration is up, but always
+-- after the season starts.
+CREATE FUNCTION next_season_end(p_game_id UUID, p_start_date TIMESTAMPTZ)
+RETURNS TIMESTAMPTZ AS $$
+DECLARE
+    game games%ROWTYPE;
+    local_start TIMESTAMP;
+    local_end TIMESTAMP;
+    unit TEXT;
+    unit_interval INTERVAL;
+    lower_boundary TIMESTAMP;
+    upper_boundary TIMESTAMP;
+BEGIN
+    SELECT * INTO game FROM games WHERE id = p_game_id;
+
+    IF game.season_duration IS NULL OR p_start_date IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    local_start := p_start_date AT TIME ZONE game.season_timezone;
+    local_end := local_start + game.season_duration;
+
+    IF game.season_alignment <> 'none' THEN
+        unit := CASE game.season_alignment
+            WHEN 'month' THEN 'month'
+            WHEN 'iso_week' THEN 'week'
+            WHEN 'quarter' THEN 'quarter'
+        END;
+        unit_interval := CASE game.season_alignment
+            WHEN 'month' THEN INTERVAL '1 month'
+            WHEN 'iso_week' THEN INTERVAL '1 week'
+            WHEN 'quarter' THEN INTERVAL '3 months'
+        END;
+
+        lower_boundary := date_trunc(unit, local_end);
+        upper_boundary := lower_boundary + unit_interval;
+
+        IF local_end - lower_boundary < upper_boundary - local_end
+            AND lower_boundary > local_start THEN
+            local_end := lower_boundary;
+        ELSE
+            local_end := upper_boundary;
+        END IF;
+    END IF;
+
+    RETURN local_end AT TIME ZONE game.season_timezone;
+END;
+$$ LANGUAGE plpgsql STABLE;
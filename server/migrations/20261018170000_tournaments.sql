@@ -0,0 +1,47 @@
+CREATE TYPE tournament_format AS ENUM ('single_elimination', 'double_elimination', 'round_robin');
+CREATE TYPE tournament_status AS ENUM ('in_progress', 'completed');
+CREATE TYPE tournament_bracket AS ENUM ('winners', 'losers', 'grand_final', 'round_robin');
+CREATE TYPE tournament_slot_status AS ENUM ('pending', 'ready', 'completed', 'walkover', 'void');
+
+CREATE TABLE tournaments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    format tournament_format NOT NULL,
+    status tournament_status NOT NULL DEFAULT 'in_progress',
+    winner_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_tournaments_game ON tournaments(game_id, created_at DESC);
+
+CREATE TABLE tournament_participants (
+    tournament_id UUID NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    seed INT NOT NULL,
+    PRIMARY KEY (tournament_id, user_id),
+    CONSTRAINT unique_tournament_seed UNIQUE (tournament_id, seed)
+);
+
+-- A match within a bracket. Players arrive from earlier slots, with the winner (and in double
+-- elimination, the loser) moving on to the linked slots once the match is recorded
+CREATE TABLE tournament_slots (
+    id UUID PRIMARY KEY,
+    tournament_id UUID NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    bracket tournament_bracket NOT NULL,
+    round INT NOT NULL,
+    position INT NOT NULL,
+    status tournament_slot_status NOT NULL DEFAULT 'pending',
+    player_1_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    player_2_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    match_id UUID UNIQUE REFERENCES matches(id) ON DELETE SET NULL,
+    winner_id UUID REFERENCES users(id) ON DELETE SET NULL,
+
+    winner_next_slot_id UUID REFERENCES tournament_slots(id) DEFERRABLE INITIALLY DEFERRED,
+    winner_next_position INT CHECK (winner_next_position IN (1, 2)),
+    loser_next_slot_id UUID REFERENCES tournament_slots(id) DEFERRABLE INITIALLY DEFERRED,
+    loser_next_position INT CHECK (loser_next_position IN (1, 2)),
+
+    CONSTRAINT unique_tournament_slot UNIQUE (tournament_id, bracket, round, position)
+);
@@ -0,0 +1,28 @@
+CREATE TYPE fixture_rsvp AS ENUM ('invited', 'going', 'maybe', 'declined');
+
+-- Matches planned for the future. Once results are entered, the fixture points at the recorded match
+CREATE TABLE IF NOT EXISTS fixtures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    scheduled_at TIMESTAMPTZ NOT NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    match_id UUID UNIQUE REFERENCES matches(id) ON DELETE SET NULL
+);
+
+-- Invited players, along with anyone else in the group who has responded
+CREATE TABLE IF NOT EXISTS fixture_players (
+    fixture_id UUID NOT NULL REFERENCES fixtures(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rsvp fixture_rsvp NOT NULL DEFAULT 'invited',
+    responded_at TIMESTAMPTZ,
+
+    PRIMARY KEY (fixture_id, user_id)
+);
+
+CREATE INDEX idx_fixtures_game ON fixtures (game_id, scheduled_at);
+
+-- Back-dated matches record when they were moved
+ALTER TABLE match_revisions
+    ADD COLUMN previous_played_at TIMESTAMPTZ,
+    ADD COLUMN new_played_at TIMESTAMPTZ;
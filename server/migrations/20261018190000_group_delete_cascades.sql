@@ -0,0 +1,21 @@
+BEGIN;
+
+-- GAMES
+-- Handle group deletion - remove its games, which removes their matches
+ALTER TABLE games
+DROP CONSTRAINT IF EXISTS games_group_id_fkey;
+
+ALTER TABLE games
+ADD CONSTRAINT games_group_id_fkey
+FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE;
+
+-- MATCHES
+-- Handle game deletion - remove its matches
+ALTER TABLE matches
+DROP CONSTRAINT IF EXISTS matches_game_id_fkey;
+
+ALTER TABLE matches
+ADD CONSTRAINT matches_game_id_fkey
+FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE;
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Groups can have several owners. The server makes sure at least one always remains
+DROP INDEX IF EXISTS one_owner_per_group;
@@ -0,0 +1,6 @@
+-- Guests are players without an account, who belong to a single group. They're stored as users
+-- who can't sign in, so they can be scored like anyone else
+ALTER TYPE user_role ADD VALUE IF NOT EXISTS 'guest' BEFORE 'viewer';
+
+-- An invite can be for a guest, who is claimed by whoever accepts it
+ALTER TABLE invites ADD COLUMN guest_id UUID REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,37 @@
+CREATE TYPE activity_kind AS ENUM (
+    'match_recorded',
+    'game_created',
+    'game_updated',
+    'member_joined',
+    'role_changed',
+    'season_ended',
+    'personal_best',
+    'medal_earned'
+);
+
+CREATE TYPE medal AS ENUM ('star', 'gold', 'silver', 'bronze');
+
+-- Things that happened in a group, for its activity feed. Only the columns relevant to each kind are set
+-- NOTE: match_id intentionally has no foreign key, so events are kept after a match is deleted
+CREATE TABLE IF NOT EXISTS group_activity (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    kind activity_kind NOT NULL,
+
+    -- Who the event is about, e.g. who joined, set a personal best or won a season
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Who made the change, when that was someone else
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+
+    game_id UUID REFERENCES games(id) ON DELETE CASCADE,
+    season_id UUID REFERENCES seasons(id) ON DELETE CASCADE,
+    match_id UUID,
+    role user_role,
+    medal medal,
+    score INT,
+
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_group_activity_group ON group_activity (group_id, created_at DESC, id DESC);
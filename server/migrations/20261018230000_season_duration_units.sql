@@ -0,0 +1,12 @@
+CREATE TYPE interval_unit AS ENUM ('months', 'weeks', 'days');
+
+-- Weeks are stored as days, so the unit a duration was given in can't be worked out from it
+ALTER TABLE games
+    ADD COLUMN season_duration_unit interval_unit DEFAULT NULL;
+
+UPDATE games
+SET season_duration_unit = CASE
+    WHEN EXTRACT(MONTH FROM season_duration) > 0 OR EXTRACT(YEAR FROM season_duration) > 0 THEN 'months'
+    ELSE 'days'
+END::interval_unit
+WHERE season_duration IS NOT NULL;
@@ -52,6 +52,21 @@ pub enum GroupError {
     #[error("Group not found")]
     NotFound,
 
+    #[error("A group must always have an owner, make someone else an owner first")]
+    LastOwner,
+
+    #[error("Guests can't be given a role, invite them to claim their place instead")]
+    GuestRole,
+
+    #[error("Both players were in the same match, so their results can't be combined")]
+    SharedMatches,
+
+    #[error("A member can't be merged into themselves")]
+    MergeIntoSelf,
+
+    #[error("Only guests can be merged into a guest")]
+    MergeIntoGuest,
+
     #[error(transparent)]
     Database(#[from] sqlx::Error),
 }
@@ -82,15 +97,51 @@ pub enum GameError {
     #[error("Season not found")]
     SeasonNotFound,
 
+    #[error("Season has not finished yet")]
+    SeasonResultsNotFound,
+
+    #[error("Season has already ended")]
+    SeasonAlreadyEnded,
+
+    #[error("Season must end after it starts")]
+    SeasonEndBeforeStart,
+
+    #[error("Seasons cannot overlap")]
+    SeasonOverlap,
+
+    #[error("Only the latest season can be open-ended")]
+    SeasonMustEnd,
+
+    #[error("Matches cannot be left outside of a season")]
+    SeasonStrandsMatches,
+
+    #[error("The latest season cannot start in the future")]
+    LatestSeasonStartInFuture,
+
+    #[error("Seasons can only be aligned to the calendar when they have a duration")]
+    AlignmentRequiresDuration,
+
+    #[error("Unknown time zone")]
+    InvalidTimezone,
+
     #[error("Max players cannot be less than min players")]
     MaxLessThanMin,
 
+    #[error("Max team size cannot be less than min team size")]
+    MaxTeamSizeLessThanMin,
+
+    #[error("Average score can only be used by games that record scores")]
+    MetricRequiresScores,
+
     #[error(transparent)]
     Database(#[from] sqlx::Error),
 }
 
 #[derive(Debug, Error)]
 pub enum MatchError {
+    #[error("Match not found")]
+    NotFound,
+
     #[error(
         "Number of scores provided does not match the number of players per match for this game"
     )]
@@ -102,6 +153,96 @@ pub enum MatchError {
     #[error("The same player was used in multiple scores")]
     DuplicatePlayer,
 
+    #[error("Either every player or no players must be assigned a team")]
+    PartialTeams,
+
+    #[error("Number of players in a team does not match the team size for this game")]
+    IncorrectTeamSize,
+
+    #[error("All players in a team must have the same result")]
+    TeamResultMismatch,
+
+    #[error("Each result must be a score, placement or outcome, matching the game's result type")]
+    ResultTypeMismatch,
+
+    #[error("A match must have either winners or draws, but not both")]
+    InvalidOutcomes,
+
+    #[error("Matches in a tournament bracket cannot be changed")]
+    InTournament,
+
+    #[error("A match cannot be played in the future")]
+    PlayedInFuture,
+
+    #[error("No season was running when the match was played")]
+    NoSeasonAtTime,
+
+    #[error("Imports must be sent as CSV or JSON")]
+    UnsupportedImportFormat,
+
+    #[error("Import could not be read: {0}")]
+    InvalidImport(String),
+
+    #[error("Cannot import more than {0} matches at once")]
+    ImportTooLarge(usize),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum TournamentError {
+    #[error("Tournament not found")]
+    NotFound,
+
+    #[error("Tournament slot not found")]
+    SlotNotFound,
+
+    #[error("Tournaments need a game where one player can play against another")]
+    UnsupportedGame,
+
+    #[error("The same player was entered multiple times")]
+    DuplicatePlayer,
+
+    #[error("One or more players are not a member of this group")]
+    PlayerNotMember,
+
+    #[error("Tournament has already finished")]
+    AlreadyCompleted,
+
+    #[error("This slot is not waiting for a match")]
+    SlotNotReady,
+
+    #[error("The match must be played by the two players in the slot")]
+    PlayersMismatch,
+
+    #[error("Knockout matches must have a winner")]
+    DrawNotAllowed,
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum FixtureError {
+    #[error("Fixture not found")]
+    NotFound,
+
+    #[error("Fixtures must be scheduled in the future")]
+    ScheduledInPast,
+
+    #[error("The same player was invited multiple times")]
+    DuplicatePlayer,
+
+    #[error("One or more players are not a member of this group")]
+    PlayerNotMember,
+
+    #[error("Results have already been entered for this fixture")]
+    AlreadyPlayed,
+
+    #[error("A response must be going, maybe or declined")]
+    InvalidRsvp,
+
     #[error(transparent)]
     Database(#[from] sqlx::Error),
 }
@@ -110,6 +251,9 @@ pub enum MatchError {
 pub enum StatsError {
     #[error("Not enough data")]
     NotEnoughData,
+
+    #[error("Head to head requires two different players")]
+    SamePlayer,
 }
 
 #[derive(Debug, Error)]
@@ -132,6 +276,12 @@ pub enum AppError {
     #[error(transparent)]
     Match(#[from] MatchError),
 
+    #[error(transparent)]
+    Tournament(#[from] TournamentError),
+
+    #[error(transparent)]
+    Fixture(#[from] FixtureError),
+
     #[error(transparent)]
     Stats(#[from] StatsError),
 
@@ -177,6 +327,11 @@ impl IntoResponse for AppError {
                 GroupError::UserAlreadyMember => (StatusCode::CONFLICT, err.to_string()),
                 GroupError::MemberNotFound => (StatusCode::NOT_FOUND, err.to_string()),
                 GroupError::NotFound => (StatusCode::NOT_FOUND, err.to_string()),
+                GroupError::LastOwner => (StatusCode::CONFLICT, err.to_string()),
+                GroupError::GuestRole => (StatusCode::BAD_REQUEST, err.to_string()),
+                GroupError::SharedMatches => (StatusCode::CONFLICT, err.to_string()),
+                GroupError::MergeIntoSelf => (StatusCode::BAD_REQUEST, err.to_string()),
+                GroupError::MergeIntoGuest => (StatusCode::BAD_REQUEST, err.to_string()),
                 GroupError::Database(e) => {
                     eprintln!("Group DB error: {:?}", e);
                     (
@@ -203,7 +358,18 @@ impl IntoResponse for AppError {
             AppError::Game(err) => match err {
                 GameError::NotFound => (StatusCode::NOT_FOUND, err.to_string()),
                 GameError::SeasonNotFound => (StatusCode::NOT_FOUND, err.to_string()),
+                GameError::SeasonResultsNotFound => (StatusCode::NOT_FOUND, err.to_string()),
+                GameError::SeasonAlreadyEnded => (StatusCode::CONFLICT, err.to_string()),
+                GameError::SeasonEndBeforeStart => (StatusCode::BAD_REQUEST, err.to_string()),
+                GameError::SeasonOverlap => (StatusCode::CONFLICT, err.to_string()),
+                GameError::SeasonMustEnd => (StatusCode::BAD_REQUEST, err.to_string()),
+                GameError::SeasonStrandsMatches => (StatusCode::CONFLICT, err.to_string()),
+                GameError::LatestSeasonStartInFuture => (StatusCode::BAD_REQUEST, err.to_string()),
+                GameError::AlignmentRequiresDuration => (StatusCode::BAD_REQUEST, err.to_string()),
+                GameError::InvalidTimezone => (StatusCode::BAD_REQUEST, err.to_string()),
                 GameError::MaxLessThanMin => (StatusCode::BAD_REQUEST, err.to_string()),
+                GameError::MaxTeamSizeLessThanMin => (StatusCode::BAD_REQUEST, err.to_string()),
+                GameError::MetricRequiresScores => (StatusCode::BAD_REQUEST, err.to_string()),
                 GameError::Database(e) => {
                     eprintln!("Game DB error: {:?}", e);
                     (
@@ -214,9 +380,23 @@ impl IntoResponse for AppError {
             },
 
             AppError::Match(err) => match err {
+                MatchError::NotFound => (StatusCode::NOT_FOUND, err.to_string()),
                 MatchError::IncorrectNumberOfScores => (StatusCode::BAD_REQUEST, err.to_string()),
                 MatchError::OneOrMorePlayersNotMember => (StatusCode::BAD_REQUEST, err.to_string()),
                 MatchError::DuplicatePlayer => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::PartialTeams => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::IncorrectTeamSize => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::TeamResultMismatch => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::ResultTypeMismatch => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::InvalidOutcomes => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::InTournament => (StatusCode::CONFLICT, err.to_string()),
+                MatchError::PlayedInFuture => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::NoSeasonAtTime => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::UnsupportedImportFormat => {
+                    (StatusCode::UNSUPPORTED_MEDIA_TYPE, err.to_string())
+                }
+                MatchError::InvalidImport(_) => (StatusCode::BAD_REQUEST, err.to_string()),
+                MatchError::ImportTooLarge(_) => (StatusCode::BAD_REQUEST, err.to_string()),
                 MatchError::Database(e) => {
                     eprintln!("Match DB error: {:?}", e);
                     (
@@ -226,8 +406,44 @@ impl IntoResponse for AppError {
                 }
             },
 
+            AppError::Tournament(err) => match err {
+                TournamentError::NotFound => (StatusCode::NOT_FOUND, err.to_string()),
+                TournamentError::SlotNotFound => (StatusCode::NOT_FOUND, err.to_string()),
+                TournamentError::UnsupportedGame => (StatusCode::BAD_REQUEST, err.to_string()),
+                TournamentError::DuplicatePlayer => (StatusCode::BAD_REQUEST, err.to_string()),
+                TournamentError::PlayerNotMember => (StatusCode::BAD_REQUEST, err.to_string()),
+                TournamentError::AlreadyCompleted => (StatusCode::CONFLICT, err.to_string()),
+                TournamentError::SlotNotReady => (StatusCode::CONFLICT, err.to_string()),
+                TournamentError::PlayersMismatch => (StatusCode::BAD_REQUEST, err.to_string()),
+                TournamentError::DrawNotAllowed => (StatusCode::BAD_REQUEST, err.to_string()),
+                TournamentError::Database(e) => {
+                    eprintln!("Tournament DB error: {:?}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Internal server error".to_string(),
+                    )
+                }
+            },
+
+            AppError::Fixture(err) => match err {
+                FixtureError::NotFound => (StatusCode::NOT_FOUND, err.to_string()),
+                FixtureError::ScheduledInPast => (StatusCode::BAD_REQUEST, err.to_string()),
+                FixtureError::DuplicatePlayer => (StatusCode::BAD_REQUEST, err.to_string()),
+                FixtureError::PlayerNotMember => (StatusCode::BAD_REQUEST, err.to_string()),
+                FixtureError::AlreadyPlayed => (StatusCode::CONFLICT, err.to_string()),
+                FixtureError::InvalidRsvp => (StatusCode::BAD_REQUEST, err.to_string()),
+                FixtureError::Database(e) => {
+                    eprintln!("Fixture DB error: {:?}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Internal server error".to_string(),
+                    )
+                }
+            },
+
             AppError::Stats(err) => match err {
                 StatsError::NotEnoughData => (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()),
+                StatsError::SamePlayer => (StatusCode::BAD_REQUEST, err.to_string()),
             },
 
             AppError::InternalServerError(e) => {
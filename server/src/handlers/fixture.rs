@@ -0,0 +1,114 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::AppError,
+    extractors::{
+        auth_user::AuthUser,
+        rate_limiting::ip::{create_ip_limiter, ip_limit_mw},
+        validated_json::ValidatedJson,
+        verified::Verified,
+    },
+    models::{
+        fixture::{CreateFixtureReq, FixtureResponse, RsvpReq},
+        game_match::{CreateMatchReq, MatchResponse},
+    },
+    services,
+};
+
+async fn create_fixture(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<CreateFixtureReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let fixture = services::fixture::create_fixture(&state, user.id, game_id, payload).await?;
+
+    let response: FixtureResponse = fixture.into();
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn get_fixtures(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let fixtures = services::fixture::get_fixtures(&state, user.id, game_id).await?;
+
+    let response: Vec<FixtureResponse> = fixtures.into_iter().map(|f| f.into()).collect();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn get_fixture(
+    State(state): State<AppState>,
+    Path(fixture_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let fixture = services::fixture::get_fixture(&state, user.id, fixture_id).await?;
+
+    let response: FixtureResponse = fixture.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn rsvp(
+    State(state): State<AppState>,
+    Path(fixture_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<RsvpReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let fixture = services::fixture::rsvp(&state, user.id, fixture_id, payload.rsvp).await?;
+
+    let response: FixtureResponse = fixture.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn delete_fixture(
+    State(state): State<AppState>,
+    Path(fixture_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    services::fixture::delete_fixture(&state, user.id, fixture_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn record_fixture_match(
+    State(state): State<AppState>,
+    Path(fixture_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<CreateMatchReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let game_match =
+        services::fixture::record_fixture_match(&state, user.id, fixture_id, payload).await?;
+
+    let response: MatchResponse = game_match.into();
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/games/{game_id}/fixtures",
+            post(create_fixture)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60))),
+        )
+        .route("/games/{game_id}/fixtures", get(get_fixtures))
+        .route("/fixtures/{fixture_id}", get(get_fixture))
+        .route("/fixtures/{fixture_id}", delete(delete_fixture))
+        .route("/fixtures/{fixture_id}/rsvp", put(rsvp))
+        .route(
+            "/fixtures/{fixture_id}/match",
+            post(record_fixture_match)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60))),
+        )
+}
@@ -3,7 +3,10 @@ use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     middleware,
-    response::IntoResponse,
+    response::{
+        IntoResponse,
+        sse::{KeepAlive, Sse},
+    },
     routing::{delete, get, post, put},
 };
 use uuid::Uuid;
@@ -20,7 +23,8 @@ use crate::{
     },
     models::{
         game::{CreateGameReq, GameResponse, SeasonsResponse, UpdateGameReq},
-        stats::{ScoreboardParams, ScoreboardResponse, SeasonScope},
+        season::{SeasonResponse, SeasonResultsResponse, StartSeasonReq, UpdateSeasonReq},
+        stats::{LiveScoreboardParams, ScoreboardParams, ScoreboardResponse, SeasonScope},
     },
     services,
 };
@@ -115,6 +119,18 @@ async fn get_scoreboard(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Pushes an event whenever a match is recorded, so scoreboards can update without polling
+async fn get_live_scoreboard(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    Query(query): Query<LiveScoreboardParams>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let updates = services::live::scoreboard_updates(&state, user.id, game_id, query).await?;
+
+    Ok(Sse::new(updates).keep_alive(KeepAlive::default()))
+}
+
 async fn get_game_details(
     State(state): State<AppState>,
     Path(game_id): Path<Uuid>,
@@ -149,6 +165,53 @@ async fn get_seasons(
     Ok((StatusCode::OK, Json(result)))
 }
 
+async fn get_season_results(
+    State(state): State<AppState>,
+    Path((game_id, season_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let results = services::season::get_season_results(&state, user.id, game_id, season_id).await?;
+
+    let response: SeasonResultsResponse = results.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn start_season(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<StartSeasonReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let season = services::season::start_season(&state, user.id, game_id, payload).await?;
+
+    let response: SeasonResponse = season.into();
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn end_season(
+    State(state): State<AppState>,
+    Path((game_id, season_id)): Path<(Uuid, Uuid)>,
+    user: Verified<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let season = services::season::end_season(&state, user.id, game_id, season_id).await?;
+
+    let response: SeasonResponse = season.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn update_season(
+    State(state): State<AppState>,
+    Path((game_id, season_id)): Path<(Uuid, Uuid)>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<UpdateSeasonReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let season =
+        services::season::update_season(&state, user.id, game_id, season_id, payload).await?;
+
+    let response: SeasonResponse = season.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route(
@@ -162,6 +225,14 @@ pub fn router() -> Router<AppState> {
         .route("/games/{game_id}", put(update_game))
         .route("/games/{game_id}", delete(delete_game))
         .route("/games/{game_id}/scoreboard", get(get_scoreboard))
+        .route("/games/{game_id}/scoreboard/live", get(get_live_scoreboard))
         .route("/games/{game_id}/last-players", get(get_last_players))
         .route("/games/{game_id}/seasons", get(get_seasons))
+        .route("/games/{game_id}/seasons", post(start_season))
+        .route("/games/{game_id}/seasons/{season_id}", put(update_season))
+        .route("/games/{game_id}/seasons/{season_id}/end", post(end_season))
+        .route(
+            "/games/{game_id}/seasons/{season_id}/results",
+            get(get_season_results),
+        )
 }
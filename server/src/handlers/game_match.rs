@@ -1,23 +1,30 @@
 use axum::{
     Extension, Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header::CONTENT_TYPE},
     middleware,
     response::IntoResponse,
-    routing::post,
+    routing::{delete, get, post, put},
 };
 use uuid::Uuid;
 
 use crate::{
     AppState,
-    errors::AppError,
+    errors::{AppError, MatchError},
     extractors::{
         auth_user::AuthUser,
         rate_limiting::ip::{create_ip_limiter, ip_limit_mw},
         validated_json::ValidatedJson,
         verified::Verified,
     },
-    models::game_match::{CreateMatchReq, MatchResponse},
+    models::{
+        game_match::{
+            CreateMatchReq, MatchDetailResponse, MatchListParams, MatchListResponse, MatchResponse,
+            MatchRevisionResponse, UpdateMatchReq,
+        },
+        import::{ImportFormat, ImportParams},
+        stats::SeasonScope,
+    },
     services,
 };
 
@@ -33,11 +40,137 @@ async fn create_match(
     Ok((StatusCode::CREATED, Json(response)))
 }
 
-pub fn router() -> Router<AppState> {
-    Router::new().route(
-        "/games/{game_id}/matches",
-        post(create_match)
-            .route_layer(middleware::from_fn(ip_limit_mw))
-            .route_layer(Extension(create_ip_limiter(10, 60))),
+async fn import_matches(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    Query(query): Query<ImportParams>,
+    user: Verified<AuthUser>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<impl IntoResponse, AppError> {
+    let format = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(ImportFormat::from_content_type)
+        .ok_or(MatchError::UnsupportedImportFormat)?;
+
+    let report =
+        services::import::import_matches(&state, user.id, game_id, format, &body, query.dry_run)
+            .await?;
+
+    let status = if !report.errors.is_empty() {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else if report.imported {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((status, Json(report)))
+}
+
+async fn get_matches(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    Query(query): Query<MatchListParams>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let season_id = query
+        .season
+        .unwrap_or(SeasonScope::All)
+        .to_season_id(&state, game_id)
+        .await?;
+
+    let (matches, next_cursor) = services::game_match::get_matches(
+        &state,
+        game_id,
+        user.id,
+        season_id,
+        query.player,
+        query.cursor,
+        query.limit,
     )
+    .await?;
+
+    let response = MatchListResponse {
+        matches: matches.into_iter().map(|m| m.into()).collect(),
+        next_cursor,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn get_match(
+    State(state): State<AppState>,
+    Path(match_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let game_match = services::game_match::get_match(&state, match_id, user.id).await?;
+
+    let response: MatchDetailResponse = game_match.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn update_match(
+    State(state): State<AppState>,
+    Path(match_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<UpdateMatchReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let game_match = services::game_match::update_match(&state, match_id, user.id, payload).await?;
+
+    let response: MatchResponse = game_match.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn delete_match(
+    State(state): State<AppState>,
+    Path(match_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    services::game_match::delete_match(&state, match_id, user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_match_revisions(
+    State(state): State<AppState>,
+    Path(match_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let revisions = services::game_match::get_match_revisions(&state, match_id, user.id).await?;
+
+    let response: Vec<MatchRevisionResponse> = revisions.into_iter().map(|r| r.into()).collect();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/games/{game_id}/matches",
+            post(create_match)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60))),
+        )
+        .route("/games/{game_id}/matches", get(get_matches))
+        .route(
+            "/games/{game_id}/matches/import",
+            post(import_matches)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60 * 60))),
+        )
+        .route("/matches/{match_id}", get(get_match))
+        .route(
+            "/matches/{match_id}",
+            put(update_match)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60))),
+        )
+        .route(
+            "/matches/{match_id}",
+            delete(delete_match)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60))),
+        )
+        .route("/matches/{match_id}/revisions", get(get_match_revisions))
 }
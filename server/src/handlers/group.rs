@@ -1,7 +1,7 @@
 use axum::{
     Extension, Json, Router,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     middleware,
     response::IntoResponse,
     routing::{delete, get, post, put},
@@ -19,11 +19,14 @@ use crate::{
         verified::Verified,
     },
     models::{
+        activity::{ActivityListResponse, ActivityParams},
+        export::{ExportFormat, ExportParams, GroupExportResponse},
         group::{
-            CreateGroupReq, GroupMembersParams, GroupResponse, GroupWithRoleResponse, OrderBy,
-            SetRoleReq, UpdateGroupReq,
+            CreateGroupReq, CreateGuestReq, GroupMembersParams, GroupResponse,
+            GroupWithRoleResponse, MergeMemberReq, OrderBy, SetRoleReq, TransferOwnershipReq,
+            UpdateGroupReq,
         },
-        stats::OrderDir,
+        stats::{GroupLeaderboardParams, GroupLeaderboardResponse, OrderDir, SeasonScope},
     },
     services,
 };
@@ -105,6 +108,103 @@ async fn set_member_role(
     Ok(StatusCode::NO_CONTENT)
 }
 
+async fn merge_member(
+    Verified(AuthMember { member, .. }): Verified<AuthMember>,
+    State(state): State<AppState>,
+    Path((_, member_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<MergeMemberReq>,
+) -> Result<impl IntoResponse, AppError> {
+    services::group::merge_members(&state, member, member_id, payload).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_group_activity(
+    AuthMember { member, .. }: AuthMember,
+    State(state): State<AppState>,
+    Query(query): Query<ActivityParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let (activity, next_cursor) =
+        services::activity::get_activity(&state, member, query.cursor, query.limit).await?;
+
+    let response = ActivityListResponse {
+        activity: activity.into_iter().map(|a| a.into()).collect(),
+        next_cursor,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn create_guest(
+    Verified(AuthMember { member, .. }): Verified<AuthMember>,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateGuestReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = services::guest::create_guest(&state, member, payload).await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn transfer_ownership(
+    Verified(AuthMember { member, .. }): Verified<AuthMember>,
+    State(state): State<AppState>,
+    Json(payload): Json<TransferOwnershipReq>,
+) -> Result<impl IntoResponse, AppError> {
+    services::group::transfer_ownership(&state, member, payload.user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_group_leaderboard(
+    AuthMember { member, .. }: AuthMember,
+    State(state): State<AppState>,
+    Query(query): Query<GroupLeaderboardParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let season = query.season.unwrap_or(SeasonScope::All);
+    let entries = services::group::get_group_leaderboard(&state, member, season).await?;
+
+    let response = GroupLeaderboardResponse { entries };
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn export_group(
+    AuthMember { member, .. }: AuthMember,
+    State(state): State<AppState>,
+    Query(query): Query<ExportParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let export = services::export::export_group(&state, member).await?;
+
+    let response = match query.format.unwrap_or_default() {
+        ExportFormat::Json => {
+            let response: GroupExportResponse = export.into();
+            (
+                [(
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"group-export.json\"",
+                )],
+                Json(response),
+            )
+                .into_response()
+        }
+        ExportFormat::Csv => {
+            let bundle = services::export::export_to_zip(&export)?;
+            (
+                [
+                    (header::CONTENT_TYPE, "application/zip"),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        "attachment; filename=\"group-export.zip\"",
+                    ),
+                ],
+                bundle,
+            )
+                .into_response()
+        }
+    };
+
+    Ok((StatusCode::OK, response))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route(
@@ -117,6 +217,20 @@ pub fn router() -> Router<AppState> {
         .route("/groups/{group_id}", put(update_group))
         .route("/groups/{group_id}", delete(delete_group))
         .route("/groups/{group_id}/members", get(get_group_members))
+        .route(
+            "/groups/{group_id}/guests",
+            post(create_guest)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(20, 60 * 60))),
+        )
+        .route("/groups/{group_id}/leaderboard", get(get_group_leaderboard))
+        .route("/groups/{group_id}/activity", get(get_group_activity))
+        .route(
+            "/groups/{group_id}/export",
+            get(export_group)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(5, 60 * 60))),
+        )
         .route(
             "/groups/{group_id}/member/{member_id}",
             delete(remove_group_member),
@@ -125,4 +239,12 @@ pub fn router() -> Router<AppState> {
             "/groups/{group_id}/member/{member_id}/role",
             put(set_member_role),
         )
+        .route(
+            "/groups/{group_id}/member/{member_id}/merge",
+            post(merge_member),
+        )
+        .route(
+            "/groups/{group_id}/transfer-ownership",
+            post(transfer_ownership),
+        )
 }
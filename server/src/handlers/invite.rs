@@ -64,6 +64,15 @@ async fn get_invite(
         .await?
         .is_some();
 
+    let guest_name = match invite.guest_id {
+        Some(guest_id) => state
+            .user_repo
+            .find_by_id(&state.pool, &guest_id)
+            .await?
+            .map(|guest| guest.name),
+        None => None,
+    };
+
     let response = InviteDetailResponse {
         id: invite.id,
         created_by_name: invite.created_by_name,
@@ -73,6 +82,8 @@ async fn get_invite(
         group_name: group.name,
         role: invite.role,
 
+        guest_name,
+
         is_current_user_member: in_group,
     };
 
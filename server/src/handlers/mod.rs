@@ -3,11 +3,13 @@ use axum::Router;
 use crate::AppState;
 
 mod auth;
+mod fixture;
 mod game;
 mod game_match;
 mod group;
 mod invite;
 mod stats;
+mod tournament;
 mod user;
 mod vitals;
 
@@ -20,6 +22,8 @@ pub fn api_router() -> Router<AppState> {
         .merge(invite::router())
         .merge(game::router())
         .merge(game_match::router())
+        .merge(fixture::router())
         .merge(stats::router())
+        .merge(tournament::router())
         .merge(vitals::router())
 }
@@ -11,7 +11,10 @@ use crate::{
     AppState,
     errors::AppError,
     extractors::auth_user::AuthUser,
-    models::stats::{PlayerHighlightsResponse, PlayerHistoryResponse, SeasonScope, StatsParams},
+    models::stats::{
+        HeadToHeadParams, HeadToHeadResponse, PlayerHighlightsResponse, PlayerHistoryResponse,
+        SeasonScope, StandingsHistoryResponse, StatsParams,
+    },
 };
 
 pub async fn get_user_history(
@@ -82,6 +85,50 @@ pub async fn get_distributions(
     Ok((StatusCode::OK, Json(distribution)))
 }
 
+pub async fn get_head_to_head(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    Query(query): Query<HeadToHeadParams>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let season_id = query
+        .season
+        .unwrap_or(SeasonScope::All)
+        .to_season_id(&state, game_id)
+        .await?;
+
+    let stats = state
+        .stats_service
+        .get_head_to_head(&state, user.id, game_id, season_id, query.players)
+        .await?;
+
+    let response = HeadToHeadResponse::new(stats, season_id);
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn get_standings_history(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    Query(query): Query<StatsParams>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let season_id = query
+        .season
+        .unwrap_or(SeasonScope::All)
+        .to_season_id(&state, game_id)
+        .await?;
+
+    let history = state
+        .stats_service
+        .get_standings_history(&state, user.id, game_id, season_id)
+        .await?;
+
+    let response = StandingsHistoryResponse::new(history, season_id);
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route(
@@ -93,4 +140,9 @@ pub fn router() -> Router<AppState> {
             get(get_player_highlights),
         )
         .route("/games/{game_id}/distributions", get(get_distributions))
+        .route("/games/{game_id}/head-to-head", get(get_head_to_head))
+        .route(
+            "/games/{game_id}/standings-history",
+            get(get_standings_history),
+        )
 }
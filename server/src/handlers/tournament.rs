@@ -0,0 +1,103 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::AppError,
+    extractors::{
+        auth_user::AuthUser,
+        rate_limiting::ip::{create_ip_limiter, ip_limit_mw},
+        validated_json::ValidatedJson,
+        verified::Verified,
+    },
+    models::{
+        game_match::CreateMatchReq,
+        tournament::{CreateTournamentReq, TournamentDetailResponse, TournamentResponse},
+    },
+    services,
+};
+
+async fn create_tournament(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<CreateTournamentReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let tournament =
+        services::tournament::create_tournament(&state, user.id, game_id, payload).await?;
+
+    let response: TournamentDetailResponse = tournament.into();
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn get_tournaments(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let tournaments = services::tournament::get_tournaments(&state, user.id, game_id).await?;
+
+    let response: Vec<TournamentResponse> = tournaments.into_iter().map(|t| t.into()).collect();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn get_tournament(
+    State(state): State<AppState>,
+    Path(tournament_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let tournament = services::tournament::get_tournament(&state, user.id, tournament_id).await?;
+
+    let response: TournamentDetailResponse = tournament.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn delete_tournament(
+    State(state): State<AppState>,
+    Path(tournament_id): Path<Uuid>,
+    user: Verified<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    services::tournament::delete_tournament(&state, user.id, tournament_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn record_slot_match(
+    State(state): State<AppState>,
+    Path((tournament_id, slot_id)): Path<(Uuid, Uuid)>,
+    user: Verified<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<CreateMatchReq>,
+) -> Result<impl IntoResponse, AppError> {
+    let tournament =
+        services::tournament::record_slot_match(&state, user.id, tournament_id, slot_id, payload)
+            .await?;
+
+    let response: TournamentDetailResponse = tournament.into();
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/games/{game_id}/tournaments",
+            post(create_tournament)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(5, 60 * 60))),
+        )
+        .route("/games/{game_id}/tournaments", get(get_tournaments))
+        .route("/tournaments/{tournament_id}", get(get_tournament))
+        .route("/tournaments/{tournament_id}", delete(delete_tournament))
+        .route(
+            "/tournaments/{tournament_id}/slots/{slot_id}/match",
+            post(record_slot_match)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(Extension(create_ip_limiter(10, 60))),
+        )
+}
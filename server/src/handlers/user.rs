@@ -1,12 +1,13 @@
 use axum::{
     Extension, Json, Router,
-    extract::State,
-    http::StatusCode,
+    extract::{Path, State},
+    http::{StatusCode, header},
     middleware,
     response::IntoResponse,
-    routing::{get, patch, post, put},
+    routing::{delete, get, patch, post, put},
 };
 use tower_sessions::Session;
+use uuid::Uuid;
 
 use crate::{
     AppState,
@@ -23,7 +24,11 @@ use crate::{
     },
     models::{
         group::GroupResponse,
-        user::{CreateUserReq, UpdateEmailReq, UpdatePasswordReq, UpdateUserReq, UserResponse},
+        season::TrophyCabinetResponse,
+        user::{
+            CreateUserReq, DeleteAccountReq, UpdateEmailReq, UpdatePasswordReq, UpdateUserReq,
+            UserResponse,
+        },
     },
     services,
 };
@@ -132,6 +137,35 @@ async fn update_password(
     Ok(StatusCode::NO_CONTENT)
 }
 
+async fn export_personal_data(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let response = services::user::export_personal_data(&state, user).await?;
+
+    Ok((
+        StatusCode::OK,
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"personal-data.json\"",
+        )],
+        Json(response),
+    ))
+}
+
+async fn delete_current_user(
+    session: Session,
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    ValidatedJson(payload): ValidatedJson<DeleteAccountReq>,
+) -> Result<impl IntoResponse, AppError> {
+    services::user::delete_account(&state, user, payload).await?;
+
+    session.delete().await.ok();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn resend_verification(
     State(state): State<AppState>,
     AuthUser(user): AuthUser,
@@ -141,6 +175,17 @@ async fn resend_verification(
     Ok(StatusCode::NO_CONTENT)
 }
 
+async fn get_user_trophies(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let trophies = services::season::get_trophies(&state, user.id, user_id).await?;
+
+    let response: TrophyCabinetResponse = trophies.into();
+    Ok((StatusCode::OK, Json(response)))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route(
@@ -152,6 +197,20 @@ pub fn router() -> Router<AppState> {
         )
         .route("/users/me", get(get_current_user))
         .route("/users/me", patch(update_current_user))
+        .route(
+            "/users/me",
+            delete(delete_current_user)
+                .route_layer(middleware::from_fn(ip_limit_mw))
+                .route_layer(middleware::from_fn(user_limit_mw))
+                .route_layer(Extension(create_ip_limiter(5, 60 * 60)))
+                .route_layer(Extension(create_user_limiter(3, 60 * 60))),
+        )
+        .route(
+            "/users/me/export",
+            get(export_personal_data)
+                .route_layer(middleware::from_fn(user_limit_mw))
+                .route_layer(Extension(create_user_limiter(5, 60 * 60))),
+        )
         .route(
             "/users/me/password",
             put(update_password)
@@ -169,6 +228,7 @@ pub fn router() -> Router<AppState> {
                 .route_layer(Extension(create_ip_limiter(5, 3600))),
         )
         .route("/users/me/groups", get(get_current_user_groups))
+        .route("/users/{user_id}/trophies", get(get_user_trophies))
         .route(
             "/resend-verification",
             post(resend_verification)
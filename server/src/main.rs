@@ -31,12 +31,14 @@ use crate::services::email::ConsoleProvider;
 use crate::{
     extractors::rate_limiting::ip::{create_ip_limiter, ip_limit_mw},
     repositories::{
-        game_repo::GameRepo, group_repo::GroupRepo, invite_repo::InviteRepo, match_repo::MatchRepo,
+        activity_repo::ActivityRepo, fixture_repo::FixtureRepo, game_repo::GameRepo,
+        group_repo::GroupRepo, invite_repo::InviteRepo, match_repo::MatchRepo,
         password_resets_repo::PasswordResetsRepo, season_repo::SeasonRepo, stats_repo::StatsRepo,
-        user_repo::UserRepo, verification_repo::VerificationRepo,
+        tournament_repo::TournamentRepo, user_repo::UserRepo, verification_repo::VerificationRepo,
     },
     services::{
         email::EmailService,
+        live::LiveUpdates,
         season::check_and_update_seasons,
         stats::{
             CacheInvalidator, StatsProvider,
@@ -53,6 +55,7 @@ pub struct AppState {
     pub email_service: Arc<EmailService>,
     pub stats_service: Arc<dyn StatsProvider>,
     pub stats_cache_invalidator: Arc<dyn CacheInvalidator>,
+    pub live_updates: Arc<LiveUpdates>,
 
     pub password_resets_repo: Arc<PasswordResetsRepo>,
     pub verification_repo: Arc<VerificationRepo>,
@@ -63,6 +66,9 @@ pub struct AppState {
     pub match_repo: Arc<MatchRepo>,
     pub stats_repo: Arc<StatsRepo>,
     pub season_repo: Arc<SeasonRepo>,
+    pub tournament_repo: Arc<TournamentRepo>,
+    pub fixture_repo: Arc<FixtureRepo>,
+    pub activity_repo: Arc<ActivityRepo>,
 
     pub vitals_log: Arc<tokio::sync::Mutex<tokio::fs::File>>,
 }
@@ -78,6 +84,9 @@ impl AppState {
         let verification_repo = Arc::new(VerificationRepo {});
         let password_resets_repo = Arc::new(PasswordResetsRepo {});
         let season_repo = Arc::new(SeasonRepo {});
+        let tournament_repo = Arc::new(TournamentRepo {});
+        let fixture_repo = Arc::new(FixtureRepo {});
+        let activity_repo = Arc::new(ActivityRepo {});
 
         let email_service = Arc::new(Self::get_email_service());
 
@@ -118,6 +127,7 @@ impl AppState {
 
             stats_service,
             stats_cache_invalidator,
+            live_updates: Arc::new(LiveUpdates::default()),
 
             password_resets_repo,
             verification_repo,
@@ -128,6 +138,9 @@ impl AppState {
             match_repo,
             stats_repo,
             season_repo,
+            tournament_repo,
+            fixture_repo,
+            activity_repo,
 
             vitals_log,
         }
@@ -0,0 +1,230 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::{FromRow, prelude::Type};
+use uuid::Uuid;
+
+use crate::models::{
+    group::GroupMemberRole,
+    stats::Medal,
+    user::{Avatar, AvatarColour},
+};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[sqlx(type_name = "activity_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    MatchRecorded,
+    GameCreated,
+    GameUpdated,
+    MemberJoined,
+    RoleChanged,
+    SeasonEnded,
+    PersonalBest,
+    MedalEarned,
+}
+
+/// An event to add to a group's activity feed. Only the fields relevant to its kind are set
+#[derive(Debug)]
+pub struct NewActivity {
+    pub group_id: Uuid,
+    pub kind: ActivityKind,
+
+    /// Who the event is about
+    pub user_id: Option<Uuid>,
+
+    /// Who made the change, when that was someone else
+    pub actor_id: Option<Uuid>,
+
+    pub game_id: Option<Uuid>,
+    pub season_id: Option<Uuid>,
+    pub match_id: Option<Uuid>,
+    pub role: Option<GroupMemberRole>,
+    pub medal: Option<Medal>,
+    pub score: Option<i32>,
+}
+
+impl NewActivity {
+    pub fn new(group_id: Uuid, kind: ActivityKind) -> Self {
+        Self {
+            group_id,
+            kind,
+            user_id: None,
+            actor_id: None,
+            game_id: None,
+            season_id: None,
+            match_id: None,
+            role: None,
+            medal: None,
+            score: None,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct ActivityDb {
+    pub id: Uuid,
+    pub kind: ActivityKind,
+    pub created_at: DateTime<Utc>,
+
+    pub user_id: Option<Uuid>,
+    pub user_name: Option<String>,
+    pub user_avatar: Option<Avatar>,
+    pub user_avatar_colour: Option<AvatarColour>,
+
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub actor_avatar: Option<Avatar>,
+    pub actor_avatar_colour: Option<AvatarColour>,
+
+    pub game_id: Option<Uuid>,
+    pub game_name: Option<String>,
+
+    pub season_id: Option<Uuid>,
+    pub season_number: Option<i32>,
+    pub season_name: Option<String>,
+
+    pub match_id: Option<Uuid>,
+    pub role: Option<GroupMemberRole>,
+    pub medal: Option<Medal>,
+    pub score: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityResponse {
+    pub id: Uuid,
+    pub kind: ActivityKind,
+    pub created_at: DateTime<Utc>,
+    pub user: Option<ActivityUserResponse>,
+    pub actor: Option<ActivityUserResponse>,
+    pub game: Option<ActivityGameResponse>,
+    pub season: Option<ActivitySeasonResponse>,
+    pub match_id: Option<Uuid>,
+    pub role: Option<GroupMemberRole>,
+    pub medal: Option<Medal>,
+    pub score: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityUserResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+}
+
+impl ActivityUserResponse {
+    /// Users are joined onto events, so are only there if the event has one
+    fn from_parts(
+        id: Option<Uuid>,
+        name: Option<String>,
+        avatar: Option<Avatar>,
+        avatar_colour: Option<AvatarColour>,
+    ) -> Option<Self> {
+        Some(Self {
+            id: id?,
+            name: name?,
+            avatar: avatar?,
+            avatar_colour: avatar_colour?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityGameResponse {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivitySeasonResponse {
+    pub id: Uuid,
+    pub number: i32,
+    pub name: Option<String>,
+}
+
+impl From<ActivityDb> for ActivityResponse {
+    fn from(activity: ActivityDb) -> Self {
+        Self {
+            id: activity.id,
+            kind: activity.kind,
+            created_at: activity.created_at,
+            user: ActivityUserResponse::from_parts(
+                activity.user_id,
+                activity.user_name,
+                activity.user_avatar,
+                activity.user_avatar_colour,
+            ),
+            actor: ActivityUserResponse::from_parts(
+                activity.actor_id,
+                activity.actor_name,
+                activity.actor_avatar,
+                activity.actor_avatar_colour,
+            ),
+            game: activity
+                .game_id
+                .zip(activity.game_name)
+                .map(|(id, name)| ActivityGameResponse { id, name }),
+            season: activity
+                .season_id
+                .zip(activity.season_number)
+                .map(|(id, number)| ActivitySeasonResponse {
+                    id,
+                    number,
+                    name: activity.season_name,
+                }),
+            match_id: activity.match_id,
+            role: activity.role,
+            medal: activity.medal,
+            score: activity.score,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityListResponse {
+    pub activity: Vec<ActivityResponse>,
+    pub next_cursor: Option<ActivityCursor>,
+}
+
+#[derive(Deserialize)]
+pub struct ActivityParams {
+    pub cursor: Option<ActivityCursor>,
+    pub limit: Option<i64>,
+}
+
+/// Position in a group's activity, ordered by `created_at` with the ID breaking ties.
+/// Represented as `<created_at in microseconds>_<activity id>`
+#[derive(Debug, Clone, Copy)]
+pub struct ActivityCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Serialize for ActivityCursor {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!(
+            "{}_{}",
+            self.created_at.timestamp_micros(),
+            self.id
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for ActivityCursor {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        let (micros, id) = s
+            .split_once('_')
+            .ok_or_else(|| serde::de::Error::custom("Invalid cursor"))?;
+
+        let created_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(|| serde::de::Error::custom("Invalid cursor"))?;
+
+        let id = Uuid::parse_str(id).map_err(serde::de::Error::custom)?;
+
+        Ok(Self { created_at, id })
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{
+    game::{
+        GameDb, GameResponse, Interval, MatchResultType, ScoreDirection, ScoringMetric,
+        SeasonAlignment,
+    },
+    game_match::{MatchDetailsDb, MatchOutcome},
+    group::{GroupDb, GroupMemberDetailsDb, GroupMemberRole, GroupResponse},
+    season::{SeasonDb, SeasonResponse},
+    user::UserResponse,
+};
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+
+    /// A zip of CSV files, one for each kind of data
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ExportScoreDb {
+    pub match_id: Uuid,
+    pub user_id: Uuid,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+}
+
+/// Everything recorded for a group
+#[derive(Debug)]
+pub struct GroupExport {
+    pub exported_at: DateTime<Utc>,
+    pub group: GroupDb,
+    pub members: Vec<GroupMemberDetailsDb>,
+    pub games: Vec<GameDb>,
+    pub seasons: Vec<SeasonDb>,
+    pub matches: Vec<MatchDetailsDb>,
+    pub scores: Vec<ExportScoreDb>,
+
+    /// Emails are only exported for those allowed to see them
+    pub include_emails: bool,
+}
+
+impl GroupExport {
+    /// How each member is named in exported scores, matching the format used by match imports
+    fn player_names(&self) -> HashMap<Uuid, String> {
+        self.members
+            .iter()
+            .map(|m| (m.id, self.member_email(m).unwrap_or_else(|| m.name.clone())))
+            .collect()
+    }
+
+    /// Former members are still in the history of a group, but can no longer be named
+    fn player_name(player_names: &HashMap<Uuid, String>, user_id: Uuid) -> String {
+        player_names
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| user_id.to_string())
+    }
+
+    /// Guests don't have a real email, so they're always named instead
+    fn member_email(&self, member: &GroupMemberDetailsDb) -> Option<String> {
+        (self.include_emails && member.role != GroupMemberRole::Guest).then(|| member.email.clone())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupExportResponse {
+    pub exported_at: DateTime<Utc>,
+    pub group: GroupResponse,
+    pub members: Vec<ExportMemberResponse>,
+    pub games: Vec<ExportGameResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportMemberResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub role: GroupMemberRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// A game with its seasons and matches. Its matches can be imported as they are
+#[derive(Debug, Serialize)]
+pub struct ExportGameResponse {
+    #[serde(flatten)]
+    pub game: GameResponse,
+    pub seasons: Vec<SeasonResponse>,
+    pub matches: Vec<ExportMatchResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportMatchResponse {
+    pub id: Uuid,
+    pub season_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub recorded_by: Uuid,
+    pub scores: Vec<ExportScoreResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportScoreResponse {
+    pub user_id: Uuid,
+    pub player: String,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+}
+
+impl From<GroupExport> for GroupExportResponse {
+    fn from(export: GroupExport) -> Self {
+        let player_names = export.player_names();
+
+        let mut scores_by_match: HashMap<Uuid, Vec<&ExportScoreDb>> = HashMap::new();
+        for score in &export.scores {
+            scores_by_match
+                .entry(score.match_id)
+                .or_default()
+                .push(score);
+        }
+
+        let mut matches_by_game: HashMap<Uuid, Vec<&MatchDetailsDb>> = HashMap::new();
+        for game_match in &export.matches {
+            matches_by_game
+                .entry(game_match.game_id)
+                .or_default()
+                .push(game_match);
+        }
+
+        let members = export
+            .members
+            .iter()
+            .map(|m| ExportMemberResponse {
+                id: m.id,
+                name: m.name.clone(),
+                email: export.member_email(m),
+                role: m.role,
+                joined_at: m.joined_at,
+            })
+            .collect();
+
+        let games = export
+            .games
+            .iter()
+            .map(|game| ExportGameResponse {
+                game: game.clone().into(),
+                seasons: export
+                    .seasons
+                    .iter()
+                    .filter(|s| s.game_id == game.id)
+                    .map(|s| s.clone().into())
+                    .collect(),
+                matches: matches_by_game
+                    .get(&game.id)
+                    .into_iter()
+                    .flatten()
+                    .map(|m| ExportMatchResponse {
+                        id: m.id,
+                        season_id: m.season_id,
+                        played_at: m.played_at,
+                        recorded_by: m.recorded_by,
+                        scores: scores_by_match
+                            .get(&m.id)
+                            .into_iter()
+                            .flatten()
+                            .map(|s| ExportScoreResponse {
+                                user_id: s.user_id,
+                                player: GroupExport::player_name(&player_names, s.user_id),
+                                score: s.score,
+                                placement: s.placement,
+                                outcome: s.outcome,
+                                team: s.team,
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            exported_at: export.exported_at,
+            group: export.group.into(),
+            members,
+            games,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberCsvRow {
+    pub id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub role: GroupMemberRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameCsvRow {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub min_players_per_match: i32,
+    pub max_players_per_match: i32,
+    pub min_players_per_team: i32,
+    pub max_players_per_team: i32,
+    pub metric: ScoringMetric,
+    pub score_direction: ScoreDirection,
+    pub result_type: MatchResultType,
+    pub season_duration_unit: Option<&'static str>,
+    pub season_duration_value: Option<i32>,
+    pub season_alignment: SeasonAlignment,
+    pub season_timezone: String,
+    pub star_threshold: Option<i32>,
+    pub gold_threshold: Option<i32>,
+    pub silver_threshold: Option<i32>,
+    pub bronze_threshold: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeasonCsvRow {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub number: i32,
+    pub name: Option<String>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchCsvRow {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub season_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub recorded_by: Uuid,
+}
+
+/// A player's result, with the columns a match import expects
+#[derive(Debug, Serialize)]
+pub struct ScoreCsvRow {
+    #[serde(rename = "match")]
+    pub match_id: Uuid,
+    pub game_id: Uuid,
+    pub date: DateTime<Utc>,
+    pub player: String,
+    pub user_id: Uuid,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+}
+
+impl GroupExport {
+    pub fn member_rows(&self) -> Vec<MemberCsvRow> {
+        self.members
+            .iter()
+            .map(|m| MemberCsvRow {
+                id: m.id,
+                name: m.name.clone(),
+                email: self.member_email(m),
+                role: m.role,
+                joined_at: m.joined_at,
+            })
+            .collect()
+    }
+
+    pub fn game_rows(&self) -> Vec<GameCsvRow> {
+        self.games
+            .iter()
+            .map(|g| {
+                let (season_duration_unit, season_duration_value) = match g.season_duration {
+                    Some(Interval::Months(value)) => (Some("months"), Some(value)),
+                    Some(Interval::Weeks(value)) => (Some("weeks"), Some(value)),
+                    Some(Interval::Days(value)) => (Some("days"), Some(value)),
+                    None => (None, None),
+                };
+
+                GameCsvRow {
+                    id: g.id,
+                    name: g.name.clone(),
+                    created_at: g.created_at,
+                    min_players_per_match: g.min_players_per_match,
+                    max_players_per_match: g.max_players_per_match,
+                    min_players_per_team: g.min_players_per_team,
+                    max_players_per_team: g.max_players_per_team,
+                    metric: g.metric,
+                    score_direction: g.score_direction,
+                    result_type: g.result_type,
+                    season_duration_unit,
+                    season_duration_value,
+                    season_alignment: g.season_alignment,
+                    season_timezone: g.season_timezone.clone(),
+                    star_threshold: g.star_threshold,
+                    gold_threshold: g.gold_threshold,
+                    silver_threshold: g.silver_threshold,
+                    bronze_threshold: g.bronze_threshold,
+                }
+            })
+            .collect()
+    }
+
+    pub fn season_rows(&self) -> Vec<SeasonCsvRow> {
+        self.seasons
+            .iter()
+            .map(|s| SeasonCsvRow {
+                id: s.id,
+                game_id: s.game_id,
+                number: s.number,
+                name: s.name.clone(),
+                start_date: s.start_date,
+                end_date: s.end_date,
+            })
+            .collect()
+    }
+
+    pub fn match_rows(&self) -> Vec<MatchCsvRow> {
+        self.matches
+            .iter()
+            .map(|m| MatchCsvRow {
+                id: m.id,
+                game_id: m.game_id,
+                season_id: m.season_id,
+                played_at: m.played_at,
+                recorded_by: m.recorded_by,
+            })
+            .collect()
+    }
+
+    pub fn score_rows(&self) -> Vec<ScoreCsvRow> {
+        let player_names = self.player_names();
+        let matches: HashMap<Uuid, &MatchDetailsDb> =
+            self.matches.iter().map(|m| (m.id, m)).collect();
+
+        self.scores
+            .iter()
+            .filter_map(|s| {
+                let game_match = matches.get(&s.match_id)?;
+
+                Some(ScoreCsvRow {
+                    match_id: s.match_id,
+                    game_id: game_match.game_id,
+                    date: game_match.played_at,
+                    player: Self::player_name(&player_names, s.user_id),
+                    user_id: s.user_id,
+                    score: s.score,
+                    placement: s.placement,
+                    outcome: s.outcome,
+                    team: s.team,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A group the user belongs to, for a personal data export
+#[derive(Debug, FromRow, Serialize)]
+pub struct MembershipExportDb {
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub role: GroupMemberRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// One of the user's results, for a personal data export
+#[derive(Debug, FromRow, Serialize)]
+pub struct UserScoreExportDb {
+    pub match_id: Uuid,
+    pub group_id: Uuid,
+    pub game_id: Uuid,
+    pub game_name: String,
+    pub season_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+}
+
+/// Everything stored about a user
+#[derive(Debug, Serialize)]
+pub struct PersonalDataResponse {
+    pub exported_at: DateTime<Utc>,
+    pub profile: UserResponse,
+    pub memberships: Vec<MembershipExportDb>,
+    pub scores: Vec<UserScoreExportDb>,
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, prelude::Type};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::user::{Avatar, AvatarColour};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "fixture_rsvp", rename_all = "snake_case")]
+pub enum FixtureRsvp {
+    /// Invited, but has not responded yet
+    Invited,
+    Going,
+    Maybe,
+    Declined,
+}
+
+#[derive(Debug, FromRow)]
+pub struct FixtureDb {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub scheduled_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub match_id: Option<Uuid>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct FixturePlayerDb {
+    pub fixture_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+    pub rsvp: FixtureRsvp,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct FixtureWithPlayers {
+    pub fixture: FixtureDb,
+    pub players: Vec<FixturePlayerDb>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateFixtureReq {
+    pub scheduled_at: DateTime<Utc>,
+
+    #[validate(length(max = 64, message = "Cannot invite more than 64 players"))]
+    #[serde(default)]
+    pub player_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RsvpReq {
+    pub rsvp: FixtureRsvp,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FixtureResponse {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub scheduled_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub match_id: Option<Uuid>,
+    pub players: Vec<FixturePlayerResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FixturePlayerResponse {
+    pub user_id: Uuid,
+    pub name: String,
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+    pub rsvp: FixtureRsvp,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+impl From<FixtureWithPlayers> for FixtureResponse {
+    fn from(value: FixtureWithPlayers) -> Self {
+        Self {
+            id: value.fixture.id,
+            game_id: value.fixture.game_id,
+            scheduled_at: value.fixture.scheduled_at,
+            created_by: value.fixture.created_by,
+            created_at: value.fixture.created_at,
+            match_id: value.fixture.match_id,
+            players: value
+                .players
+                .into_iter()
+                .map(|p| FixturePlayerResponse {
+                    user_id: p.user_id,
+                    name: p.name,
+                    avatar: p.avatar,
+                    avatar_colour: p.avatar_colour,
+                    rsvp: p.rsvp,
+                    responded_at: p.responded_at,
+                })
+                .collect(),
+        }
+    }
+}
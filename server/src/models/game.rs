@@ -1,17 +1,20 @@
-use crate::models::{season::SeasonResponse, trim_string};
+use crate::models::{season::SeasonResponse, trim_optional_string, trim_string};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{
-    Decode, Encode, FromRow, Postgres,
+    Decode, Encode, FromRow, Postgres, Row,
     encode::IsNull,
     error::BoxDynError,
-    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef, types::PgInterval},
+    postgres::{
+        PgArgumentBuffer, PgHasArrayType, PgRow, PgTypeInfo, PgValueRef, types::PgInterval,
+    },
     prelude::Type,
 };
+use std::cmp::Ordering;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameDb {
     pub id: Uuid,
     pub group_id: Uuid,
@@ -19,8 +22,14 @@ pub struct GameDb {
     pub created_at: DateTime<Utc>,
     pub min_players_per_match: i32,
     pub max_players_per_match: i32,
+    pub min_players_per_team: i32,
+    pub max_players_per_team: i32,
     pub metric: ScoringMetric,
+    pub score_direction: ScoreDirection,
+    pub result_type: MatchResultType,
     pub season_duration: Option<Interval>,
+    pub season_alignment: SeasonAlignment,
+    pub season_timezone: String,
 
     pub star_threshold: Option<i32>,
     pub gold_threshold: Option<i32>,
@@ -28,6 +37,59 @@ pub struct GameDb {
     pub bronze_threshold: Option<i32>,
 }
 
+impl<'r> FromRow<'r, PgRow> for GameDb {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        // Weeks are stored as days, so the unit they were given in is stored alongside
+        let season_duration = match (
+            row.try_get::<Option<Interval>, _>("season_duration")?,
+            row.try_get::<Option<IntervalUnit>, _>("season_duration_unit")?,
+        ) {
+            (Some(Interval::Days(days)), Some(IntervalUnit::Weeks)) => {
+                Some(Interval::Weeks(days / 7))
+            }
+            (season_duration, _) => season_duration,
+        };
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            group_id: row.try_get("group_id")?,
+            name: row.try_get("name")?,
+            created_at: row.try_get("created_at")?,
+            min_players_per_match: row.try_get("min_players_per_match")?,
+            max_players_per_match: row.try_get("max_players_per_match")?,
+            min_players_per_team: row.try_get("min_players_per_team")?,
+            max_players_per_team: row.try_get("max_players_per_team")?,
+            metric: row.try_get("metric")?,
+            score_direction: row.try_get("score_direction")?,
+            result_type: row.try_get("result_type")?,
+            season_duration,
+            season_alignment: row.try_get("season_alignment")?,
+            season_timezone: row.try_get("season_timezone")?,
+
+            star_threshold: row.try_get("star_threshold")?,
+            gold_threshold: row.try_get("gold_threshold")?,
+            silver_threshold: row.try_get("silver_threshold")?,
+            bronze_threshold: row.try_get("bronze_threshold")?,
+        })
+    }
+}
+
+/// Settings of a game that can be changed after it has been created
+#[derive(Debug)]
+pub struct GameSettings<'a> {
+    pub name: &'a str,
+    pub min_players_per_match: i32,
+    pub max_players_per_match: i32,
+    pub min_players_per_team: i32,
+    pub max_players_per_team: i32,
+    pub metric: ScoringMetric,
+    pub score_direction: ScoreDirection,
+    pub season_duration: Option<Interval>,
+    pub season_alignment: SeasonAlignment,
+    pub season_timezone: &'a str,
+    pub medal_scores: Option<GameMedals>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct GameResponse {
     pub id: Uuid,
@@ -36,8 +98,14 @@ pub struct GameResponse {
     pub created_at: DateTime<Utc>,
     pub min_players_per_match: i32,
     pub max_players_per_match: i32,
+    pub min_players_per_team: i32,
+    pub max_players_per_team: i32,
     pub metric: ScoringMetric,
+    pub score_direction: ScoreDirection,
+    pub result_type: MatchResultType,
     pub season_duration: Option<Interval>,
+    pub season_alignment: SeasonAlignment,
+    pub season_timezone: String,
 
     pub star_threshold: Option<i32>,
     pub gold_threshold: Option<i32>,
@@ -54,8 +122,14 @@ impl From<GameDb> for GameResponse {
             created_at: game.created_at,
             min_players_per_match: game.min_players_per_match,
             max_players_per_match: game.max_players_per_match,
+            min_players_per_team: game.min_players_per_team,
+            max_players_per_team: game.max_players_per_team,
             metric: game.metric,
+            score_direction: game.score_direction,
+            result_type: game.result_type,
             season_duration: game.season_duration,
+            season_alignment: game.season_alignment,
+            season_timezone: game.season_timezone,
 
             star_threshold: game.star_threshold,
             gold_threshold: game.gold_threshold,
@@ -76,6 +150,52 @@ pub struct SeasonsResponse {
 pub enum ScoringMetric {
     WinRate,
     AverageScore,
+    Rating,
+}
+
+/// What is recorded for each player in a match
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "match_result_type", rename_all = "snake_case")]
+pub enum MatchResultType {
+    #[default]
+    Score,
+    Placement,
+    WinLoss,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "score_direction", rename_all = "snake_case")]
+pub enum ScoreDirection {
+    #[default]
+    HigherIsBetter,
+    LowerIsBetter,
+}
+
+impl ScoreDirection {
+    /// Compares two scores, where `Ordering::Greater` means `a` is the better score
+    pub fn compare(&self, a: f64, b: f64) -> Ordering {
+        match self {
+            Self::HigherIsBetter => a.total_cmp(&b),
+            Self::LowerIsBetter => b.total_cmp(&a),
+        }
+    }
+
+    pub fn best(&self, a: i32, b: i32) -> i32 {
+        match self {
+            Self::HigherIsBetter => a.max(b),
+            Self::LowerIsBetter => a.min(b),
+        }
+    }
+
+    /// Whether the score is at least as good as the threshold
+    pub fn meets(&self, score: i32, threshold: i32) -> bool {
+        match self {
+            Self::HigherIsBetter => score >= threshold,
+            Self::LowerIsBetter => score <= threshold,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
@@ -84,6 +204,7 @@ pub enum ScoringMetric {
 pub enum OrderBy {
     WinRate,
     AverageScore,
+    Rating,
     Name,
 }
 
@@ -92,6 +213,7 @@ impl From<ScoringMetric> for OrderBy {
         match value {
             ScoringMetric::WinRate => OrderBy::WinRate,
             ScoringMetric::AverageScore => OrderBy::AverageScore,
+            ScoringMetric::Rating => OrderBy::Rating,
         }
     }
 }
@@ -104,6 +226,15 @@ pub struct GameMedals {
     pub bronze: Option<i32>,
 }
 
+// Games are individual unless configured otherwise
+fn default_team_size() -> i32 {
+    1
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateGameReq {
     #[validate(length(min = 3, max = 50, message = "Name must be between 3 and 50 chars"))]
@@ -124,8 +255,37 @@ pub struct CreateGameReq {
     ))]
     pub max_players_per_match: i32,
 
+    #[validate(range(
+        min = 1,
+        max = 50,
+        message = "Min number of players per team must be between 1 and 50"
+    ))]
+    #[serde(default = "default_team_size")]
+    pub min_players_per_team: i32,
+
+    #[validate(range(
+        min = 1,
+        max = 50,
+        message = "Max number of players per team must be between 1 and 50"
+    ))]
+    #[serde(default = "default_team_size")]
+    pub max_players_per_team: i32,
+
     pub metric: ScoringMetric,
+    #[serde(default)]
+    pub score_direction: ScoreDirection,
+    #[serde(default)]
+    pub result_type: MatchResultType,
     pub season_duration: Option<Interval>,
+    #[serde(default)]
+    pub season_alignment: SeasonAlignment,
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Time zone must be between 1 and 64 chars"
+    ))]
+    #[serde(default = "default_timezone", deserialize_with = "trim_string")]
+    pub season_timezone: String,
     pub medal_scores: Option<GameMedals>,
 }
 
@@ -149,19 +309,110 @@ pub struct UpdateGameReq {
     ))]
     pub max_players_per_match: i32,
 
+    /// Kept as it is when missing
+    #[validate(range(
+        min = 1,
+        max = 50,
+        message = "Min number of players per team must be between 1 and 50"
+    ))]
+    pub min_players_per_team: Option<i32>,
+
+    /// Kept as it is when missing
+    #[validate(range(
+        min = 1,
+        max = 50,
+        message = "Max number of players per team must be between 1 and 50"
+    ))]
+    pub max_players_per_team: Option<i32>,
+
     pub metric: ScoringMetric,
+
+    /// Kept as it is when missing
+    pub score_direction: Option<ScoreDirection>,
     pub season_duration: Option<Interval>,
+
+    /// Kept as it is when missing
+    pub season_alignment: Option<SeasonAlignment>,
+
+    /// Kept as it is when missing
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Time zone must be between 1 and 64 chars"
+    ))]
+    #[serde(default, deserialize_with = "trim_optional_string")]
+    pub season_timezone: Option<String>,
     pub next_season_start: Option<DateTime<Utc>>,
     pub medal_scores: Option<GameMedals>,
 }
 
+impl UpdateGameReq {
+    /// The game's new settings, keeping what it already has for anything left out
+    pub fn settings<'a>(&'a self, game: &'a GameDb) -> GameSettings<'a> {
+        GameSettings {
+            name: &self.name,
+            min_players_per_match: self.min_players_per_match,
+            max_players_per_match: self.max_players_per_match,
+            min_players_per_team: self
+                .min_players_per_team
+                .unwrap_or(game.min_players_per_team),
+            max_players_per_team: self
+                .max_players_per_team
+                .unwrap_or(game.max_players_per_team),
+            metric: self.metric,
+            score_direction: self.score_direction.unwrap_or(game.score_direction),
+            season_duration: self.season_duration,
+            season_alignment: self.season_alignment.unwrap_or(game.season_alignment),
+            season_timezone: self
+                .season_timezone
+                .as_deref()
+                .unwrap_or(&game.season_timezone),
+            medal_scores: self.medal_scores,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "unit", content = "value")]
 pub enum Interval {
     Months(i32),
+    Weeks(i32),
     Days(i32),
 }
 
+impl Interval {
+    pub fn unit(&self) -> IntervalUnit {
+        match self {
+            Self::Months(_) => IntervalUnit::Months,
+            Self::Weeks(_) => IntervalUnit::Weeks,
+            Self::Days(_) => IntervalUnit::Days,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Type, PartialEq, Eq)]
+#[sqlx(type_name = "interval_unit", rename_all = "snake_case")]
+pub enum IntervalUnit {
+    Months,
+    Weeks,
+    Days,
+}
+
+/// Calendar boundary that season end dates are snapped to, in the game's time zone
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "season_alignment", rename_all = "snake_case")]
+pub enum SeasonAlignment {
+    #[default]
+    None,
+    /// First of the month
+    Month,
+    /// Monday
+    IsoWeek,
+    /// First of January, April, July or October
+    Quarter,
+}
+
 impl Type<Postgres> for Interval {
     fn type_info() -> PgTypeInfo {
         PgInterval::type_info()
@@ -183,6 +434,7 @@ impl<'de> Decode<'de, Postgres> for Interval {
             return Ok(Interval::Months(pg_interval.months));
         }
 
+        // Weeks are read back as days, unless told otherwise by the unit stored with them
         Ok(Interval::Days(pg_interval.days))
     }
 }
@@ -195,6 +447,11 @@ impl Encode<'_, Postgres> for Interval {
                 days: 0,
                 microseconds: 0,
             },
+            Self::Weeks(weeks) => PgInterval {
+                months: 0,
+                days: weeks * 7,
+                microseconds: 0,
+            },
             Self::Days(days) => PgInterval {
                 months: 0,
                 days: *days,
@@ -209,3 +466,75 @@ impl Encode<'_, Postgres> for Interval {
         2 * std::mem::size_of::<i64>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> GameDb {
+        GameDb {
+            id: Uuid::new_v4(),
+            group_id: Uuid::new_v4(),
+            name: "game".to_string(),
+            created_at: Utc::now(),
+            min_players_per_match: 2,
+            max_players_per_match: 4,
+            min_players_per_team: 2,
+            max_players_per_team: 2,
+            metric: ScoringMetric::AverageScore,
+            score_direction: ScoreDirection::LowerIsBetter,
+            result_type: MatchResultType::Score,
+            season_duration: Some(Interval::Weeks(1)),
+            season_alignment: SeasonAlignment::IsoWeek,
+            season_timezone: "Europe/London".to_string(),
+            star_threshold: None,
+            gold_threshold: None,
+            silver_threshold: None,
+            bronze_threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_update_keeps_settings_left_out() {
+        let game = game();
+        let payload: UpdateGameReq = serde_json::from_str(
+            r#"{
+                "name": "renamed",
+                "min_players_per_match": 2,
+                "max_players_per_match": 4,
+                "metric": "average_score",
+                "season_duration": { "unit": "weeks", "value": 1 }
+            }"#,
+        )
+        .unwrap();
+
+        let settings = payload.settings(&game);
+        assert_eq!(settings.name, "renamed");
+        assert_eq!(settings.min_players_per_team, 2);
+        assert_eq!(settings.max_players_per_team, 2);
+        assert_eq!(settings.score_direction, ScoreDirection::LowerIsBetter);
+        assert_eq!(settings.season_alignment, SeasonAlignment::IsoWeek);
+        assert_eq!(settings.season_timezone, "Europe/London");
+    }
+
+    #[test]
+    fn test_update_replaces_settings_given() {
+        let game = game();
+        let payload: UpdateGameReq = serde_json::from_str(
+            r#"{
+                "name": "game",
+                "min_players_per_match": 2,
+                "max_players_per_match": 4,
+                "metric": "average_score",
+                "season_duration": { "unit": "months", "value": 1 },
+                "season_alignment": "month",
+                "season_timezone": " UTC "
+            }"#,
+        )
+        .unwrap();
+
+        let settings = payload.settings(&game);
+        assert_eq!(settings.season_alignment, SeasonAlignment::Month);
+        assert_eq!(settings.season_timezone, "UTC");
+    }
+}
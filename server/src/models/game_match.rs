@@ -1,13 +1,19 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::{FromRow, prelude::Type};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::{
+    stats::SeasonScope,
+    user::{Avatar, AvatarColour, PublicUserDetailsResponse},
+};
+
 #[derive(Debug)]
 pub struct MatchDb {
     pub id: Uuid,
     pub game_id: Uuid,
+    pub season_id: Uuid,
     pub played_at: DateTime<Utc>,
     pub scores: Vec<MatchScoreDb>,
 }
@@ -16,25 +22,91 @@ pub struct MatchDb {
 pub struct MatchDetailsDb {
     pub id: Uuid,
     pub game_id: Uuid,
+    pub season_id: Uuid,
+    pub recorded_by: Uuid,
+    pub played_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MatchSummaryDb {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub season_id: Uuid,
     pub played_at: DateTime<Utc>,
+    pub recorded_by: Uuid,
+    pub recorded_by_name: String,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MatchLeaderboardEntryDb {
+    pub match_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+    pub rank: i64,
+}
+
+#[derive(Debug)]
+pub struct MatchWithLeaderboard {
+    pub details: MatchSummaryDb,
+    pub scores: Vec<MatchLeaderboardEntryDb>,
 }
 
 #[derive(Debug, FromRow)]
 pub struct MatchScoreDb {
     pub user_id: Uuid,
-    pub score: i32,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[sqlx(type_name = "match_outcome", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MatchOutcome {
+    Win,
+    Draw,
+    Loss,
 }
 
 #[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct CreateMatchReq {
     #[validate(length(min = 1, message = "There must be at least one score"))]
+    #[validate(nested)]
+    pub scores: Vec<CreateMatchScoreReq>,
+
+    /// When the match was played, for results entered after the fact. Defaults to now
+    pub played_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct UpdateMatchReq {
+    #[validate(length(min = 1, message = "There must be at least one score"))]
+    #[validate(nested)]
     pub scores: Vec<CreateMatchScoreReq>,
+
+    /// Moves the match to when it was actually played, e.g. for results entered after the fact
+    pub played_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct CreateMatchScoreReq {
     pub user_id: Uuid,
-    pub score: i32,
+
+    // Only the one matching the game's result type should be provided
+    pub score: Option<i32>,
+    #[validate(range(min = 1, message = "Placement must be at least 1"))]
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+
+    // Players with the same team share its result and rank
+    pub team: Option<i32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -48,7 +120,10 @@ pub struct MatchResponse {
 #[derive(Debug, Serialize)]
 pub struct MatchScoreResponse {
     pub user_id: Uuid,
-    pub score: i32,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
 }
 
 impl From<MatchDb> for MatchResponse {
@@ -63,6 +138,240 @@ impl From<MatchDb> for MatchResponse {
                 .map(|s| MatchScoreResponse {
                     user_id: s.user_id,
                     score: s.score,
+                    placement: s.placement,
+                    outcome: s.outcome,
+                    team: s.team,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchDetailResponse {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub season_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub recorded_by: PublicUserDetailsResponse,
+    pub scores: Vec<MatchLeaderboardEntryResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchLeaderboardEntryResponse {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub user_avatar: Avatar,
+    pub user_avatar_colour: AvatarColour,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+    pub rank: i64,
+}
+
+impl From<MatchWithLeaderboard> for MatchDetailResponse {
+    fn from(game_match: MatchWithLeaderboard) -> Self {
+        Self {
+            id: game_match.details.id,
+            game_id: game_match.details.game_id,
+            season_id: game_match.details.season_id,
+            played_at: game_match.details.played_at,
+            recorded_by: PublicUserDetailsResponse {
+                id: game_match.details.recorded_by,
+                name: game_match.details.recorded_by_name,
+            },
+            scores: game_match
+                .scores
+                .into_iter()
+                .map(|s| MatchLeaderboardEntryResponse {
+                    user_id: s.user_id,
+                    user_name: s.name,
+                    user_avatar: s.avatar,
+                    user_avatar_colour: s.avatar_colour,
+                    score: s.score,
+                    placement: s.placement,
+                    outcome: s.outcome,
+                    team: s.team,
+                    rank: s.rank,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchListResponse {
+    pub matches: Vec<MatchDetailResponse>,
+    pub next_cursor: Option<MatchCursor>,
+}
+
+#[derive(Deserialize)]
+pub struct MatchListParams {
+    pub season: Option<SeasonScope>,
+    pub player: Option<Uuid>,
+    pub cursor: Option<MatchCursor>,
+    pub limit: Option<i64>,
+}
+
+/// Position in a list of matches ordered by `played_at`, with the ID breaking ties.
+/// Represented as `<played_at in microseconds>_<match id>`
+#[derive(Debug, Clone, Copy)]
+pub struct MatchCursor {
+    pub played_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Serialize for MatchCursor {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!(
+            "{}_{}",
+            self.played_at.timestamp_micros(),
+            self.id
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for MatchCursor {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        let (micros, id) = s
+            .split_once('_')
+            .ok_or_else(|| serde::de::Error::custom("Invalid cursor"))?;
+
+        let played_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(|| serde::de::Error::custom("Invalid cursor"))?;
+
+        let id = Uuid::parse_str(id).map_err(serde::de::Error::custom)?;
+
+        Ok(Self { played_at, id })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[sqlx(type_name = "match_revision_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MatchRevisionAction {
+    Update,
+    Delete,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MatchRevisionDb {
+    pub id: Uuid,
+    pub match_id: Uuid,
+    pub game_id: Uuid,
+    pub action: MatchRevisionAction,
+    pub changed_by: Uuid,
+    pub changed_by_name: String,
+    pub changed_at: DateTime<Utc>,
+
+    // Only set when the match was moved to a different time
+    pub previous_played_at: Option<DateTime<Utc>>,
+    pub new_played_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MatchRevisionScoreDb {
+    pub revision_id: Uuid,
+    pub user_id: Uuid,
+    pub previous_score: Option<i32>,
+    pub new_score: Option<i32>,
+    pub previous_placement: Option<i32>,
+    pub new_placement: Option<i32>,
+    pub previous_outcome: Option<MatchOutcome>,
+    pub new_outcome: Option<MatchOutcome>,
+    pub previous_team: Option<i32>,
+    pub new_team: Option<i32>,
+}
+
+#[derive(Debug)]
+pub struct MatchScoreChange {
+    pub user_id: Uuid,
+    pub previous_score: Option<i32>,
+    pub new_score: Option<i32>,
+    pub previous_placement: Option<i32>,
+    pub new_placement: Option<i32>,
+    pub previous_outcome: Option<MatchOutcome>,
+    pub new_outcome: Option<MatchOutcome>,
+    pub previous_team: Option<i32>,
+    pub new_team: Option<i32>,
+}
+
+/// Everything that changed in a revision of a match
+#[derive(Debug)]
+pub struct MatchChanges {
+    pub scores: Vec<MatchScoreChange>,
+
+    /// Previous and new time the match was played, if it was moved
+    pub played_at: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl MatchChanges {
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty() && self.played_at.is_none()
+    }
+}
+
+#[derive(Debug)]
+pub struct MatchRevisionWithScores {
+    pub revision: MatchRevisionDb,
+    pub scores: Vec<MatchRevisionScoreDb>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchRevisionResponse {
+    pub id: Uuid,
+    pub match_id: Uuid,
+    pub action: MatchRevisionAction,
+    pub changed_by: Uuid,
+    pub changed_by_name: String,
+    pub changed_at: DateTime<Utc>,
+    pub previous_played_at: Option<DateTime<Utc>>,
+    pub new_played_at: Option<DateTime<Utc>>,
+    pub scores: Vec<MatchRevisionScoreResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchRevisionScoreResponse {
+    pub user_id: Uuid,
+    pub previous_score: Option<i32>,
+    pub new_score: Option<i32>,
+    pub previous_placement: Option<i32>,
+    pub new_placement: Option<i32>,
+    pub previous_outcome: Option<MatchOutcome>,
+    pub new_outcome: Option<MatchOutcome>,
+    pub previous_team: Option<i32>,
+    pub new_team: Option<i32>,
+}
+
+impl From<MatchRevisionWithScores> for MatchRevisionResponse {
+    fn from(value: MatchRevisionWithScores) -> Self {
+        Self {
+            id: value.revision.id,
+            match_id: value.revision.match_id,
+            action: value.revision.action,
+            changed_by: value.revision.changed_by,
+            changed_by_name: value.revision.changed_by_name,
+            changed_at: value.revision.changed_at,
+            previous_played_at: value.revision.previous_played_at,
+            new_played_at: value.revision.new_played_at,
+            scores: value
+                .scores
+                .into_iter()
+                .map(|s| MatchRevisionScoreResponse {
+                    user_id: s.user_id,
+                    previous_score: s.previous_score,
+                    new_score: s.new_score,
+                    previous_placement: s.previous_placement,
+                    new_placement: s.new_placement,
+                    previous_outcome: s.previous_outcome,
+                    new_outcome: s.new_outcome,
+                    previous_team: s.previous_team,
+                    new_team: s.new_team,
                 })
                 .collect(),
         }
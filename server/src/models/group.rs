@@ -53,6 +53,8 @@ pub struct GroupMemberDetailsDb {
 #[sqlx(type_name = "user_role", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
 pub enum GroupMemberRole {
+    /// A player without an account, who can be scored but can't sign in
+    Guest,
     Viewer,
     Member,
     Admin,
@@ -70,12 +72,27 @@ impl GroupMemberRole {
             (GroupMemberRole::Admin, GroupAction::DeleteInvite) => true,
             (GroupMemberRole::Admin, GroupAction::ViewInvites) => true,
             (GroupMemberRole::Admin, GroupAction::CreateMatch) => true,
+            (GroupMemberRole::Admin, GroupAction::UpdateMatch) => true,
+            (GroupMemberRole::Admin, GroupAction::DeleteMatch) => true,
+            (GroupMemberRole::Admin, GroupAction::ViewMatchRevisions) => true,
+            (GroupMemberRole::Admin, GroupAction::ImportMatches) => true,
             (GroupMemberRole::Admin, GroupAction::CreateGame) => true,
             (GroupMemberRole::Admin, GroupAction::UpdateGame) => true,
             (GroupMemberRole::Admin, GroupAction::DeleteGame) => true,
+            (GroupMemberRole::Admin, GroupAction::StartSeason) => true,
+            (GroupMemberRole::Admin, GroupAction::EndSeason) => true,
+            (GroupMemberRole::Admin, GroupAction::UpdateSeason) => true,
+            (GroupMemberRole::Admin, GroupAction::CreateTournament) => true,
+            (GroupMemberRole::Admin, GroupAction::DeleteTournament) => true,
+            (GroupMemberRole::Admin, GroupAction::CreateFixture) => true,
+            (GroupMemberRole::Admin, GroupAction::DeleteFixture) => true,
+            (GroupMemberRole::Admin, GroupAction::RsvpFixture) => true,
             (GroupMemberRole::Admin, GroupAction::UpdateGroup) => true,
             (GroupMemberRole::Admin, GroupAction::RemoveMember(GroupMemberRole::Member)) => true,
             (GroupMemberRole::Admin, GroupAction::RemoveMember(GroupMemberRole::Viewer)) => true,
+            (GroupMemberRole::Admin, GroupAction::RemoveMember(GroupMemberRole::Guest)) => true,
+            (GroupMemberRole::Admin, GroupAction::CreateGuest) => true,
+            (GroupMemberRole::Admin, GroupAction::MergeMembers) => true,
             (GroupMemberRole::Admin, GroupAction::ViewEmails) => true,
 
             // Admins can update roles with restrictions:
@@ -92,11 +109,19 @@ impl GroupMemberRole {
 
             // Member actions
             (GroupMemberRole::Member, GroupAction::CreateMatch) => true,
+            (GroupMemberRole::Member, GroupAction::CreateFixture) => true,
+            (GroupMemberRole::Member, GroupAction::RsvpFixture) => true,
+            (GroupMemberRole::Member, GroupAction::CreateGuest) => true,
 
             // Deny everything else
             _ => false,
         }
     }
+
+    /// Guests have no account to sign in with, so can't be handed a group
+    pub fn can_own_group(&self) -> bool {
+        *self != GroupMemberRole::Guest
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -157,7 +182,9 @@ pub struct GroupMemberResponse {
 
 impl GroupMemberResponse {
     pub fn from_db(member: GroupMemberDetailsDb, viewer_role: GroupMemberRole) -> Self {
-        let show_email = viewer_role.can_perform(GroupAction::ViewEmails);
+        // Guests don't have a real email
+        let show_email = viewer_role.can_perform(GroupAction::ViewEmails)
+            && member.role != GroupMemberRole::Guest;
 
         Self {
             id: member.id,
@@ -194,6 +221,42 @@ pub struct SetRoleReq {
     pub role: GroupMemberRole,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateGuestReq {
+    #[validate(length(min = 1, max = 512, message = "Name must be between 1 and 512 chars"))]
+    #[serde(deserialize_with = "trim_string")]
+    pub name: String,
+
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferOwnershipReq {
+    pub user_id: Uuid,
+}
+
+/// Which result is kept for a match both merged members played in
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeConflict {
+    /// Keep the result of the member being merged into
+    #[default]
+    KeepTarget,
+
+    /// Keep the result of the member being merged away
+    KeepSource,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeMemberReq {
+    /// The member whose results are kept, and who stays in the group
+    pub into_user_id: Uuid,
+
+    #[serde(default)]
+    pub conflicts: MergeConflict,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderBy {
@@ -221,6 +284,7 @@ mod tests {
         // Owners should be able to do anything, even destructive group actions
         assert!(role.can_perform(GroupAction::DeleteGroup));
         assert!(role.can_perform(GroupAction::UpdateRole(Admin, Owner)));
+        assert!(role.can_perform(GroupAction::TransferOwnership));
     }
 
     #[test]
@@ -229,7 +293,14 @@ mod tests {
 
         // Valid Admin actions
         assert!(admin.can_perform(GroupAction::CreateInvite));
+        assert!(admin.can_perform(GroupAction::UpdateMatch));
+        assert!(admin.can_perform(GroupAction::DeleteMatch));
+        assert!(admin.can_perform(GroupAction::ViewMatchRevisions));
         assert!(admin.can_perform(GroupAction::RemoveMember(Member)));
+        assert!(admin.can_perform(GroupAction::StartSeason));
+        assert!(admin.can_perform(GroupAction::EndSeason));
+        assert!(admin.can_perform(GroupAction::UpdateSeason));
+        assert!(admin.can_perform(GroupAction::CreateTournament));
 
         // Admins CANNOT promote someone to Owner
         assert!(!admin.can_perform(GroupAction::UpdateRole(Member, Owner)));
@@ -238,8 +309,9 @@ mod tests {
         assert!(!admin.can_perform(GroupAction::UpdateRole(Admin, Member)));
         assert!(!admin.can_perform(GroupAction::RemoveMember(Admin)));
 
-        // Admins CANNOT delete the group
+        // Admins CANNOT delete or take over the group
         assert!(!admin.can_perform(GroupAction::DeleteGroup));
+        assert!(!admin.can_perform(GroupAction::TransferOwnership));
     }
 
     #[test]
@@ -250,8 +322,29 @@ mod tests {
 
         // Members cannot do admin stuff
         assert!(!member.can_perform(GroupAction::CreateGame));
+        assert!(!member.can_perform(GroupAction::UpdateMatch));
+        assert!(!member.can_perform(GroupAction::DeleteMatch));
         assert!(!member.can_perform(GroupAction::CreateInvite));
         assert!(!member.can_perform(GroupAction::RemoveMember(Viewer)));
+        assert!(!member.can_perform(GroupAction::StartSeason));
+        assert!(!member.can_perform(GroupAction::EndSeason));
+        assert!(!member.can_perform(GroupAction::UpdateSeason));
+        assert!(!member.can_perform(GroupAction::CreateTournament));
+    }
+
+    #[test]
+    fn test_guests_cannot_do_anything() {
+        assert!(!Guest.can_perform(GroupAction::CreateMatch));
+        assert!(!Guest.can_perform(GroupAction::RsvpFixture));
+        assert!(!Guest.can_perform(GroupAction::CreateGuest));
+        assert!(!Guest.can_perform(GroupAction::ViewEmails));
+
+        // Anyone who can record matches can add a guest to them
+        assert!(Member.can_perform(GroupAction::CreateGuest));
+        assert!(!Viewer.can_perform(GroupAction::CreateGuest));
+        assert!(Admin.can_perform(GroupAction::RemoveMember(Guest)));
+        assert!(Admin.can_perform(GroupAction::MergeMembers));
+        assert!(!Member.can_perform(GroupAction::MergeMembers));
     }
 
     #[test]
@@ -334,4 +427,13 @@ mod tests {
         assert!(!member.can_perform(GroupAction::CreateGame));
         assert!(!member.can_perform(GroupAction::DeleteInvite));
     }
+
+    #[test]
+    fn test_guests_cannot_own_groups() {
+        assert!(!Guest.can_own_group());
+
+        for role in [Viewer, Member, Admin, Owner] {
+            assert!(role.can_own_group());
+        }
+    }
 }
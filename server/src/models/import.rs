@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::game_match::MatchOutcome;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportParams {
+    /// Checks the import without saving anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A match in a JSON import
+#[derive(Debug, Deserialize)]
+pub struct ImportMatchReq {
+    pub played_at: String,
+    pub scores: Vec<ImportScoreReq>,
+}
+
+/// A player's result in a JSON import, or a row of a CSV import. Players are given by email or name
+#[derive(Debug, Deserialize)]
+pub struct ImportScoreReq {
+    pub player: String,
+    pub score: Option<i32>,
+    pub placement: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
+    pub team: Option<i32>,
+}
+
+/// A match read from an import, with the row (or position in a JSON array) it started on
+#[derive(Debug)]
+pub struct ImportMatch {
+    pub row: usize,
+    pub played_at: String,
+    pub scores: Vec<ImportScoreReq>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub imported: bool,
+
+    /// Number of matches that were (or could be) imported
+    pub matches: usize,
+
+    /// Nothing is imported unless this is empty
+    pub errors: Vec<ImportRowError>,
+}
+
+impl ImportFormat {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+
+        match mime {
+            "text/csv" => Some(Self::Csv),
+            "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
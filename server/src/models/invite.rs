@@ -19,6 +19,9 @@ pub struct InviteDb {
     pub email_whitelist: Vec<String>,
     pub role: GroupMemberRole,
 
+    /// The guest whose place goes to whoever accepts
+    pub guest_id: Option<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
 }
@@ -38,6 +41,9 @@ pub struct InviteWithCreatedByNameDb {
     pub email_whitelist: Vec<String>,
     pub role: GroupMemberRole,
 
+    /// The guest whose place goes to whoever accepts
+    pub guest_id: Option<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
 }
@@ -54,6 +60,9 @@ pub struct InviteSummaryResponse {
     pub email_whitelist: Vec<String>,
     pub role: GroupMemberRole,
 
+    /// The guest whose place goes to whoever accepts
+    pub guest_id: Option<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
 }
@@ -71,6 +80,8 @@ impl From<InviteWithCreatedByNameDb> for InviteSummaryResponse {
             email_whitelist: invite.email_whitelist,
             role: invite.role,
 
+            guest_id: invite.guest_id,
+
             created_at: invite.created_at,
             expires_at: invite.expires_at,
         }
@@ -89,6 +100,9 @@ pub struct InviteBasicResponse {
     pub email_whitelist: Vec<String>,
     pub role: GroupMemberRole,
 
+    /// The guest whose place goes to whoever accepts
+    pub guest_id: Option<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
 }
@@ -106,6 +120,8 @@ impl From<InviteDb> for InviteBasicResponse {
             email_whitelist: invite.email_whitelist,
             role: invite.role,
 
+            guest_id: invite.guest_id,
+
             created_at: invite.created_at,
             expires_at: invite.expires_at,
         }
@@ -122,6 +138,9 @@ pub struct InviteDetailResponse {
     pub group_name: String,
     pub role: GroupMemberRole,
 
+    /// The guest whose results the user will take over
+    pub guest_name: Option<String>,
+
     pub is_current_user_member: bool,
 }
 
@@ -148,4 +167,7 @@ pub struct CreateInviteReq {
 
     #[validate(nested)]
     pub email_whitelist: Vec<ValidEmail>,
+
+    /// Lets whoever accepts claim a guest's place and results
+    pub guest_id: Option<Uuid>,
 }
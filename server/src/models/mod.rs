@@ -1,10 +1,15 @@
+pub mod activity;
 pub mod auth;
+pub mod export;
+pub mod fixture;
 pub mod game;
 pub mod game_match;
 pub mod group;
+pub mod import;
 pub mod invite;
 pub mod season;
 pub mod stats;
+pub mod tournament;
 pub mod user;
 pub mod vitals;
 
@@ -17,3 +22,12 @@ where
     let s = String::deserialize(deserializer)?;
     Ok(s.trim().to_string())
 }
+
+/// Trims an optional string, treating blank strings as missing
+fn trim_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = Option::<String>::deserialize(deserializer)?;
+    Ok(s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()))
+}
@@ -2,6 +2,13 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    stats::HighlightsResponse,
+    trim_optional_string,
+    user::{Avatar, AvatarColour},
+};
 
 #[derive(Clone, Debug, FromRow, Serialize, Deserialize)]
 pub struct SeasonDb {
@@ -35,3 +42,170 @@ impl From<SeasonDb> for SeasonResponse {
         }
     }
 }
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct StartSeasonReq {
+    #[validate(length(min = 1, max = 50, message = "Name must be between 1 and 50 chars"))]
+    #[serde(default, deserialize_with = "trim_optional_string")]
+    pub name: Option<String>,
+
+    /// Defaults to the game's season duration from now
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateSeasonReq {
+    /// Kept as it is when missing
+    #[validate(length(min = 1, max = 50, message = "Name must be between 1 and 50 chars"))]
+    #[serde(default, deserialize_with = "trim_optional_string")]
+    pub name: Option<String>,
+
+    pub start_date: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct SeasonResultDb {
+    pub season_id: Uuid,
+    pub champion_id: Option<Uuid>,
+    pub finalised_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct SeasonResultEntryDb {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub user_avatar: Avatar,
+    pub user_avatar_colour: AvatarColour,
+    pub rank: i32,
+    pub matches_played: i64,
+    pub wins: i64,
+    pub win_rate: f64,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i32>,
+    pub rating: f64,
+    pub star_medals: i32,
+    pub gold_medals: i32,
+    pub silver_medals: i32,
+    pub bronze_medals: i32,
+}
+
+#[derive(Debug)]
+pub struct SeasonResults {
+    pub season: SeasonDb,
+    pub result: SeasonResultDb,
+    pub entries: Vec<SeasonResultEntryDb>,
+    pub highlights: HighlightsResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeasonResultsResponse {
+    pub season: SeasonResponse,
+    pub champion_id: Option<Uuid>,
+    pub finalised_at: DateTime<Utc>,
+    pub entries: Vec<SeasonResultEntryResponse>,
+    pub highlights: HighlightsResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeasonResultEntryResponse {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub user_avatar: Avatar,
+    pub user_avatar_colour: AvatarColour,
+    pub rank: i32,
+    pub matches_played: i64,
+    pub wins: i64,
+    pub win_rate: f64,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i32>,
+    pub rating: f64,
+    pub star_medals: i32,
+    pub gold_medals: i32,
+    pub silver_medals: i32,
+    pub bronze_medals: i32,
+}
+
+impl From<SeasonResultEntryDb> for SeasonResultEntryResponse {
+    fn from(entry: SeasonResultEntryDb) -> Self {
+        Self {
+            user_id: entry.user_id,
+            user_name: entry.user_name,
+            user_avatar: entry.user_avatar,
+            user_avatar_colour: entry.user_avatar_colour,
+            rank: entry.rank,
+            matches_played: entry.matches_played,
+            wins: entry.wins,
+            win_rate: entry.win_rate,
+            average_score: entry.average_score,
+            best_score: entry.best_score,
+            rating: entry.rating,
+            star_medals: entry.star_medals,
+            gold_medals: entry.gold_medals,
+            silver_medals: entry.silver_medals,
+            bronze_medals: entry.bronze_medals,
+        }
+    }
+}
+
+impl From<SeasonResults> for SeasonResultsResponse {
+    fn from(results: SeasonResults) -> Self {
+        Self {
+            season: results.season.into(),
+            champion_id: results.result.champion_id,
+            finalised_at: results.result.finalised_at,
+            entries: results.entries.into_iter().map(|e| e.into()).collect(),
+            highlights: results.highlights,
+        }
+    }
+}
+
+/// A season the user finished as champion
+#[derive(Debug, FromRow)]
+pub struct TrophyDb {
+    pub season_id: Uuid,
+    pub season_number: i32,
+    pub season_name: Option<String>,
+    pub game_id: Uuid,
+    pub game_name: String,
+    pub group_id: Uuid,
+    pub finalised_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrophyResponse {
+    pub season_id: Uuid,
+    pub season_number: i32,
+    pub season_name: Option<String>,
+    pub game_id: Uuid,
+    pub game_name: String,
+    pub group_id: Uuid,
+    pub finalised_at: DateTime<Utc>,
+}
+
+impl From<TrophyDb> for TrophyResponse {
+    fn from(trophy: TrophyDb) -> Self {
+        Self {
+            season_id: trophy.season_id,
+            season_number: trophy.season_number,
+            season_name: trophy.season_name,
+            game_id: trophy.game_id,
+            game_name: trophy.game_name,
+            group_id: trophy.group_id,
+            finalised_at: trophy.finalised_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrophyCabinetResponse {
+    pub trophies: Vec<TrophyResponse>,
+}
+
+impl From<Vec<TrophyDb>> for TrophyCabinetResponse {
+    fn from(trophies: Vec<TrophyDb>) -> Self {
+        Self {
+            trophies: trophies.into_iter().map(|t| t.into()).collect(),
+        }
+    }
+}
@@ -5,9 +5,10 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    errors::AppError,
+    errors::{AppError, GameError},
     models::{
-        game::{GameDb, GameResponse, OrderBy},
+        game::{GameDb, GameResponse, OrderBy, ScoreDirection},
+        game_match::MatchOutcome,
         user::{Avatar, AvatarColour, UserDb},
     },
 };
@@ -19,7 +20,8 @@ pub struct RawMatchStats {
     pub avatar: Avatar,
     pub avatar_colour: AvatarColour,
     pub match_id: Uuid,
-    pub score: i32,
+    pub score: Option<i32>,
+    pub team: Option<i32>,
     pub played_at: chrono::DateTime<chrono::Utc>,
     pub rank_in_match: i64,
 }
@@ -27,9 +29,16 @@ pub struct RawMatchStats {
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
 pub struct PlayerMatchDb {
     pub match_id: Uuid,
-    pub score: i32,
+    pub score: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
     pub played_at: chrono::DateTime<chrono::Utc>,
     pub rank_in_match: i64,
+
+    // Calculated from all matches in the scope, rather than fetched
+    #[sqlx(skip)]
+    pub rating: f64,
+    #[sqlx(skip)]
+    pub rating_change: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,9 +59,12 @@ pub struct PlayerHistoryResponse {
 #[derive(Debug, Serialize)]
 pub struct PlayerMatchResponse {
     pub match_id: Uuid,
-    pub score: i32,
+    pub score: Option<i32>,
+    pub outcome: Option<MatchOutcome>,
     pub played_at: DateTime<Utc>,
     pub rank_in_match: i64,
+    pub rating: f64,
+    pub rating_change: f64,
 }
 
 impl From<PlayerMatchDb> for PlayerMatchResponse {
@@ -60,8 +72,11 @@ impl From<PlayerMatchDb> for PlayerMatchResponse {
         Self {
             match_id: stats.match_id,
             score: stats.score,
+            outcome: stats.outcome,
             played_at: stats.played_at,
             rank_in_match: stats.rank_in_match,
+            rating: stats.rating,
+            rating_change: stats.rating_change,
         }
     }
 }
@@ -73,10 +88,12 @@ pub struct ScoreboardEntry {
     pub user_avatar: Avatar,
     pub user_avatar_colour: AvatarColour,
     pub matches_played: i64,
-    pub average_score: f64,
-    pub best_score: i32,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i32>,
     pub wins: i64,
     pub win_rate: f64,
+    pub rating: f64,
+    pub current_streak: i64,
 
     pub star_medals: u32,
     pub gold_medals: u32,
@@ -86,8 +103,9 @@ pub struct ScoreboardEntry {
     pub rank: i32,
 
     pub rank_diff: i32,
-    pub average_score_diff: f64,
+    pub average_score_diff: Option<f64>,
     pub win_rate_diff: f64,
+    pub rating_diff: f64,
 }
 
 #[derive(Deserialize)]
@@ -97,6 +115,55 @@ pub struct ScoreboardParams {
     pub season: Option<SeasonScope>,
 }
 
+/// Scoreboard options for a live scoreboard, which can be sent along with each update
+#[derive(Deserialize)]
+pub struct LiveScoreboardParams {
+    pub order_by: Option<OrderBy>,
+    pub order_dir: Option<OrderDir>,
+    pub season: Option<SeasonScope>,
+
+    #[serde(default)]
+    pub include_scoreboard: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MatchChange {
+    Recorded,
+    Updated,
+    Deleted,
+}
+
+impl MatchChange {
+    /// Name of the event sent to live scoreboards
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            Self::Recorded => "match_recorded",
+            Self::Updated => "match_updated",
+            Self::Deleted => "match_deleted",
+        }
+    }
+}
+
+/// Published once a change to a match has been committed
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchChangedEvent {
+    #[serde(skip)]
+    pub change: MatchChange,
+
+    pub game_id: Uuid,
+    pub match_id: Uuid,
+    pub season_id: Uuid,
+}
+
+#[derive(Serialize)]
+pub struct LiveScoreboardEvent {
+    #[serde(flatten)]
+    pub event: MatchChangedEvent,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scoreboard: Option<ScoreboardResponse>,
+}
+
 #[derive(Debug)]
 pub enum SeasonScope {
     All,
@@ -118,7 +185,10 @@ impl SeasonScope {
                     .season_repo
                     .get_latest(&mut tx, game_id)
                     .await
-                    .expect("Encountered a game without a season");
+                    .map_err(|e| match e {
+                        sqlx::Error::RowNotFound => GameError::SeasonNotFound,
+                        e => GameError::Database(e),
+                    })?;
                 tx.commit().await?;
 
                 Ok(Some(season.id))
@@ -182,9 +252,11 @@ pub struct ScoreboardEntryResponse {
     pub user_avatar: Avatar,
     pub user_avatar_colour: AvatarColour,
     pub matches_played: i64,
-    pub average_score: f64,
+    pub average_score: Option<f64>,
     pub wins: i64,
     pub win_rate: f64,
+    pub rating: f64,
+    pub current_streak: i64,
 
     pub star_medals: u32,
     pub gold_medals: u32,
@@ -192,8 +264,9 @@ pub struct ScoreboardEntryResponse {
     pub bronze_medals: u32,
 
     pub rank_diff: i32,
-    pub average_score_diff: f64,
+    pub average_score_diff: Option<f64>,
     pub win_rate_diff: f64,
+    pub rating_diff: f64,
 }
 
 impl From<ScoreboardEntry> for ScoreboardEntryResponse {
@@ -208,6 +281,8 @@ impl From<ScoreboardEntry> for ScoreboardEntryResponse {
             average_score: entry.average_score,
             wins: entry.wins,
             win_rate: entry.win_rate,
+            rating: entry.rating,
+            current_streak: entry.current_streak,
 
             star_medals: entry.star_medals,
             gold_medals: entry.gold_medals,
@@ -217,6 +292,7 @@ impl From<ScoreboardEntry> for ScoreboardEntryResponse {
             rank_diff: entry.rank_diff,
             average_score_diff: entry.average_score_diff,
             win_rate_diff: entry.win_rate_diff,
+            rating_diff: entry.rating_diff,
         }
     }
 }
@@ -249,10 +325,11 @@ pub struct PlayerHighlightStats {
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct StatsLifetime {
-    pub average_score: f64,
-    pub best_score: i32,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i32>,
     pub total_games: i64,
     pub win_rate: f64,
+    pub rating: f64,
     pub rank: i64,
 }
 
@@ -265,10 +342,11 @@ pub struct PlayerHighlightsResponse {
 
 #[derive(Serialize)]
 pub struct HighlightsLifetimeResponse {
-    pub average_score: f64,
-    pub best_score: i32,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i32>,
     pub total_games: i64,
     pub win_rate: f64,
+    pub rating: f64,
     pub rank: i64,
 }
 
@@ -279,6 +357,7 @@ impl From<StatsLifetime> for HighlightsLifetimeResponse {
             best_score: value.best_score,
             total_games: value.total_games,
             win_rate: value.win_rate,
+            rating: value.rating,
             rank: value.rank,
         }
     }
@@ -316,6 +395,181 @@ impl PlayerHighlightsResponse {
     }
 }
 
+impl From<UserDb> for Player {
+    fn from(value: UserDb) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            avatar: value.avatar,
+            avatar_colour: value.avatar_colour,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GroupLeaderboardParams {
+    pub season: Option<SeasonScope>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupLeaderboardEntry {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub user_avatar: Avatar,
+    pub user_avatar_colour: AvatarColour,
+    pub rank: i32,
+    pub points: f64,
+    pub games_played: i64,
+    pub contributions: Vec<GameContribution>,
+}
+
+/// Points earned from a single game, based on where the player ranks on its scoreboard
+#[derive(Debug, Serialize)]
+pub struct GameContribution {
+    pub game_id: Uuid,
+    pub game_name: String,
+    pub rank: i32,
+    pub players: i64,
+    pub points: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupLeaderboardResponse {
+    pub entries: Vec<GroupLeaderboardEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StandingsHistory {
+    pub players: Vec<Player>,
+
+    // Ordered from oldest
+    pub snapshots: Vec<StandingsSnapshot>,
+}
+
+/// The standings of everyone who had played, as they were after a match
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StandingsSnapshot {
+    pub match_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub standings: Vec<Standing>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Standing {
+    pub user_id: Uuid,
+    pub rank: i32,
+    pub win_rate: f64,
+    pub average_score: Option<f64>,
+    pub rating: f64,
+}
+
+#[derive(Serialize)]
+pub struct StandingsHistoryResponse {
+    pub players: Vec<PlayerResponse>,
+    pub snapshots: Vec<StandingsSnapshot>,
+    pub season_id: Option<Uuid>,
+}
+
+impl StandingsHistoryResponse {
+    pub fn new(history: StandingsHistory, season_id: Option<Uuid>) -> Self {
+        Self {
+            players: history.players.into_iter().map(|p| p.into()).collect(),
+            snapshots: history.snapshots,
+            season_id,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HeadToHeadParams {
+    pub players: PlayerPair,
+    pub season: Option<SeasonScope>,
+}
+
+/// The two players being compared. Represented as `<player id>,<player id>`
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerPair(pub Uuid, pub Uuid);
+
+impl<'de> Deserialize<'de> for PlayerPair {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        let (a, b) = s
+            .split_once(',')
+            .ok_or_else(|| serde::de::Error::custom("Expected two players"))?;
+
+        let a = Uuid::parse_str(a.trim()).map_err(serde::de::Error::custom)?;
+        let b = Uuid::parse_str(b.trim()).map_err(serde::de::Error::custom)?;
+
+        Ok(Self(a, b))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeadToHeadStats {
+    pub players: [HeadToHeadPlayer; 2],
+    pub matches_played: i64,
+
+    // Matches where both players finished with the same rank
+    pub ties: i64,
+
+    // Average of the first player's score minus the second's, in matches where both have a score
+    pub average_score_margin: Option<f64>,
+
+    // Ordered from most recent
+    pub recent_meetings: Vec<HeadToHeadMeeting>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeadToHeadPlayer {
+    pub player: Player,
+    pub times_ahead: i64,
+}
+
+/// A match both players took part in, with each player's result in the same order as the players
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeadToHeadMeeting {
+    pub match_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub scores: [Option<i32>; 2],
+    pub ranks: [i64; 2],
+}
+
+#[derive(Serialize)]
+pub struct HeadToHeadResponse {
+    pub players: Vec<HeadToHeadPlayerResponse>,
+    pub matches_played: i64,
+    pub ties: i64,
+    pub average_score_margin: Option<f64>,
+    pub recent_meetings: Vec<HeadToHeadMeeting>,
+    pub season_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct HeadToHeadPlayerResponse {
+    pub player: PlayerResponse,
+    pub times_ahead: i64,
+}
+
+impl HeadToHeadResponse {
+    pub fn new(stats: HeadToHeadStats, season_id: Option<Uuid>) -> Self {
+        Self {
+            players: stats
+                .players
+                .into_iter()
+                .map(|p| HeadToHeadPlayerResponse {
+                    player: p.player.into(),
+                    times_ahead: p.times_ahead,
+                })
+                .collect(),
+            matches_played: stats.matches_played,
+            ties: stats.ties,
+            average_score_margin: stats.average_score_margin,
+            recent_meetings: stats.recent_meetings,
+            season_id,
+        }
+    }
+}
+
 #[derive(sqlx::FromRow)]
 pub struct RawHighlight {
     pub user_id: Uuid,
@@ -349,8 +603,10 @@ pub struct HighlightsResponse {
     pub highest_average_score: Vec<HighlightDetail<f64>>,
     pub highest_single_score: Vec<HighlightDetail<i32>>,
     pub most_games_played: Vec<HighlightDetail<u32>>,
-    // TODO: longest win streak
-    // TODO: most consistent
+    pub longest_win_streak: Vec<HighlightDetail<u32>>,
+
+    // Standard deviation of their scores
+    pub most_consistent: Vec<HighlightDetail<f64>>,
 }
 
 impl From<Vec<RawHighlight>> for HighlightsResponse {
@@ -388,6 +644,20 @@ impl From<Vec<RawHighlight>> for HighlightsResponse {
                         value: row.value as u32,
                     });
                 }
+                "longest_win_streak" => {
+                    response.longest_win_streak.push(HighlightDetail {
+                        user_id: row.user_id,
+                        user_name: row.user_name,
+                        value: row.value as u32,
+                    });
+                }
+                "most_consistent" => {
+                    response.most_consistent.push(HighlightDetail {
+                        user_id: row.user_id,
+                        user_name: row.user_name,
+                        value: row.value,
+                    });
+                }
                 _ => panic!("Unknown stat type {}", row.stat_type),
             }
         }
@@ -407,6 +677,7 @@ pub struct DistributionWithMaxMin {
     pub distribution: Distribution,
     pub min_score: i32,
     pub max_score: i32,
+    pub score_direction: ScoreDirection,
 }
 
 pub struct MedalsThresholds {
@@ -414,19 +685,33 @@ pub struct MedalsThresholds {
     pub gold: Option<i32>,
     pub silver: Option<i32>,
     pub bronze: Option<i32>,
+    pub direction: ScoreDirection,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct PlayerStats {
     pub matches_played: i64,
-    pub average_score: f64,
+    pub average_score: Option<f64>,
     pub wins: i64,
-    pub best_score: i32,
+    pub best_score: Option<i32>,
     pub win_rate: f64,
 
+    // Number of wins since their last match without one
+    pub current_streak: i64,
+
     pub medals: Medals,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "medal", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Medal {
+    Star,
+    Gold,
+    Silver,
+    Bronze,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Medals {
     pub star: u32,
@@ -435,16 +720,46 @@ pub struct Medals {
     pub bronze: u32,
 }
 
+impl From<&GameDb> for MedalsThresholds {
+    fn from(game: &GameDb) -> Self {
+        Self {
+            star: game.star_threshold,
+            gold: game.gold_threshold,
+            silver: game.silver_threshold,
+            bronze: game.bronze_threshold,
+            direction: game.score_direction,
+        }
+    }
+}
+
+impl MedalsThresholds {
+    /// The best medal a score earns, if any
+    pub fn medal(&self, score: i32) -> Option<Medal> {
+        let meets =
+            |threshold: Option<i32>| threshold.is_some_and(|t| self.direction.meets(score, t));
+
+        if meets(self.star) {
+            Some(Medal::Star)
+        } else if meets(self.gold) {
+            Some(Medal::Gold)
+        } else if meets(self.silver) {
+            Some(Medal::Silver)
+        } else if meets(self.bronze) {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
 impl Medals {
     pub fn count_medal(&mut self, score: i32, thresholds: &MedalsThresholds) {
-        if thresholds.star.is_some_and(|t| score >= t) {
-            self.star += 1;
-        } else if thresholds.gold.is_some_and(|t| score >= t) {
-            self.gold += 1;
-        } else if thresholds.silver.is_some_and(|t| score >= t) {
-            self.silver += 1;
-        } else if thresholds.bronze.is_some_and(|t| score >= t) {
-            self.bronze += 1;
+        match thresholds.medal(score) {
+            Some(Medal::Star) => self.star += 1,
+            Some(Medal::Gold) => self.gold += 1,
+            Some(Medal::Silver) => self.silver += 1,
+            Some(Medal::Bronze) => self.bronze += 1,
+            None => {}
         }
     }
 }
@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, prelude::Type};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    trim_string,
+    user::{Avatar, AvatarColour},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "tournament_format", rename_all = "snake_case")]
+pub enum TournamentFormat {
+    SingleElimination,
+    DoubleElimination,
+    RoundRobin,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "tournament_status", rename_all = "snake_case")]
+pub enum TournamentStatus {
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "tournament_bracket", rename_all = "snake_case")]
+pub enum TournamentBracket {
+    Winners,
+    Losers,
+    GrandFinal,
+    RoundRobin,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "tournament_slot_status", rename_all = "snake_case")]
+pub enum TournamentSlotStatus {
+    /// Waiting for players from earlier slots
+    Pending,
+    /// Both players are known, waiting for the match
+    Ready,
+    Completed,
+    /// Only one player arrived, so they move on without playing
+    Walkover,
+    /// No players will arrive, so the slot is never played
+    Void,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TournamentDb {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub name: String,
+    pub format: TournamentFormat,
+    pub status: TournamentStatus,
+    pub winner_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct TournamentParticipantDb {
+    pub user_id: Uuid,
+    pub seed: i32,
+    pub name: String,
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+}
+
+#[derive(Debug, Clone, FromRow, PartialEq)]
+pub struct TournamentSlotDb {
+    pub id: Uuid,
+    pub tournament_id: Uuid,
+    pub bracket: TournamentBracket,
+    pub round: i32,
+    pub position: i32,
+    pub status: TournamentSlotStatus,
+    pub player_1_id: Option<Uuid>,
+    pub player_2_id: Option<Uuid>,
+    pub match_id: Option<Uuid>,
+    pub winner_id: Option<Uuid>,
+
+    pub winner_next_slot_id: Option<Uuid>,
+    pub winner_next_position: Option<i32>,
+    pub loser_next_slot_id: Option<Uuid>,
+    pub loser_next_position: Option<i32>,
+}
+
+/// Record of a player in a round robin
+#[derive(Debug, Serialize)]
+pub struct TournamentStanding {
+    pub user_id: Uuid,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32,
+}
+
+#[derive(Debug)]
+pub struct TournamentDetails {
+    pub tournament: TournamentDb,
+    pub participants: Vec<TournamentParticipantDb>,
+    pub slots: Vec<TournamentSlotDb>,
+    pub standings: Vec<TournamentStanding>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateTournamentReq {
+    #[validate(length(min = 3, max = 50, message = "Name must be between 3 and 50 chars"))]
+    #[serde(deserialize_with = "trim_string")]
+    pub name: String,
+
+    pub format: TournamentFormat,
+
+    /// Players in seed order, with the top seed first
+    #[validate(length(min = 2, max = 64, message = "Must have between 2 and 64 players"))]
+    pub player_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TournamentResponse {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub name: String,
+    pub format: TournamentFormat,
+    pub status: TournamentStatus,
+    pub winner_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TournamentDb> for TournamentResponse {
+    fn from(tournament: TournamentDb) -> Self {
+        Self {
+            id: tournament.id,
+            game_id: tournament.game_id,
+            name: tournament.name,
+            format: tournament.format,
+            status: tournament.status,
+            winner_id: tournament.winner_id,
+            created_by: tournament.created_by,
+            created_at: tournament.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TournamentParticipantResponse {
+    pub user_id: Uuid,
+    pub seed: i32,
+    pub name: String,
+    pub avatar: Avatar,
+    pub avatar_colour: AvatarColour,
+}
+
+impl From<TournamentParticipantDb> for TournamentParticipantResponse {
+    fn from(participant: TournamentParticipantDb) -> Self {
+        Self {
+            user_id: participant.user_id,
+            seed: participant.seed,
+            name: participant.name,
+            avatar: participant.avatar,
+            avatar_colour: participant.avatar_colour,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TournamentSlotResponse {
+    pub id: Uuid,
+    pub bracket: TournamentBracket,
+    pub round: i32,
+    pub position: i32,
+    pub status: TournamentSlotStatus,
+    pub player_1_id: Option<Uuid>,
+    pub player_2_id: Option<Uuid>,
+    pub match_id: Option<Uuid>,
+    pub winner_id: Option<Uuid>,
+    pub winner_next_slot_id: Option<Uuid>,
+    pub loser_next_slot_id: Option<Uuid>,
+}
+
+impl From<TournamentSlotDb> for TournamentSlotResponse {
+    fn from(slot: TournamentSlotDb) -> Self {
+        Self {
+            id: slot.id,
+            bracket: slot.bracket,
+            round: slot.round,
+            position: slot.position,
+            status: slot.status,
+            player_1_id: slot.player_1_id,
+            player_2_id: slot.player_2_id,
+            match_id: slot.match_id,
+            winner_id: slot.winner_id,
+            winner_next_slot_id: slot.winner_next_slot_id,
+            loser_next_slot_id: slot.loser_next_slot_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TournamentDetailResponse {
+    #[serde(flatten)]
+    pub tournament: TournamentResponse,
+    pub participants: Vec<TournamentParticipantResponse>,
+    pub slots: Vec<TournamentSlotResponse>,
+    pub standings: Vec<TournamentStanding>,
+}
+
+impl From<TournamentDetails> for TournamentDetailResponse {
+    fn from(details: TournamentDetails) -> Self {
+        Self {
+            tournament: details.tournament.into(),
+            participants: details.participants.into_iter().map(|p| p.into()).collect(),
+            slots: details.slots.into_iter().map(|s| s.into()).collect(),
+            standings: details.standings,
+        }
+    }
+}
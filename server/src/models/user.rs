@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::models::trim_string;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -170,3 +172,27 @@ pub struct UpdatePasswordReq {
     #[serde(deserialize_with = "trim_string")]
     pub new_password: String,
 }
+
+/// What happens to the match history of a deleted account
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreRetention {
+    /// Scores are kept under an anonymous player, so other members' results don't change
+    Anonymise,
+
+    /// Scores are removed, along with matches nobody else played in
+    Remove,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteAccountReq {
+    #[serde(deserialize_with = "trim_string")]
+    pub password: String,
+
+    pub scores: ScoreRetention,
+
+    /// The member to hand each owned group to, by group ID. Without one, the longest-serving admin
+    /// takes over, and groups without other members are deleted
+    #[serde(default)]
+    pub new_owners: HashMap<Uuid, Uuid>,
+}
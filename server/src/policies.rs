@@ -10,7 +10,22 @@ pub enum GroupAction {
     UpdateGroup,
     DeleteGroup,
     CreateMatch,
+    UpdateMatch,
+    DeleteMatch,
+    ViewMatchRevisions,
+    ImportMatches,
+    StartSeason,
+    EndSeason,
+    UpdateSeason,
+    CreateTournament,
+    DeleteTournament,
+    CreateFixture,
+    DeleteFixture,
+    RsvpFixture,
     RemoveMember(GroupMemberRole),
     UpdateRole(GroupMemberRole, GroupMemberRole), // (From, To)
     ViewEmails,
+    TransferOwnership,
+    CreateGuest,
+    MergeMembers,
 }
@@ -0,0 +1,113 @@
+use sqlx::{PgConnection, PgExecutor, Postgres};
+use uuid::Uuid;
+
+use crate::models::activity::{ActivityCursor, ActivityDb, NewActivity};
+
+pub struct ActivityRepo {}
+
+impl ActivityRepo {
+    pub async fn create<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        activity: NewActivity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO group_activity (group_id, kind, user_id, actor_id, game_id, season_id, match_id, role, medal, score)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(activity.group_id)
+        .bind(activity.kind)
+        .bind(activity.user_id)
+        .bind(activity.actor_id)
+        .bind(activity.game_id)
+        .bind(activity.season_id)
+        .bind(activity.match_id)
+        .bind(activity.role)
+        .bind(activity.medal)
+        .bind(activity.score)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets a page of a group's activity, newest first, starting after the cursor (if provided)
+    pub async fn get_page<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        group_id: Uuid,
+        cursor: Option<ActivityCursor>,
+        limit: i64,
+    ) -> Result<Vec<ActivityDb>, sqlx::Error> {
+        sqlx::query_as::<_, ActivityDb>(
+            r#"
+            SELECT a.id, a.kind, a.created_at,
+                   a.user_id, u.name AS user_name, u.avatar AS user_avatar, u.avatar_colour AS user_avatar_colour,
+                   a.actor_id, ac.name AS actor_name, ac.avatar AS actor_avatar, ac.avatar_colour AS actor_avatar_colour,
+                   a.game_id, g.name AS game_name,
+                   a.season_id, s.number AS season_number, s.name AS season_name,
+                   a.match_id, a.role, a.medal, a.score
+            FROM group_activity a
+            LEFT JOIN users u ON u.id = a.user_id
+            LEFT JOIN users ac ON ac.id = a.actor_id
+            LEFT JOIN games g ON g.id = a.game_id
+            LEFT JOIN seasons s ON s.id = a.season_id
+            WHERE a.group_id = $1
+                AND ($2 IS NULL OR (a.created_at, a.id) < ($2, $3))
+            ORDER BY a.created_at DESC, a.id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(group_id)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id))
+        .bind(limit)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Moves the events about one player in a group over to another player
+    pub async fn reassign_user(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE group_activity SET user_id = $3 WHERE group_id = $1 AND user_id = $2")
+            .bind(group_id)
+            .bind(from_user_id)
+            .bind(to_user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE group_activity SET actor_id = $3 WHERE group_id = $1 AND actor_id = $2",
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the personal bests and medals set in a match, once its scores no longer stand
+    pub async fn delete_match_highlights<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        match_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "DELETE FROM group_activity WHERE match_id = $1 AND kind IN ('personal_best', 'medal_earned')",
+        )
+        .bind(match_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
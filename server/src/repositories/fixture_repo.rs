@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::models::fixture::{FixtureDb, FixturePlayerDb, FixtureRsvp};
+
+pub struct FixtureRepo {}
+
+impl FixtureRepo {
+    pub async fn create(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+        scheduled_at: DateTime<Utc>,
+        created_by: Uuid,
+        player_ids: &[Uuid],
+    ) -> Result<FixtureDb, sqlx::Error> {
+        let fixture = sqlx::query_as::<_, FixtureDb>(
+            "INSERT INTO fixtures (game_id, scheduled_at, created_by) VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(game_id)
+        .bind(scheduled_at)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !player_ids.is_empty() {
+            let mut query_builder: QueryBuilder<Postgres> =
+                QueryBuilder::new("INSERT INTO fixture_players (fixture_id, user_id) ");
+
+            query_builder.push_values(player_ids, |mut b, user_id| {
+                b.push_bind(fixture.id).push_bind(user_id);
+            });
+
+            query_builder.build().execute(&mut *tx).await?;
+        }
+
+        Ok(fixture)
+    }
+
+    pub async fn get<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        fixture_id: Uuid,
+    ) -> Result<Option<FixtureDb>, sqlx::Error> {
+        sqlx::query_as::<_, FixtureDb>("SELECT * FROM fixtures WHERE id = $1")
+            .bind(fixture_id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    // Finds fixture by ID and locks until updated
+    pub async fn get_for_update(
+        &self,
+        tx: &mut PgConnection,
+        fixture_id: Uuid,
+    ) -> Result<Option<FixtureDb>, sqlx::Error> {
+        sqlx::query_as::<_, FixtureDb>("SELECT * FROM fixtures WHERE id = $1 FOR UPDATE")
+            .bind(fixture_id)
+            .fetch_optional(tx)
+            .await
+    }
+
+    /// Gets the fixtures of a game that are still waiting for results, soonest first
+    pub async fn get_unplayed<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_id: Uuid,
+    ) -> Result<Vec<FixtureDb>, sqlx::Error> {
+        sqlx::query_as::<_, FixtureDb>(
+            "SELECT * FROM fixtures WHERE game_id = $1 AND match_id IS NULL ORDER BY scheduled_at, id",
+        )
+        .bind(game_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn get_players<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        fixture_ids: &[Uuid],
+    ) -> Result<Vec<FixturePlayerDb>, sqlx::Error> {
+        sqlx::query_as::<_, FixturePlayerDb>(
+            r#"
+            SELECT fp.fixture_id, fp.user_id, u.name, u.avatar, u.avatar_colour, fp.rsvp, fp.responded_at
+            FROM fixture_players fp
+            JOIN users u ON u.id = fp.user_id
+            WHERE fp.fixture_id = ANY($1)
+            ORDER BY u.name
+            "#,
+        )
+        .bind(fixture_ids)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Records a player's response, adding them to the fixture if they weren't invited
+    pub async fn set_rsvp<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        fixture_id: Uuid,
+        user_id: Uuid,
+        rsvp: FixtureRsvp,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO fixture_players (fixture_id, user_id, rsvp, responded_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (fixture_id, user_id)
+            DO UPDATE SET rsvp = EXCLUDED.rsvp, responded_at = EXCLUDED.responded_at
+            "#,
+        )
+        .bind(fixture_id)
+        .bind(user_id)
+        .bind(rsvp)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_match(
+        &self,
+        tx: &mut PgConnection,
+        fixture_id: Uuid,
+        match_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE fixtures SET match_id = $1 WHERE id = $2")
+            .bind(match_id)
+            .bind(fixture_id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        fixture_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM fixtures WHERE id = $1")
+            .bind(fixture_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes a user from every fixture they were invited to
+    pub async fn remove_player<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM fixture_players WHERE user_id = $1")
+            .bind(user_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Moves one player's invitations to a group's fixtures over to another player
+    pub async fn reassign_player(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE fixture_players fp SET user_id = $3
+            FROM fixtures f
+            JOIN games g ON g.id = f.game_id
+            WHERE fp.fixture_id = f.id AND g.group_id = $1 AND fp.user_id = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM fixture_players o WHERE o.fixture_id = fp.fixture_id AND o.user_id = $3
+                )
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,80 +1,77 @@
 use sqlx::{PgConnection, PgExecutor, Postgres};
 use uuid::Uuid;
 
-use crate::models::game::{GameDb, Interval, ScoringMetric};
+use crate::models::game::{GameDb, GameSettings, MatchResultType};
 
 pub struct GameRepo {}
 
 impl GameRepo {
-    pub async fn create<'e>(
+    pub async fn create(
         &self,
         tx: &mut PgConnection,
         group_id: Uuid,
-        name: &str,
-        min_players_per_match: i32,
-        max_players_per_match: i32,
-        metric: ScoringMetric,
-        season_duration: Option<Interval>,
-        star_threshold: Option<i32>,
-        gold_threshold: Option<i32>,
-        silver_threshold: Option<i32>,
-        bronze_threshold: Option<i32>,
+        result_type: MatchResultType,
+        settings: &GameSettings<'_>,
     ) -> Result<GameDb, sqlx::Error> {
         let game = sqlx::query_as::<_, GameDb>(
-            "INSERT INTO games (group_id, name, min_players_per_match, max_players_per_match, metric, season_duration, star_threshold, gold_threshold, silver_threshold, bronze_threshold) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
+            "INSERT INTO games (group_id, name, min_players_per_match, max_players_per_match, min_players_per_team, max_players_per_team, metric, score_direction, result_type, season_duration, season_duration_unit, season_alignment, season_timezone, star_threshold, gold_threshold, silver_threshold, bronze_threshold) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) RETURNING *",
         )
         .bind(group_id)
-        .bind(name)
-        .bind(min_players_per_match)
-        .bind(max_players_per_match)
-        .bind(metric)
-        .bind(season_duration)
-        .bind(star_threshold)
-        .bind(gold_threshold)
-        .bind(silver_threshold)
-        .bind(bronze_threshold)
+        .bind(settings.name)
+        .bind(settings.min_players_per_match)
+        .bind(settings.max_players_per_match)
+        .bind(settings.min_players_per_team)
+        .bind(settings.max_players_per_team)
+        .bind(settings.metric)
+        .bind(settings.score_direction)
+        .bind(result_type)
+        .bind(settings.season_duration)
+        .bind(settings.season_duration.map(|d| d.unit()))
+        .bind(settings.season_alignment)
+        .bind(settings.season_timezone)
+        .bind(settings.medal_scores.and_then(|s| s.star))
+        .bind(settings.medal_scores.and_then(|s| s.gold))
+        .bind(settings.medal_scores.and_then(|s| s.silver))
+        .bind(settings.medal_scores.and_then(|s| s.bronze))
         .fetch_one(&mut *tx)
         .await?;
 
         // Insert initial season
         sqlx::query(
-            "INSERT INTO seasons (game_id, number, start_date, end_date) VALUES ($1, 1, $2, $2 + $3)",
+            "INSERT INTO seasons (game_id, number, start_date, end_date) VALUES ($1, 1, $2, next_season_end($1, $2))",
         )
         .bind(game.id)
         .bind(game.created_at)
-        .bind(game.season_duration)
         .execute(&mut *tx)
         .await?;
 
         Ok(game)
     }
 
-    pub async fn update<'e>(
+    pub async fn update(
         &self,
         tx: &mut PgConnection,
         game_id: Uuid,
-        name: &str,
-        min_players_per_match: i32,
-        max_players_per_match: i32,
-        metric: ScoringMetric,
-        season_duration: Option<Interval>,
-        star_threshold: Option<i32>,
-        gold_threshold: Option<i32>,
-        silver_threshold: Option<i32>,
-        bronze_threshold: Option<i32>,
+        settings: &GameSettings<'_>,
     ) -> Result<GameDb, sqlx::Error> {
         sqlx::query_as::<_, GameDb>(
-            "UPDATE games SET name = $1, min_players_per_match = $2, max_players_per_match = $3, metric = $4, season_duration = $5, star_threshold = $6, gold_threshold = $7, silver_threshold = $8, bronze_threshold = $9 WHERE id = $10 RETURNING *",
+            "UPDATE games SET name = $1, min_players_per_match = $2, max_players_per_match = $3, min_players_per_team = $4, max_players_per_team = $5, metric = $6, score_direction = $7, season_duration = $8, season_duration_unit = $9, season_alignment = $10, season_timezone = $11, star_threshold = $12, gold_threshold = $13, silver_threshold = $14, bronze_threshold = $15 WHERE id = $16 RETURNING *",
         )
-        .bind(name)
-        .bind(min_players_per_match)
-        .bind(max_players_per_match)
-        .bind(metric)
-        .bind(season_duration)
-        .bind(star_threshold)
-        .bind(gold_threshold)
-        .bind(silver_threshold)
-        .bind(bronze_threshold)
+        .bind(settings.name)
+        .bind(settings.min_players_per_match)
+        .bind(settings.max_players_per_match)
+        .bind(settings.min_players_per_team)
+        .bind(settings.max_players_per_team)
+        .bind(settings.metric)
+        .bind(settings.score_direction)
+        .bind(settings.season_duration)
+        .bind(settings.season_duration.map(|d| d.unit()))
+        .bind(settings.season_alignment)
+        .bind(settings.season_timezone)
+        .bind(settings.medal_scores.and_then(|s| s.star))
+        .bind(settings.medal_scores.and_then(|s| s.gold))
+        .bind(settings.medal_scores.and_then(|s| s.silver))
+        .bind(settings.medal_scores.and_then(|s| s.bronze))
         .bind(game_id)
         .fetch_one(tx)
         .await
@@ -114,4 +111,86 @@ impl GameRepo {
             .fetch_all(executor)
             .await
     }
+
+    pub async fn is_valid_timezone<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        timezone: &str,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+            .bind(timezone)
+            .fetch_one(executor)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::game::{Interval, ScoreDirection, ScoringMetric, SeasonAlignment};
+    use sqlx::PgPool;
+
+    async fn create_group(pool: &PgPool) -> Uuid {
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (name, email, password_hash) VALUES ('a', 'a@a.com', 'x') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        sqlx::query_scalar(
+            "INSERT INTO groups (name, created_by) VALUES ('group', $1) RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn settings(season_duration: Interval) -> GameSettings<'static> {
+        GameSettings {
+            name: "game",
+            min_players_per_match: 1,
+            max_players_per_match: 4,
+            min_players_per_team: 1,
+            max_players_per_team: 1,
+            metric: ScoringMetric::WinRate,
+            score_direction: ScoreDirection::HigherIsBetter,
+            season_duration: Some(season_duration),
+            season_alignment: SeasonAlignment::None,
+            season_timezone: "UTC",
+            medal_scores: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_season_duration_keeps_its_unit(pool: PgPool) {
+        let repo = GameRepo {};
+        let group_id = create_group(&pool).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let weeks = repo
+            .create(
+                &mut tx,
+                group_id,
+                MatchResultType::Score,
+                &settings(Interval::Weeks(2)),
+            )
+            .await
+            .unwrap();
+        let days = repo
+            .update(&mut tx, weeks.id, &settings(Interval::Days(14)))
+            .await
+            .unwrap();
+        assert!(matches!(days.season_duration, Some(Interval::Days(14))));
+
+        let weeks = repo
+            .update(&mut tx, weeks.id, &settings(Interval::Weeks(2)))
+            .await
+            .unwrap();
+        assert!(matches!(weeks.season_duration, Some(Interval::Weeks(2))));
+
+        let game = repo.get(&mut *tx, weeks.id).await.unwrap().unwrap();
+        assert!(matches!(game.season_duration, Some(Interval::Weeks(2))));
+    }
 }
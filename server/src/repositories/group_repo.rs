@@ -1,7 +1,8 @@
-use sqlx::{PgExecutor, Postgres};
+use sqlx::{PgConnection, PgExecutor, Postgres};
 use uuid::Uuid;
 
 use crate::models::{
+    export::MembershipExportDb,
     group::{GroupDb, GroupMemberDb, GroupMemberDetailsDb, GroupMemberRole, OrderBy},
     stats::OrderDir,
 };
@@ -233,4 +234,140 @@ impl GroupRepo {
 
         Ok(member)
     }
+
+    /// Gets the groups a user is the owner of
+    pub async fn get_owned_groups<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<Vec<GroupDb>, sqlx::Error> {
+        sqlx::query_as::<_, GroupDb>(
+            "SELECT groups.* FROM groups JOIN group_members ON groups.id = group_members.group_id WHERE group_members.user_id = $1 AND group_members.role = 'owner' ORDER BY name",
+        )
+        .bind(user_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn get_memberships<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<Vec<MembershipExportDb>, sqlx::Error> {
+        sqlx::query_as::<_, MembershipExportDb>(
+            r#"
+            SELECT groups.id AS group_id, groups.name AS group_name, group_members.role, group_members.joined_at
+            FROM group_members
+            JOIN groups ON groups.id = group_members.group_id
+            WHERE group_members.user_id = $1
+            ORDER BY groups.name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Removes a user from every group they belong to
+    pub async fn remove_memberships<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM group_members WHERE user_id = $1")
+            .bind(user_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the owners of a group, locking them until the transaction ends so ownership can't be
+    /// given up by two owners at once
+    pub async fn lock_owners(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT user_id FROM group_members WHERE group_id = $1 AND role = 'owner' FOR UPDATE",
+        )
+        .bind(group_id)
+        .fetch_all(tx)
+        .await
+    }
+
+    /// Finds who should take over a group from a departing member: the longest-serving admin, or
+    /// failing that the longest-serving member of the highest role. Guests never take over
+    pub async fn get_successor<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        group_id: Uuid,
+        departing_user_id: Uuid,
+    ) -> Result<Option<GroupMemberDb>, sqlx::Error> {
+        sqlx::query_as::<_, GroupMemberDb>(
+            "SELECT * FROM group_members WHERE group_id = $1 AND user_id <> $2 AND role <> 'guest' ORDER BY role DESC, joined_at LIMIT 1",
+        )
+        .bind(group_id)
+        .bind(departing_user_id)
+        .fetch_optional(executor)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    async fn add_member(pool: &PgPool, group_id: Uuid, role: &str, joined_days_ago: i32) -> Uuid {
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (name, email, password_hash) VALUES ('a', gen_random_uuid() || '@a.com', 'x') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO group_members (group_id, user_id, role, joined_at) VALUES ($1, $2, $3::user_role, NOW() - make_interval(days => $4))",
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(role)
+        .bind(joined_days_ago)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        user_id
+    }
+
+    #[sqlx::test]
+    async fn test_successor_is_longest_serving_admin(pool: PgPool) {
+        let repo = GroupRepo {};
+        let creator: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (name, email, password_hash) VALUES ('a', 'a@a.com', 'x') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let group = repo.create(&pool, "group", creator).await.unwrap();
+
+        let owner = add_member(&pool, group.id, "owner", 30).await;
+        add_member(&pool, group.id, "member", 20).await;
+        add_member(&pool, group.id, "admin", 5).await;
+        let oldest_admin = add_member(&pool, group.id, "admin", 10).await;
+
+        let successor = repo.get_successor(&pool, group.id, owner).await.unwrap();
+        assert_eq!(successor.map(|m| m.user_id), Some(oldest_admin));
+
+        // Several owners can share a group
+        repo.update_member_role(&pool, group.id, oldest_admin, GroupMemberRole::Owner)
+            .await
+            .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        let owners = repo.lock_owners(&mut tx, group.id).await.unwrap();
+        assert_eq!(owners.len(), 2);
+    }
 }
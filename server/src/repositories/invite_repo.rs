@@ -20,9 +20,10 @@ impl InviteRepo {
         max_uses: Option<i32>,
         email_whitelist: Vec<String>,
         expires_at: Option<DateTime<Utc>>,
+        guest_id: Option<Uuid>,
     ) -> Result<InviteDb, sqlx::Error> {
         sqlx::query_as::<_, InviteDb>(
-            "INSERT INTO invites (group_id, created_by, name, role, max_uses, email_whitelist, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            "INSERT INTO invites (group_id, created_by, name, role, max_uses, email_whitelist, expires_at, guest_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
         )
         .bind(group_id)
         .bind(created_by)
@@ -31,6 +32,7 @@ impl InviteRepo {
         .bind(max_uses)
         .bind(email_whitelist)
         .bind(expires_at)
+        .bind(guest_id)
         .fetch_one(executor)
         .await
     }
@@ -94,4 +96,18 @@ impl InviteRepo {
             .fetch_all(executor)
             .await
     }
+
+    /// Deletes every invite a user created
+    pub async fn delete_created_by<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM invites WHERE created_by = $1")
+            .bind(user_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
 }
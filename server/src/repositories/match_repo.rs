@@ -1,7 +1,17 @@
-use sqlx::{PgConnection, Postgres, QueryBuilder};
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::models::game_match::{MatchDb, MatchDetailsDb, MatchScoreDb};
+use crate::models::{
+    export::{ExportScoreDb, UserScoreExportDb},
+    game_match::{
+        MatchChanges, MatchCursor, MatchDb, MatchDetailsDb, MatchLeaderboardEntryDb,
+        MatchRevisionAction, MatchRevisionDb, MatchRevisionScoreDb, MatchRevisionWithScores,
+        MatchScoreDb, MatchSummaryDb,
+    },
+};
 
 pub struct MatchRepo {}
 
@@ -12,37 +22,1152 @@ impl MatchRepo {
         game_id: Uuid,
         season_id: Uuid,
         recorded_by: Uuid,
+        played_at: DateTime<Utc>,
         scores: Vec<MatchScoreDb>,
     ) -> Result<MatchDb, sqlx::Error> {
         let match_details = sqlx::query_as::<_, MatchDetailsDb>(
-            "INSERT INTO matches (game_id, recorded_by, season_id) VALUES ($1, $2, $3) RETURNING *",
+            "INSERT INTO matches (game_id, recorded_by, season_id, played_at) VALUES ($1, $2, $3, $4) RETURNING *",
         )
         .bind(game_id)
         .bind(recorded_by)
         .bind(season_id)
+        .bind(played_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let scores = self.insert_scores(tx, match_details.id, scores).await?;
+
+        Ok(MatchDb {
+            id: match_details.id,
+            game_id: match_details.game_id,
+            season_id: match_details.season_id,
+            played_at: match_details.played_at,
+            scores,
+        })
+    }
+
+    pub async fn get<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        match_id: Uuid,
+    ) -> Result<Option<MatchDetailsDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchDetailsDb>("SELECT * FROM matches WHERE id = $1")
+            .bind(match_id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    // Finds match by ID and locks until updated
+    pub async fn get_for_update(
+        &self,
+        tx: &mut PgConnection,
+        match_id: Uuid,
+    ) -> Result<Option<MatchDetailsDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchDetailsDb>("SELECT * FROM matches WHERE id = $1 FOR UPDATE")
+            .bind(match_id)
+            .fetch_optional(&mut *tx)
+            .await
+    }
+
+    pub async fn get_summary<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        match_id: Uuid,
+    ) -> Result<Option<MatchSummaryDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchSummaryDb>(
+            r#"
+            SELECT m.*, u.name AS recorded_by_name
+            FROM matches m
+            JOIN users u ON u.id = m.recorded_by
+            WHERE m.id = $1
+            "#,
+        )
+        .bind(match_id)
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Gets a page of matches for a game, newest first, starting after the cursor (if provided)
+    pub async fn get_summaries<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+        player_id: Option<Uuid>,
+        cursor: Option<MatchCursor>,
+        limit: i64,
+    ) -> Result<Vec<MatchSummaryDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchSummaryDb>(
+            r#"
+            SELECT m.*, u.name AS recorded_by_name
+            FROM matches m
+            JOIN users u ON u.id = m.recorded_by
+            WHERE m.game_id = $1
+                AND ($2 IS NULL OR m.season_id = $2)
+                AND ($3 IS NULL OR EXISTS (
+                    SELECT 1 FROM match_scores ms WHERE ms.match_id = m.id AND ms.user_id = $3
+                ))
+                AND ($4 IS NULL OR (m.played_at, m.id) < ($4, $5))
+            ORDER BY m.played_at DESC, m.id DESC
+            LIMIT $6
+            "#,
+        )
+        .bind(game_id)
+        .bind(season_id)
+        .bind(player_id)
+        .bind(cursor.map(|c| c.played_at))
+        .bind(cursor.map(|c| c.id))
+        .bind(limit)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Gets the ranked scores of each match provided, ordered by rank
+    pub async fn get_leaderboards<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        match_ids: &[Uuid],
+    ) -> Result<Vec<MatchLeaderboardEntryDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchLeaderboardEntryDb>(
+            r#"
+            SELECT lb.match_id, lb.user_id, u.name, u.avatar, u.avatar_colour, lb.score, lb.placement, lb.outcome, lb.team, lb.rank
+            FROM match_leaderboards lb
+            JOIN users u ON u.id = lb.user_id
+            WHERE lb.match_id = ANY($1)
+            ORDER BY lb.rank, u.name
+            "#,
+        )
+        .bind(match_ids)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn get_scores<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        match_id: Uuid,
+    ) -> Result<Vec<MatchScoreDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchScoreDb>(
+            "SELECT * FROM match_scores WHERE match_id = $1 ORDER BY score DESC, placement, outcome",
+        )
+        .bind(match_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Gets every match of the games provided, oldest first
+    pub async fn get_for_games<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_ids: &[Uuid],
+    ) -> Result<Vec<MatchDetailsDb>, sqlx::Error> {
+        sqlx::query_as::<_, MatchDetailsDb>(
+            "SELECT * FROM matches WHERE game_id = ANY($1) ORDER BY played_at, id",
+        )
+        .bind(game_ids)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Gets the scores of every match of the games provided
+    pub async fn get_scores_for_games<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_ids: &[Uuid],
+    ) -> Result<Vec<ExportScoreDb>, sqlx::Error> {
+        sqlx::query_as::<_, ExportScoreDb>(
+            r#"
+            SELECT ms.match_id, ms.user_id, ms.score, ms.placement, ms.outcome, ms.team
+            FROM match_scores ms
+            JOIN matches m ON m.id = ms.match_id
+            WHERE m.game_id = ANY($1)
+            ORDER BY m.played_at, m.id, ms.placement, ms.score DESC
+            "#,
+        )
+        .bind(game_ids)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Gets every result a user has in any game
+    pub async fn get_user_scores<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<Vec<UserScoreExportDb>, sqlx::Error> {
+        sqlx::query_as::<_, UserScoreExportDb>(
+            r#"
+            SELECT ms.match_id, g.group_id, g.id AS game_id, g.name AS game_name, m.season_id, m.played_at,
+                   ms.score, ms.placement, ms.outcome, ms.team
+            FROM match_scores ms
+            JOIN matches m ON m.id = ms.match_id
+            JOIN games g ON g.id = m.game_id
+            WHERE ms.user_id = $1
+            ORDER BY m.played_at, m.id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Gets the best score each player has in a game, leaving out one match
+    pub async fn get_best_scores<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_id: Uuid,
+        user_ids: &[Uuid],
+        excluded_match_id: Uuid,
+    ) -> Result<Vec<(Uuid, i32)>, sqlx::Error> {
+        sqlx::query_as::<_, (Uuid, i32)>(
+            r#"
+            SELECT ms.user_id,
+                   CASE WHEN g.score_direction = 'lower_is_better' THEN MIN(ms.score) ELSE MAX(ms.score) END
+            FROM match_scores ms
+            JOIN matches m ON m.id = ms.match_id
+            JOIN games g ON g.id = m.game_id
+            WHERE m.game_id = $1 AND ms.user_id = ANY($2) AND m.id <> $3 AND ms.score IS NOT NULL
+            GROUP BY ms.user_id, g.score_direction
+            "#,
+        )
+        .bind(game_id)
+        .bind(user_ids)
+        .bind(excluded_match_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Removes a user's results from every match and its history. Matches that are no longer valid
+    /// for their game are deleted
+    pub async fn delete_user_scores(
+        &self,
+        tx: &mut PgConnection,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let removed = sqlx::query_as::<_, (Uuid, Option<i32>)>(
+            "DELETE FROM match_scores WHERE user_id = $1 RETURNING match_id, team",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM match_revision_scores WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        self.delete_invalidated_matches(tx, removed).await
+    }
+
+    /// Deletes the matches left invalid for their game once the given results, by match and team,
+    /// were taken out of them. Taking players out can only break the rules on how many play, which
+    /// teams take part and how a win/loss match ends, so only those are checked again
+    async fn delete_invalidated_matches(
+        &self,
+        tx: &mut PgConnection,
+        removed: Vec<(Uuid, Option<i32>)>,
+    ) -> Result<(), sqlx::Error> {
+        let (match_ids, teams): (Vec<Uuid>, Vec<Option<i32>>) = removed.into_iter().unzip();
+
+        sqlx::query(
+            r#"
+            DELETE FROM matches m
+            USING games g
+            WHERE g.id = m.game_id AND m.id = ANY($1) AND (
+                (SELECT COUNT(*) FROM match_scores ms WHERE ms.match_id = m.id) < g.min_players_per_match
+
+                -- The last player in a team was taken out
+                OR EXISTS (
+                    SELECT 1 FROM UNNEST($1::UUID[], $2::INT[]) AS removed(match_id, team)
+                    WHERE removed.match_id = m.id AND removed.team IS NOT NULL AND NOT EXISTS (
+                        SELECT 1 FROM match_scores ms
+                        WHERE ms.match_id = m.id AND ms.team = removed.team
+                    )
+                )
+
+                OR EXISTS (
+                    SELECT 1 FROM match_scores ms
+                    WHERE ms.match_id = m.id AND ms.team IS NOT NULL
+                    GROUP BY ms.team
+                    HAVING COUNT(*) < g.min_players_per_team
+                )
+
+                -- A win/loss match needs either a winner or a draw
+                OR (g.result_type = 'win_loss' AND (
+                    EXISTS (SELECT 1 FROM match_scores ms WHERE ms.match_id = m.id AND ms.outcome = 'win')
+                    = EXISTS (SELECT 1 FROM match_scores ms WHERE ms.match_id = m.id AND ms.outcome = 'draw')
+                ))
+            )
+            "#,
+        )
+        .bind(&match_ids)
+        .bind(&teams)
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Checks whether two players were ever in the same match in a group
+    pub async fn share_matches<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        group_id: Uuid,
+        user_1_id: Uuid,
+        user_2_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM match_scores a
+                JOIN match_scores b ON b.match_id = a.match_id
+                JOIN matches m ON m.id = a.match_id
+                JOIN games g ON g.id = m.game_id
+                WHERE g.group_id = $1 AND a.user_id = $2 AND b.user_id = $3
+            )
+            "#,
+        )
+        .bind(group_id)
+        .bind(user_1_id)
+        .bind(user_2_id)
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Removes a player's results from the matches in a group they played alongside another
+    /// player. Matches that are no longer valid for their game are deleted. Returns how many
+    /// results were removed
+    pub async fn delete_shared_scores(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+        user_id: Uuid,
+        other_user_id: Uuid,
+    ) -> Result<usize, sqlx::Error> {
+        let removed = sqlx::query_as::<_, (Uuid, Option<i32>)>(
+            r#"
+            DELETE FROM match_scores ms
+            USING matches m
+            JOIN games g ON g.id = m.game_id
+            WHERE ms.match_id = m.id AND g.group_id = $1 AND ms.user_id = $2
+                AND EXISTS (
+                    SELECT 1 FROM match_scores o WHERE o.match_id = ms.match_id AND o.user_id = $3
+                )
+            RETURNING ms.match_id, ms.team
+            "#,
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(other_user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let count = removed.len();
+        self.delete_invalidated_matches(tx, removed).await?;
+
+        Ok(count)
+    }
+
+    /// Moves one player's results in a group, and their history, over to another player
+    pub async fn reassign_scores(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE match_scores ms SET user_id = $3
+            FROM matches m
+            JOIN games g ON g.id = m.game_id
+            WHERE ms.match_id = m.id AND g.group_id = $1 AND ms.user_id = $2
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE match_revision_scores rs SET user_id = $3
+            FROM match_revisions r
+            JOIN games g ON g.id = r.game_id
+            WHERE rs.revision_id = r.id AND g.group_id = $1 AND rs.user_id = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM match_revision_scores o WHERE o.revision_id = rs.revision_id AND o.user_id = $3
+                )
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Revisions that already had both players keep the other player's side
+        sqlx::query(
+            r#"
+            DELETE FROM match_revision_scores rs
+            USING match_revisions r
+            JOIN games g ON g.id = r.game_id
+            WHERE rs.revision_id = r.id AND g.group_id = $1 AND rs.user_id = $2
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replaces all scores of a match with the ones provided
+    pub async fn replace_scores(
+        &self,
+        tx: &mut PgConnection,
+        match_id: Uuid,
+        scores: Vec<MatchScoreDb>,
+    ) -> Result<Vec<MatchScoreDb>, sqlx::Error> {
+        sqlx::query("DELETE FROM match_scores WHERE match_id = $1")
+            .bind(match_id)
+            .execute(&mut *tx)
+            .await?;
+
+        self.insert_scores(tx, match_id, scores).await
+    }
+
+    /// Moves a match to when it was actually played, along with the season running at that time
+    pub async fn update_played_at(
+        &self,
+        tx: &mut PgConnection,
+        match_id: Uuid,
+        played_at: DateTime<Utc>,
+        season_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE matches SET played_at = $1, season_id = $2 WHERE id = $3")
+            .bind(played_at)
+            .bind(season_id)
+            .bind(match_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts the matches of a game that were played when none of its seasons were running
+    pub async fn count_outside_seasons(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM matches m
+            WHERE m.game_id = $1 AND NOT EXISTS (
+                SELECT 1 FROM seasons s
+                WHERE s.game_id = m.game_id
+                    AND s.start_date <= m.played_at
+                    AND (s.end_date IS NULL OR s.end_date > m.played_at)
+            )
+            "#,
+        )
+        .bind(game_id)
         .fetch_one(&mut *tx)
+        .await
+    }
+
+    /// Moves the matches of a game into the season running when they were played, after the
+    /// seasons' dates have changed. Returns the seasons that matches were moved out of and into
+    pub async fn reassign_seasons(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let moves = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            UPDATE matches m SET season_id = s.id
+            FROM matches old, seasons s
+            WHERE old.id = m.id
+                AND m.game_id = $1
+                AND s.game_id = m.game_id
+                AND s.start_date <= m.played_at
+                AND (s.end_date IS NULL OR s.end_date > m.played_at)
+                AND m.season_id <> s.id
+            RETURNING old.season_id, m.season_id
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(&mut *tx)
         .await?;
 
-        let mut query_builder: QueryBuilder<Postgres> =
-            QueryBuilder::new("INSERT INTO match_scores (match_id, user_id, score) ");
+        let mut season_ids = Vec::new();
+        for (from, to) in moves {
+            for season_id in [from, to] {
+                if !season_ids.contains(&season_id) {
+                    season_ids.push(season_id);
+                }
+            }
+        }
+
+        Ok(season_ids)
+    }
+
+    pub async fn delete(&self, tx: &mut PgConnection, match_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM matches WHERE id = $1")
+            .bind(match_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_revision(
+        &self,
+        tx: &mut PgConnection,
+        match_id: Uuid,
+        game_id: Uuid,
+        action: MatchRevisionAction,
+        changed_by: Uuid,
+        changes: MatchChanges,
+    ) -> Result<Uuid, sqlx::Error> {
+        let revision_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO match_revisions (match_id, game_id, action, changed_by, previous_played_at, new_played_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        )
+        .bind(match_id)
+        .bind(game_id)
+        .bind(action)
+        .bind(changed_by)
+        .bind(changes.played_at.map(|(previous, _)| previous))
+        .bind(changes.played_at.map(|(_, new)| new))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if changes.scores.is_empty() {
+            return Ok(revision_id);
+        }
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO match_revision_scores (revision_id, user_id, previous_score, new_score, previous_placement, new_placement, previous_outcome, new_outcome, previous_team, new_team) ",
+        );
+
+        query_builder.push_values(changes.scores, |mut b, change| {
+            b.push_bind(revision_id)
+                .push_bind(change.user_id)
+                .push_bind(change.previous_score)
+                .push_bind(change.new_score)
+                .push_bind(change.previous_placement)
+                .push_bind(change.new_placement)
+                .push_bind(change.previous_outcome)
+                .push_bind(change.new_outcome)
+                .push_bind(change.previous_team)
+                .push_bind(change.new_team);
+        });
+
+        query_builder.build().execute(&mut *tx).await?;
+
+        Ok(revision_id)
+    }
+
+    /// Gets all revisions of a match, newest first
+    pub async fn get_revisions(
+        &self,
+        tx: &mut PgConnection,
+        match_id: Uuid,
+    ) -> Result<Vec<MatchRevisionWithScores>, sqlx::Error> {
+        let revisions = sqlx::query_as::<_, MatchRevisionDb>(
+            r#"
+            SELECT mr.*, u.name AS changed_by_name
+            FROM match_revisions mr
+            JOIN users u ON u.id = mr.changed_by
+            WHERE mr.match_id = $1
+            ORDER BY mr.changed_at DESC
+            "#,
+        )
+        .bind(match_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let revision_ids: Vec<Uuid> = revisions.iter().map(|r| r.id).collect();
+        let scores = sqlx::query_as::<_, MatchRevisionScoreDb>(
+            "SELECT * FROM match_revision_scores WHERE revision_id = ANY($1)",
+        )
+        .bind(&revision_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut scores_by_revision: HashMap<Uuid, Vec<MatchRevisionScoreDb>> = HashMap::new();
+        for score in scores {
+            scores_by_revision
+                .entry(score.revision_id)
+                .or_default()
+                .push(score);
+        }
+
+        Ok(revisions
+            .into_iter()
+            .map(|revision| MatchRevisionWithScores {
+                scores: scores_by_revision.remove(&revision.id).unwrap_or_default(),
+                revision,
+            })
+            .collect())
+    }
+
+    async fn insert_scores(
+        &self,
+        tx: &mut PgConnection,
+        match_id: Uuid,
+        scores: Vec<MatchScoreDb>,
+    ) -> Result<Vec<MatchScoreDb>, sqlx::Error> {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO match_scores (match_id, user_id, score, placement, outcome, team) ",
+        );
 
         query_builder.push_values(scores, |mut b, score| {
-            b.push_bind(match_details.id)
+            b.push_bind(match_id)
                 .push_bind(score.user_id)
-                .push_bind(score.score);
+                .push_bind(score.score)
+                .push_bind(score.placement)
+                .push_bind(score.outcome)
+                .push_bind(score.team);
         });
 
         query_builder.push(" RETURNING *");
-        let scores = query_builder
+        query_builder
             .build_query_as::<MatchScoreDb>()
             .fetch_all(&mut *tx)
-            .await?;
+            .await
+    }
+}
 
-        Ok(MatchDb {
-            id: match_details.id,
-            game_id: match_details.game_id,
-            played_at: match_details.played_at,
-            scores,
-        })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::game_match::MatchOutcome;
+    use sqlx::PgPool;
+
+    async fn create_user(pool: &PgPool, email: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO users (name, email, password_hash) VALUES ('a', $1, 'x') RETURNING id",
+        )
+        .bind(email)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Creates a group with a game in its first season, returning all three IDs
+    async fn create_game(pool: &PgPool, created_by: Uuid) -> (Uuid, Uuid, Uuid) {
+        let group_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO groups (name, created_by) VALUES ('group', $1) RETURNING id",
+        )
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let game_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO games (group_id, name, min_players_per_match, max_players_per_match) VALUES ($1, 'game', 1, 5) RETURNING id",
+        )
+        .bind(group_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let season_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO seasons (game_id, number, start_date) VALUES ($1, 1, NOW() - INTERVAL '1 day') RETURNING id",
+        )
+        .bind(game_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        (group_id, game_id, season_id)
+    }
+
+    fn score(user_id: Uuid) -> MatchScoreDb {
+        MatchScoreDb {
+            user_id,
+            score: Some(1),
+            placement: None,
+            outcome: None,
+            team: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_user_scores_removes_matches_left_empty(pool: PgPool) {
+        let repo = MatchRepo {};
+        let leaving = create_user(&pool, "a@a.com").await;
+        let staying = create_user(&pool, "b@b.com").await;
+
+        let (_, game_id, season_id) = create_game(&pool, leaving).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let shared = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                leaving,
+                Utc::now(),
+                vec![score(leaving), score(staying)],
+            )
+            .await
+            .unwrap();
+        let solo = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                leaving,
+                Utc::now(),
+                vec![score(leaving)],
+            )
+            .await
+            .unwrap();
+
+        repo.delete_user_scores(&mut tx, leaving).await.unwrap();
+
+        let scores = repo.get_scores(&mut *tx, shared.id).await.unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].user_id, staying);
+
+        assert!(repo.get(&mut *tx, solo.id).await.unwrap().is_none());
+        assert!(
+            repo.get_user_scores(&mut *tx, leaving)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_reassigning_scores_moves_them_to_the_other_player(pool: PgPool) {
+        let repo = MatchRepo {};
+        let guest = create_user(&pool, "guest@a.com").await;
+        let other = create_user(&pool, "other@a.com").await;
+        let claimer = create_user(&pool, "claimer@a.com").await;
+        let (group_id, game_id, season_id) = create_game(&pool, other).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let game_match = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                other,
+                Utc::now(),
+                vec![score(guest), score(other)],
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            repo.share_matches(&mut *tx, group_id, guest, other)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .share_matches(&mut *tx, group_id, guest, claimer)
+                .await
+                .unwrap()
+        );
+
+        repo.reassign_scores(&mut tx, group_id, guest, claimer)
+            .await
+            .unwrap();
+
+        let scores = repo.get_scores(&mut *tx, game_match.id).await.unwrap();
+        let players: Vec<Uuid> = scores.iter().map(|s| s.user_id).collect();
+        assert!(players.contains(&claimer));
+        assert!(!players.contains(&guest));
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_shared_scores_only_touches_matches_played_together(pool: PgPool) {
+        let repo = MatchRepo {};
+        let duplicate = create_user(&pool, "duplicate@a.com").await;
+        let kept = create_user(&pool, "kept@a.com").await;
+        let (group_id, game_id, season_id) = create_game(&pool, kept).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let shared = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                kept,
+                Utc::now(),
+                vec![score(duplicate), score(kept)],
+            )
+            .await
+            .unwrap();
+        let solo = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                kept,
+                Utc::now(),
+                vec![score(duplicate)],
+            )
+            .await
+            .unwrap();
+
+        let removed = repo
+            .delete_shared_scores(&mut tx, group_id, duplicate, kept)
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        let scores = repo.get_scores(&mut *tx, shared.id).await.unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].user_id, kept);
+
+        let scores = repo.get_scores(&mut *tx, solo.id).await.unwrap();
+        assert_eq!(scores[0].user_id, duplicate);
+    }
+
+    #[sqlx::test]
+    async fn test_best_scores_leave_out_the_given_match(pool: PgPool) {
+        let repo = MatchRepo {};
+        let player = create_user(&pool, "player@a.com").await;
+        let newcomer = create_user(&pool, "newcomer@a.com").await;
+        let (_, game_id, season_id) = create_game(&pool, player).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        for points in [3, 7] {
+            repo.create(
+                &mut tx,
+                game_id,
+                season_id,
+                player,
+                Utc::now(),
+                vec![MatchScoreDb {
+                    score: Some(points),
+                    ..score(player)
+                }],
+            )
+            .await
+            .unwrap();
+        }
+
+        let latest = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                player,
+                Utc::now(),
+                vec![
+                    MatchScoreDb {
+                        score: Some(9),
+                        ..score(player)
+                    },
+                    score(newcomer),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let best = repo
+            .get_best_scores(&mut *tx, game_id, &[player, newcomer], latest.id)
+            .await
+            .unwrap();
+
+        assert_eq!(best, vec![(player, 7)]);
+    }
+
+    #[sqlx::test]
+    async fn test_reassigning_seasons_follows_moved_dates(pool: PgPool) {
+        let repo = MatchRepo {};
+        let user = create_user(&pool, "a@a.com").await;
+        let (_, game_id, first_season) = create_game(&pool, user).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let played_at = Utc::now() - chrono::Duration::hours(12);
+        let game_match = repo
+            .create(
+                &mut tx,
+                game_id,
+                first_season,
+                user,
+                played_at,
+                vec![score(user)],
+            )
+            .await
+            .unwrap();
+
+        // End the first season before the match, with a second one starting in its place
+        sqlx::query("UPDATE seasons SET end_date = $1 WHERE id = $2")
+            .bind(played_at - chrono::Duration::hours(1))
+            .bind(first_season)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.count_outside_seasons(&mut tx, game_id).await.unwrap(),
+            1
+        );
+
+        let second_season: Uuid = sqlx::query_scalar(
+            "INSERT INTO seasons (game_id, number, start_date) VALUES ($1, 2, $2) RETURNING id",
+        )
+        .bind(game_id)
+        .bind(played_at - chrono::Duration::hours(1))
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        assert_eq!(
+            repo.count_outside_seasons(&mut tx, game_id).await.unwrap(),
+            0
+        );
+
+        let moved = repo.reassign_seasons(&mut tx, game_id).await.unwrap();
+        assert_eq!(moved, vec![first_season, second_season]);
+
+        let game_match = repo.get(&mut *tx, game_match.id).await.unwrap().unwrap();
+        assert_eq!(game_match.season_id, second_season);
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_user_scores_removes_matches_below_min_players(pool: PgPool) {
+        let repo = MatchRepo {};
+        let leaving = create_user(&pool, "a@a.com").await;
+        let staying = create_user(&pool, "b@b.com").await;
+        let (_, game_id, season_id) = create_game(&pool, leaving).await;
+
+        sqlx::query(
+            "UPDATE games SET min_players_per_match = 2, max_players_per_match = 2 WHERE id = $1",
+        )
+        .bind(game_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        let game_match = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                staying,
+                Utc::now(),
+                vec![score(leaving), score(staying)],
+            )
+            .await
+            .unwrap();
+
+        repo.delete_user_scores(&mut tx, leaving).await.unwrap();
+
+        assert!(repo.get(&mut *tx, game_match.id).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_user_scores_removes_matches_that_lose_a_team(pool: PgPool) {
+        let repo = MatchRepo {};
+        let leaving = create_user(&pool, "a@a.com").await;
+        let staying = create_user(&pool, "b@b.com").await;
+        let teammate = create_user(&pool, "c@c.com").await;
+        let (_, game_id, season_id) = create_game(&pool, leaving).await;
+
+        let in_team = |user_id, team| MatchScoreDb {
+            team: Some(team),
+            ..score(user_id)
+        };
+
+        let mut tx = pool.begin().await.unwrap();
+        let game_match = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                staying,
+                Utc::now(),
+                vec![
+                    in_team(leaving, 1),
+                    in_team(staying, 2),
+                    in_team(teammate, 2),
+                ],
+            )
+            .await
+            .unwrap();
+
+        repo.delete_user_scores(&mut tx, leaving).await.unwrap();
+
+        assert!(repo.get(&mut *tx, game_match.id).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_user_scores_removes_win_loss_matches_left_without_a_winner(
+        pool: PgPool,
+    ) {
+        let repo = MatchRepo {};
+        let leaving = create_user(&pool, "a@a.com").await;
+        let staying = create_user(&pool, "b@b.com").await;
+        let other = create_user(&pool, "c@c.com").await;
+        let (_, game_id, season_id) = create_game(&pool, leaving).await;
+
+        sqlx::query("UPDATE games SET result_type = 'win_loss' WHERE id = $1")
+            .bind(game_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let with_outcome = |user_id, outcome| MatchScoreDb {
+            score: None,
+            outcome: Some(outcome),
+            ..score(user_id)
+        };
+
+        let mut tx = pool.begin().await.unwrap();
+        let won = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                staying,
+                Utc::now(),
+                vec![
+                    with_outcome(leaving, MatchOutcome::Win),
+                    with_outcome(staying, MatchOutcome::Loss),
+                    with_outcome(other, MatchOutcome::Loss),
+                ],
+            )
+            .await
+            .unwrap();
+        let lost = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                staying,
+                Utc::now(),
+                vec![
+                    with_outcome(leaving, MatchOutcome::Loss),
+                    with_outcome(staying, MatchOutcome::Win),
+                    with_outcome(other, MatchOutcome::Loss),
+                ],
+            )
+            .await
+            .unwrap();
+
+        repo.delete_user_scores(&mut tx, leaving).await.unwrap();
+
+        assert!(repo.get(&mut *tx, won.id).await.unwrap().is_none());
+        assert!(repo.get(&mut *tx, lost.id).await.unwrap().is_some());
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_shared_scores_removes_matches_below_min_players(pool: PgPool) {
+        let repo = MatchRepo {};
+        let duplicate = create_user(&pool, "duplicate@a.com").await;
+        let kept = create_user(&pool, "kept@a.com").await;
+        let other = create_user(&pool, "other@a.com").await;
+        let (group_id, game_id, season_id) = create_game(&pool, kept).await;
+
+        sqlx::query("UPDATE games SET min_players_per_match = 2 WHERE id = $1")
+            .bind(game_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        let pair = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                kept,
+                Utc::now(),
+                vec![score(duplicate), score(kept)],
+            )
+            .await
+            .unwrap();
+        let trio = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                kept,
+                Utc::now(),
+                vec![score(duplicate), score(kept), score(other)],
+            )
+            .await
+            .unwrap();
+
+        repo.delete_shared_scores(&mut tx, group_id, duplicate, kept)
+            .await
+            .unwrap();
+
+        assert!(repo.get(&mut *tx, pair.id).await.unwrap().is_none());
+        assert_eq!(repo.get_scores(&mut *tx, trio.id).await.unwrap().len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn test_deleting_shared_scores_removes_win_loss_matches_left_without_a_winner(
+        pool: PgPool,
+    ) {
+        let repo = MatchRepo {};
+        let duplicate = create_user(&pool, "duplicate@a.com").await;
+        let kept = create_user(&pool, "kept@a.com").await;
+        let other = create_user(&pool, "other@a.com").await;
+        let (group_id, game_id, season_id) = create_game(&pool, kept).await;
+
+        sqlx::query("UPDATE games SET result_type = 'win_loss' WHERE id = $1")
+            .bind(game_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let with_outcome = |user_id, outcome| MatchScoreDb {
+            score: None,
+            outcome: Some(outcome),
+            ..score(user_id)
+        };
+
+        let mut tx = pool.begin().await.unwrap();
+        let won = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                kept,
+                Utc::now(),
+                vec![
+                    with_outcome(duplicate, MatchOutcome::Win),
+                    with_outcome(kept, MatchOutcome::Loss),
+                    with_outcome(other, MatchOutcome::Loss),
+                ],
+            )
+            .await
+            .unwrap();
+        let lost = repo
+            .create(
+                &mut tx,
+                game_id,
+                season_id,
+                kept,
+                Utc::now(),
+                vec![
+                    with_outcome(duplicate, MatchOutcome::Loss),
+                    with_outcome(kept, MatchOutcome::Win),
+                    with_outcome(other, MatchOutcome::Loss),
+                ],
+            )
+            .await
+            .unwrap();
+
+        repo.delete_shared_scores(&mut tx, group_id, duplicate, kept)
+            .await
+            .unwrap();
+
+        assert!(repo.get(&mut *tx, won.id).await.unwrap().is_none());
+        assert!(repo.get(&mut *tx, lost.id).await.unwrap().is_some());
     }
 }
@@ -1,3 +1,5 @@
+pub mod activity_repo;
+pub mod fixture_repo;
 pub mod game_repo;
 pub mod group_repo;
 pub mod invite_repo;
@@ -5,5 +7,6 @@ pub mod match_repo;
 pub mod password_resets_repo;
 pub mod season_repo;
 pub mod stats_repo;
+pub mod tournament_repo;
 pub mod user_repo;
 pub mod verification_repo;
@@ -1,8 +1,11 @@
 use chrono::{DateTime, Utc};
-use sqlx::PgConnection;
+use sqlx::{PgConnection, PgExecutor, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::models::{game::Interval, season::SeasonDb};
+use crate::models::{
+    season::{SeasonDb, SeasonResultDb, SeasonResultEntryDb, TrophyDb},
+    stats::{RawHighlight, ScoreboardEntry},
+};
 
 pub struct SeasonRepo {}
 
@@ -37,6 +40,20 @@ impl SeasonRepo {
         Ok(seasons)
     }
 
+    /// Gets every season of the games provided, oldest first
+    pub async fn get_for_games<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_ids: &[Uuid],
+    ) -> Result<Vec<SeasonDb>, sqlx::Error> {
+        sqlx::query_as::<_, SeasonDb>(
+            "SELECT * FROM seasons WHERE game_id = ANY($1) ORDER BY game_id, start_date",
+        )
+        .bind(game_ids)
+        .fetch_all(executor)
+        .await
+    }
+
     pub async fn get_season(
         &self,
         tx: &mut PgConnection,
@@ -54,9 +71,56 @@ impl SeasonRepo {
         Ok(season)
     }
 
+    /// Moves the start of a game's first season back to include matches played before it
+    pub async fn extend_first_season(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+        start_date: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE seasons SET start_date = $2
+            WHERE id = (SELECT id FROM seasons WHERE game_id = $1 ORDER BY start_date LIMIT 1)
+                AND start_date > $2
+            "#,
+        )
+        .bind(game_id)
+        .bind(start_date)
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the season of a game that was running at a point in time
+    pub async fn get_at(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<Option<SeasonDb>, sqlx::Error> {
+        let season = sqlx::query_as::<_, SeasonDb>(
+            r#"
+            SELECT * FROM seasons
+            WHERE game_id = $1 AND start_date <= $2 AND (end_date IS NULL OR end_date > $2)
+            ORDER BY start_date DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(game_id)
+        .bind(at)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        Ok(season)
+    }
+
+    /// Gets the latest season of each game that had ended by `now`
     pub async fn expired_seasons(
         &self,
         tx: &mut PgConnection,
+        now: DateTime<Utc>,
     ) -> Result<Vec<SeasonDb>, sqlx::Error> {
         let season = sqlx::query_as::<_, SeasonDb>(
             r#"
@@ -65,45 +129,127 @@ impl SeasonRepo {
                 FROM seasons
                 ORDER BY game_id, start_date DESC
             ) latest
-            WHERE end_date < NOW()
+            WHERE end_date <= $1
             "#,
         )
+        .bind(now)
         .fetch_all(&mut *tx)
         .await?;
 
         Ok(season)
     }
 
-    // NOTE: this only works for seasons with an end date
+    /// Starts the season following the one that ended at `start_date`, using the game's schedule
     pub async fn new_season(
         &self,
         tx: &mut PgConnection,
-        old_season: &SeasonDb,
+        game_id: Uuid,
+        start_date: DateTime<Utc>,
+    ) -> Result<SeasonDb, sqlx::Error> {
+        let season = sqlx::query_as::<_, SeasonDb>(
+            r#"
+            INSERT INTO seasons (game_id, number, start_date, end_date)
+            SELECT $1, COALESCE(MAX(number), 0) + 1, $2, next_season_end($1, $2)
+            FROM seasons
+            WHERE game_id = $1
+            RETURNING *"#,
+        )
+        .bind(game_id)
+        .bind(start_date)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Ok(season)
+    }
+
+    /// Applies the game's schedule to its latest season. An explicit end date always wins,
+    /// otherwise an open-ended season is given an end date once the game has a season duration,
+    /// and loses it once the game no longer has one
+    pub async fn reschedule_latest(
+        &self,
+        tx: &mut PgConnection,
+        season_id: Uuid,
+        end_date: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<SeasonDb, sqlx::Error> {
+        let season = sqlx::query_as::<_, SeasonDb>(
+            r#"
+            UPDATE seasons s
+            SET end_date = CASE
+                WHEN $2::TIMESTAMPTZ IS NOT NULL THEN $2
+                WHEN g.season_duration IS NULL THEN NULL
+                ELSE COALESCE(s.end_date, next_season_end(g.id, GREATEST(s.start_date, $3)))
+            END
+            FROM games g
+            WHERE s.id = $1 AND g.id = s.game_id
+            RETURNING s.*
+            "#,
+        )
+        .bind(season_id)
+        .bind(end_date)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Ok(season)
+    }
+
+    /// Creates the next season for a game. Without an end date, the game's season schedule is used
+    pub async fn create_season(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+        name: Option<&str>,
+        start_date: DateTime<Utc>,
+        end_date: Option<DateTime<Utc>>,
     ) -> Result<SeasonDb, sqlx::Error> {
         let last_season_number: i32 = sqlx::query_scalar(
             "SELECT number FROM seasons WHERE game_id = $1 ORDER BY start_date DESC LIMIT 1",
         )
-        .bind(old_season.game_id)
+        .bind(game_id)
         .fetch_optional(&mut *tx)
         .await?
         .unwrap_or(0);
 
-        let duration: Option<Interval> =
-            sqlx::query_scalar("SELECT season_duration FROM games WHERE id = $1")
-                .bind(old_season.game_id)
-                .fetch_one(&mut *tx)
-                .await?;
-
         let season = sqlx::query_as::<_, SeasonDb>(
             r#"
-            INSERT INTO seasons (game_id, number, start_date, end_date)
-            VALUES ($1, $2, $3, $3 + $4)
+            INSERT INTO seasons (game_id, number, name, start_date, end_date)
+            VALUES (
+                $1, $2, $3, $4,
+                COALESCE($5, next_season_end($1, $4))
+            )
             RETURNING *"#,
         )
-        .bind(old_season.game_id)
+        .bind(game_id)
         .bind(last_season_number + 1)
-        .bind(old_season.end_date)
-        .bind(duration)
+        .bind(name)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Ok(season)
+    }
+
+    pub async fn update_season(
+        &self,
+        tx: &mut PgConnection,
+        season_id: Uuid,
+        name: Option<&str>,
+        start_date: DateTime<Utc>,
+        end_date: Option<DateTime<Utc>>,
+    ) -> Result<SeasonDb, sqlx::Error> {
+        let season = sqlx::query_as::<_, SeasonDb>(
+            r#"
+            UPDATE seasons SET name = COALESCE($1, name), start_date = $2, end_date = $3
+            WHERE id = $4
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(start_date)
+        .bind(end_date)
+        .bind(season_id)
         .fetch_one(&mut *tx)
         .await?;
 
@@ -126,4 +272,405 @@ impl SeasonRepo {
 
         Ok(season)
     }
+
+    /// Records the final standings of a season, crowning whoever is ranked first. Does nothing if
+    /// the season already has results
+    pub async fn create_results(
+        &self,
+        tx: &mut PgConnection,
+        season_id: Uuid,
+        entries: &[ScoreboardEntry],
+        highlights: &[RawHighlight],
+    ) -> Result<(), sqlx::Error> {
+        let inserted = sqlx::query(
+            "INSERT INTO season_results (season_id, champion_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(season_id)
+        .bind(entries.first().map(|e| e.user_id))
+        .execute(&mut *tx)
+        .await?;
+
+        if inserted.rows_affected() == 0 {
+            return Ok(());
+        }
+
+        if !entries.is_empty() {
+            let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO season_result_entries (season_id, user_id, rank, matches_played, wins, win_rate, average_score, best_score, rating, star_medals, gold_medals, silver_medals, bronze_medals) ",
+            );
+
+            query_builder.push_values(entries, |mut b, entry| {
+                b.push_bind(season_id)
+                    .push_bind(entry.user_id)
+                    .push_bind(entry.rank)
+                    .push_bind(entry.matches_played)
+                    .push_bind(entry.wins)
+                    .push_bind(entry.win_rate)
+                    .push_bind(entry.average_score)
+                    .push_bind(entry.best_score)
+                    .push_bind(entry.rating)
+                    .push_bind(entry.star_medals as i32)
+                    .push_bind(entry.gold_medals as i32)
+                    .push_bind(entry.silver_medals as i32)
+                    .push_bind(entry.bronze_medals as i32);
+            });
+
+            query_builder.build().execute(&mut *tx).await?;
+        }
+
+        if !highlights.is_empty() {
+            let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO season_result_highlights (season_id, user_id, stat_type, value) ",
+            );
+
+            query_builder.push_values(highlights, |mut b, highlight| {
+                b.push_bind(season_id)
+                    .push_bind(highlight.user_id)
+                    .push_bind(&highlight.stat_type)
+                    .push_bind(highlight.value);
+            });
+
+            query_builder.build().execute(&mut *tx).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the final standings of a season, so they can be worked out again
+    pub async fn delete_results(
+        &self,
+        tx: &mut PgConnection,
+        season_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM season_results WHERE season_id = $1")
+            .bind(season_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_results<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        season_id: Uuid,
+    ) -> Result<Option<SeasonResultDb>, sqlx::Error> {
+        sqlx::query_as::<_, SeasonResultDb>("SELECT * FROM season_results WHERE season_id = $1")
+            .bind(season_id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    pub async fn get_result_entries<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        season_id: Uuid,
+    ) -> Result<Vec<SeasonResultEntryDb>, sqlx::Error> {
+        sqlx::query_as::<_, SeasonResultEntryDb>(
+            r#"
+            SELECT
+                sre.*,
+                u.name as user_name,
+                u.avatar as user_avatar,
+                u.avatar_colour as user_avatar_colour
+            FROM season_result_entries sre
+            JOIN users u ON u.id = sre.user_id
+            WHERE sre.season_id = $1
+            ORDER BY sre.rank
+            "#,
+        )
+        .bind(season_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn get_result_highlights<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        season_id: Uuid,
+    ) -> Result<Vec<RawHighlight>, sqlx::Error> {
+        sqlx::query_as::<_, RawHighlight>(
+            r#"
+            SELECT srh.user_id, u.name as user_name, srh.value, srh.stat_type
+            FROM season_result_highlights srh
+            JOIN users u ON u.id = srh.user_id
+            WHERE srh.season_id = $1
+            "#,
+        )
+        .bind(season_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Gets the seasons a user won, in games from groups the viewer is a member of
+    pub async fn get_trophies<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        user_id: Uuid,
+        viewer_id: Uuid,
+    ) -> Result<Vec<TrophyDb>, sqlx::Error> {
+        sqlx::query_as::<_, TrophyDb>(
+            r#"
+            SELECT
+                sr.season_id,
+                s.number as season_number,
+                s.name as season_name,
+                g.id as game_id,
+                g.name as game_name,
+                g.group_id,
+                sr.finalised_at
+            FROM season_results sr
+            JOIN seasons s ON s.id = sr.season_id
+            JOIN games g ON g.id = s.game_id
+            WHERE sr.champion_id = $1
+                AND EXISTS (
+                    SELECT 1 FROM group_members gm WHERE gm.group_id = g.group_id AND gm.user_id = $2
+                )
+            ORDER BY sr.finalised_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(viewer_id)
+        .fetch_all(executor)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::PgPool;
+
+    async fn create_game(
+        pool: &PgPool,
+        season_duration: Option<&str>,
+        season_alignment: &str,
+        first_season_start: DateTime<Utc>,
+    ) -> Uuid {
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (name, email, password_hash) VALUES ('a', 'a@a.com', 'x') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let group_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO groups (name, created_by) VALUES ('group', $1) RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let game_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO games (group_id, name, min_players_per_match, max_players_per_match, season_duration, season_alignment, season_timezone, created_at)
+            VALUES ($1, 'game', 1, 5, $2::INTERVAL, $3::season_alignment, 'Europe/London', $4)
+            RETURNING id
+            "#,
+        )
+        .bind(group_id)
+        .bind(season_duration)
+        .bind(season_alignment)
+        .bind(first_season_start)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO seasons (game_id, number, start_date, end_date) VALUES ($1, 1, $2, next_season_end($1, $2))",
+        )
+        .bind(game_id)
+        .bind(first_season_start)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        game_id
+    }
+
+    async fn set_season_duration(pool: &PgPool, game_id: Uuid, season_duration: Option<&str>) {
+        sqlx::query("UPDATE games SET season_duration = $1::INTERVAL WHERE id = $2")
+            .bind(season_duration)
+            .bind(game_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    /// Mirrors the clean up job, without recording results
+    async fn roll_over_expired(repo: &SeasonRepo, tx: &mut PgConnection, now: DateTime<Utc>) {
+        let mut expired_seasons = repo.expired_seasons(tx, now).await.unwrap();
+
+        while !expired_seasons.is_empty() {
+            for season in &expired_seasons {
+                repo.new_season(tx, season.game_id, season.end_date.unwrap())
+                    .await
+                    .unwrap();
+            }
+
+            expired_seasons = repo.expired_seasons(tx, now).await.unwrap();
+        }
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_downtime_creates_every_missed_season(pool: PgPool) {
+        let repo = SeasonRepo {};
+        let game_id = create_game(&pool, Some("7 days"), "none", utc(2026, 1, 5, 0)).await;
+        let now = utc(2026, 3, 18, 12);
+
+        let mut tx = pool.begin().await.unwrap();
+        roll_over_expired(&repo, &mut tx, now).await;
+
+        // Newest first
+        let seasons = repo.get_seasons(&mut tx, game_id).await.unwrap();
+        assert_eq!(seasons.len(), 11);
+
+        for (newer, older) in seasons.iter().zip(seasons.iter().skip(1)) {
+            assert_eq!(newer.number, older.number + 1);
+            assert_eq!(Some(newer.start_date), older.end_date);
+        }
+
+        let latest = &seasons[0];
+        assert_eq!(latest.start_date, utc(2026, 3, 16, 0));
+        assert!(latest.end_date.unwrap() > now);
+
+        // Running the job again changes nothing
+        roll_over_expired(&repo, &mut tx, now).await;
+        let seasons = repo.get_seasons(&mut tx, game_id).await.unwrap();
+        assert_eq!(seasons.len(), 11);
+    }
+
+    #[sqlx::test]
+    async fn test_aligned_seasons_follow_calendar_in_game_time_zone(pool: PgPool) {
+        let repo = SeasonRepo {};
+        let game_id = create_game(&pool, Some("1 month"), "month", utc(2026, 6, 10, 9)).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let first = repo.get_latest(&mut tx, game_id).await.unwrap();
+
+        // Midnight on the 1st in London is 11pm UTC during summer time
+        assert_eq!(first.end_date, Some(utc(2026, 6, 30, 23)));
+
+        let second = repo
+            .new_season(&mut tx, game_id, first.end_date.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(second.end_date, Some(utc(2026, 7, 31, 23)));
+
+        let new_year = repo
+            .new_season(&mut tx, game_id, utc(2026, 12, 1, 0))
+            .await
+            .unwrap();
+        assert_eq!(new_year.end_date, Some(utc(2027, 1, 1, 0)));
+    }
+
+    #[sqlx::test]
+    async fn test_open_ended_seasons_never_expire(pool: PgPool) {
+        let repo = SeasonRepo {};
+        let game_id = create_game(&pool, None, "none", utc(2026, 1, 1, 0)).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let season = repo.get_latest(&mut tx, game_id).await.unwrap();
+        assert_eq!(season.end_date, None);
+
+        let expired = repo
+            .expired_seasons(&mut tx, utc(2036, 1, 1, 0))
+            .await
+            .unwrap();
+        assert!(expired.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn test_switching_to_fixed_duration_schedules_open_ended_season(pool: PgPool) {
+        let repo = SeasonRepo {};
+        let game_id = create_game(&pool, None, "none", utc(2026, 1, 1, 0)).await;
+        set_season_duration(&pool, game_id, Some("1 month")).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let season = repo.get_latest(&mut tx, game_id).await.unwrap();
+
+        // Scheduled from now rather than from a start date months ago, keeping the same local time
+        // in London after the clocks go forward
+        let now = utc(2026, 3, 10, 12);
+        let season = repo
+            .reschedule_latest(&mut tx, season.id, None, now)
+            .await
+            .unwrap();
+        assert_eq!(season.end_date, Some(utc(2026, 4, 10, 11)));
+
+        // An existing end date is kept
+        let season = repo
+            .reschedule_latest(&mut tx, season.id, None, utc(2026, 3, 20, 0))
+            .await
+            .unwrap();
+        assert_eq!(season.end_date, Some(utc(2026, 4, 10, 11)));
+    }
+
+    #[sqlx::test]
+    async fn test_switching_to_open_ended_clears_end_date(pool: PgPool) {
+        let repo = SeasonRepo {};
+        let game_id = create_game(&pool, Some("7 days"), "none", utc(2026, 1, 5, 0)).await;
+        set_season_duration(&pool, game_id, None).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let season = repo.get_latest(&mut tx, game_id).await.unwrap();
+        assert!(season.end_date.is_some());
+
+        let season = repo
+            .reschedule_latest(&mut tx, season.id, None, utc(2026, 1, 6, 0))
+            .await
+            .unwrap();
+        assert_eq!(season.end_date, None);
+
+        // A final fixed season can still be ended, after which the next one is open-ended
+        let end_date = utc(2026, 1, 8, 0);
+        let season = repo
+            .reschedule_latest(&mut tx, season.id, Some(end_date), utc(2026, 1, 6, 0))
+            .await
+            .unwrap();
+        assert_eq!(season.end_date, Some(end_date));
+
+        roll_over_expired(&repo, &mut tx, utc(2026, 2, 1, 0)).await;
+        let latest = repo.get_latest(&mut tx, game_id).await.unwrap();
+        assert_eq!(latest.number, 2);
+        assert_eq!(latest.start_date, end_date);
+        assert_eq!(latest.end_date, None);
+    }
+
+    #[sqlx::test]
+    async fn test_back_dated_match_finds_season_running_at_the_time(pool: PgPool) {
+        let repo = SeasonRepo {};
+        let game_id = create_game(&pool, Some("7 days"), "none", utc(2026, 1, 5, 0)).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        roll_over_expired(&repo, &mut tx, utc(2026, 1, 20, 0)).await;
+
+        let season = repo
+            .get_at(&mut tx, game_id, utc(2026, 1, 11, 23))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(season.number, 1);
+
+        // Boundaries belong to the season that starts there
+        let season = repo
+            .get_at(&mut tx, game_id, utc(2026, 1, 12, 0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(season.number, 2);
+
+        // Before the game's first season
+        let season = repo
+            .get_at(&mut tx, game_id, utc(2026, 1, 1, 0))
+            .await
+            .unwrap();
+        assert!(season.is_none());
+    }
 }
@@ -1,13 +1,17 @@
+use sqlx::{PgExecutor, Postgres};
 use uuid::Uuid;
 
-use crate::models::stats::{PlayerMatchDb, RawHighlight, RawMatchStats};
+use crate::models::{
+    game::ScoreDirection,
+    stats::{PlayerMatchDb, RawHighlight, RawMatchStats},
+};
 
 pub struct StatsRepo {}
 
 impl StatsRepo {
-    pub async fn get_all_matches(
+    pub async fn get_all_matches<'e>(
         &self,
-        pool: &sqlx::PgPool,
+        executor: impl PgExecutor<'e, Database = Postgres>,
         game_id: Uuid,
         season_id: Option<Uuid>,
     ) -> Result<Vec<RawMatchStats>, sqlx::Error> {
@@ -20,6 +24,7 @@ impl StatsRepo {
                 lb.user_id,
                 lb.match_id,
                 lb.score,
+                lb.team,
                 m.played_at,
                 lb.rank as rank_in_match
             FROM match_leaderboards lb
@@ -31,7 +36,7 @@ impl StatsRepo {
         )
         .bind(game_id)
         .bind(season_id)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await
     }
 
@@ -47,6 +52,7 @@ impl StatsRepo {
             SELECT
                 lb.match_id,
                 lb.score,
+                lb.outcome,
                 lb.rank as rank_in_match,
                 m.played_at
             FROM matches m
@@ -62,11 +68,12 @@ impl StatsRepo {
         .await
     }
 
-    pub async fn get_highlights(
+    pub async fn get_highlights<'e>(
         &self,
-        pool: &sqlx::PgPool,
+        executor: impl PgExecutor<'e, Database = Postgres>,
         game_id: Uuid,
         season_id: Option<Uuid>,
+        score_direction: ScoreDirection,
     ) -> Result<Vec<RawHighlight>, sqlx::Error> {
         let rows = sqlx::query_as::<_, RawHighlight>(
             r#"
@@ -78,7 +85,7 @@ impl StatsRepo {
                 WHERE m.game_id = $1 AND ($2 IS NULL OR season_id = $2)
             ),
             ranks AS (
-                SELECT lb.user_id, lb.rank
+                SELECT lb.user_id, lb.match_id, lb.rank, m.played_at
                 FROM match_leaderboards lb
                 JOIN matches m ON lb.match_id = m.id
                 WHERE m.game_id = $1 AND ($2 IS NULL OR season_id = $2)
@@ -94,7 +101,7 @@ impl StatsRepo {
                 GROUP BY user_id
             ),
 
-            -- Highest average score
+            -- Best average score
             stat_avg_score AS (
                 SELECT
                     user_id,
@@ -104,16 +111,50 @@ impl StatsRepo {
                 GROUP BY user_id
             ),
 
-            -- Highest single score
+            -- Best single score
             stat_high_score AS (
                 SELECT
                     user_id,
-                    MAX(score)::FLOAT8 as val,
+                    (CASE WHEN $3 = 'lower_is_better' THEN MIN(score) ELSE MAX(score) END)::FLOAT8 as val,
                     'highest_single_score' as stat_type
                 FROM scores
                 GROUP BY user_id
             ),
 
+            -- Longest run of consecutive wins, grouping wins by how many matches came before them
+            win_streaks AS (
+                SELECT
+                    user_id,
+                    ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY played_at, match_id)
+                        - ROW_NUMBER() OVER (PARTITION BY user_id, rank = 1 ORDER BY played_at, match_id) as streak,
+                    rank = 1 as won
+                FROM ranks
+            ),
+            stat_win_streak AS (
+                SELECT
+                    user_id,
+                    MAX(length)::FLOAT8 as val,
+                    'longest_win_streak' as stat_type
+                FROM (
+                    SELECT user_id, COUNT(*) as length
+                    FROM win_streaks
+                    WHERE won
+                    GROUP BY user_id, streak
+                ) streak_lengths
+                GROUP BY user_id
+            ),
+
+            -- Lowest standard deviation of scores, ignoring players without enough matches for it to mean much
+            stat_consistency AS (
+                SELECT
+                    user_id,
+                    STDDEV_POP(score)::FLOAT8 as val,
+                    'most_consistent' as stat_type
+                FROM scores
+                GROUP BY user_id
+                HAVING COUNT(score) >= 5
+            ),
+
             -- Most games played
             stat_most_games AS (
                 SELECT
@@ -127,9 +168,15 @@ impl StatsRepo {
             -- Combine them all, keeping only tied leaders per stat
             all_stats AS (
                 SELECT * FROM stat_win_rate WHERE val = (SELECT MAX(val) FROM stat_win_rate)
-                UNION ALL SELECT * FROM stat_avg_score WHERE val = (SELECT MAX(val) FROM stat_avg_score)
-                UNION ALL SELECT * FROM stat_high_score WHERE val = (SELECT MAX(val) FROM stat_high_score)
+                UNION ALL SELECT * FROM stat_avg_score WHERE val = (
+                    SELECT CASE WHEN $3 = 'lower_is_better' THEN MIN(val) ELSE MAX(val) END FROM stat_avg_score
+                )
+                UNION ALL SELECT * FROM stat_high_score WHERE val = (
+                    SELECT CASE WHEN $3 = 'lower_is_better' THEN MIN(val) ELSE MAX(val) END FROM stat_high_score
+                )
                 UNION ALL SELECT * FROM stat_most_games WHERE val = (SELECT MAX(val) FROM stat_most_games)
+                UNION ALL SELECT * FROM stat_win_streak WHERE val = (SELECT MAX(val) FROM stat_win_streak)
+                UNION ALL SELECT * FROM stat_consistency WHERE val = (SELECT MIN(val) FROM stat_consistency)
             )
 
             -- Join with users to get names
@@ -144,7 +191,8 @@ impl StatsRepo {
         )
         .bind(game_id)
         .bind(season_id)
-        .fetch_all(pool)
+        .bind(score_direction)
+        .fetch_all(executor)
         .await?;
 
         Ok(rows)
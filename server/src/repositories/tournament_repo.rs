@@ -0,0 +1,280 @@
+use sqlx::{PgConnection, PgExecutor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::models::tournament::{
+    TournamentDb, TournamentFormat, TournamentParticipantDb, TournamentSlotDb,
+};
+
+pub struct TournamentRepo {}
+
+impl TournamentRepo {
+    pub async fn create(
+        &self,
+        tx: &mut PgConnection,
+        game_id: Uuid,
+        name: &str,
+        format: TournamentFormat,
+        created_by: Uuid,
+    ) -> Result<TournamentDb, sqlx::Error> {
+        sqlx::query_as::<_, TournamentDb>(
+            "INSERT INTO tournaments (game_id, name, format, created_by) VALUES ($1, $2, $3, $4) RETURNING *",
+        )
+        .bind(game_id)
+        .bind(name)
+        .bind(format)
+        .bind(created_by)
+        .fetch_one(tx)
+        .await
+    }
+
+    /// Adds the players of a tournament, seeded in the order given
+    pub async fn add_participants(
+        &self,
+        tx: &mut PgConnection,
+        tournament_id: Uuid,
+        player_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO tournament_participants (tournament_id, user_id, seed) ",
+        );
+
+        query_builder.push_values(player_ids.iter().enumerate(), |mut b, (i, user_id)| {
+            b.push_bind(tournament_id)
+                .push_bind(user_id)
+                .push_bind(i as i32 + 1);
+        });
+
+        query_builder.build().execute(tx).await?;
+
+        Ok(())
+    }
+
+    pub async fn create_slots(
+        &self,
+        tx: &mut PgConnection,
+        slots: &[TournamentSlotDb],
+    ) -> Result<(), sqlx::Error> {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO tournament_slots (id, tournament_id, bracket, round, position, status, player_1_id, player_2_id, match_id, winner_id, winner_next_slot_id, winner_next_position, loser_next_slot_id, loser_next_position) ",
+        );
+
+        query_builder.push_values(slots, |mut b, slot| {
+            b.push_bind(slot.id)
+                .push_bind(slot.tournament_id)
+                .push_bind(slot.bracket)
+                .push_bind(slot.round)
+                .push_bind(slot.position)
+                .push_bind(slot.status)
+                .push_bind(slot.player_1_id)
+                .push_bind(slot.player_2_id)
+                .push_bind(slot.match_id)
+                .push_bind(slot.winner_id)
+                .push_bind(slot.winner_next_slot_id)
+                .push_bind(slot.winner_next_position)
+                .push_bind(slot.loser_next_slot_id)
+                .push_bind(slot.loser_next_position);
+        });
+
+        query_builder.build().execute(tx).await?;
+
+        Ok(())
+    }
+
+    /// Saves the progress of slots, as the bracket links between them never change
+    pub async fn update_slots(
+        &self,
+        tx: &mut PgConnection,
+        slots: &[TournamentSlotDb],
+    ) -> Result<(), sqlx::Error> {
+        for slot in slots {
+            sqlx::query(
+                "UPDATE tournament_slots SET status = $1, player_1_id = $2, player_2_id = $3, match_id = $4, winner_id = $5 WHERE id = $6",
+            )
+            .bind(slot.status)
+            .bind(slot.player_1_id)
+            .bind(slot.player_2_id)
+            .bind(slot.match_id)
+            .bind(slot.winner_id)
+            .bind(slot.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        tournament_id: Uuid,
+    ) -> Result<Option<TournamentDb>, sqlx::Error> {
+        sqlx::query_as::<_, TournamentDb>("SELECT * FROM tournaments WHERE id = $1")
+            .bind(tournament_id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    // Finds tournament by ID and locks until updated
+    pub async fn get_for_update(
+        &self,
+        tx: &mut PgConnection,
+        tournament_id: Uuid,
+    ) -> Result<Option<TournamentDb>, sqlx::Error> {
+        sqlx::query_as::<_, TournamentDb>("SELECT * FROM tournaments WHERE id = $1 FOR UPDATE")
+            .bind(tournament_id)
+            .fetch_optional(tx)
+            .await
+    }
+
+    pub async fn get_for_game<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        game_id: Uuid,
+    ) -> Result<Vec<TournamentDb>, sqlx::Error> {
+        sqlx::query_as::<_, TournamentDb>(
+            "SELECT * FROM tournaments WHERE game_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(game_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn get_participants<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        tournament_id: Uuid,
+    ) -> Result<Vec<TournamentParticipantDb>, sqlx::Error> {
+        sqlx::query_as::<_, TournamentParticipantDb>(
+            r#"
+            SELECT tp.user_id, tp.seed, u.name, u.avatar, u.avatar_colour
+            FROM tournament_participants tp
+            JOIN users u ON u.id = tp.user_id
+            WHERE tp.tournament_id = $1
+            ORDER BY tp.seed
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn get_slots<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        tournament_id: Uuid,
+    ) -> Result<Vec<TournamentSlotDb>, sqlx::Error> {
+        sqlx::query_as::<_, TournamentSlotDb>(
+            "SELECT * FROM tournament_slots WHERE tournament_id = $1 ORDER BY bracket, round, position",
+        )
+        .bind(tournament_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    pub async fn complete(
+        &self,
+        tx: &mut PgConnection,
+        tournament_id: Uuid,
+        winner_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tournaments SET status = 'completed', winner_id = $1 WHERE id = $2")
+            .bind(winner_id)
+            .bind(tournament_id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        tournament_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM tournaments WHERE id = $1")
+            .bind(tournament_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_match_in_tournament<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        match_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tournament_slots WHERE match_id = $1)")
+            .bind(match_id)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Moves one player's place in a group's tournaments over to another player, unless they're
+    /// both in the same tournament
+    pub async fn reassign_player(
+        &self,
+        tx: &mut PgConnection,
+        group_id: Uuid,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE tournament_participants tp SET user_id = $3
+            FROM tournaments t
+            JOIN games g ON g.id = t.game_id
+            WHERE tp.tournament_id = t.id AND g.group_id = $1 AND tp.user_id = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM tournament_participants o WHERE o.tournament_id = tp.tournament_id AND o.user_id = $3
+                )
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE tournament_slots s SET
+                player_1_id = CASE WHEN s.player_1_id = $2 THEN $3 ELSE s.player_1_id END,
+                player_2_id = CASE WHEN s.player_2_id = $2 THEN $3 ELSE s.player_2_id END,
+                winner_id = CASE WHEN s.winner_id = $2 THEN $3 ELSE s.winner_id END
+            FROM tournaments t
+            JOIN games g ON g.id = t.game_id
+            WHERE s.tournament_id = t.id AND g.group_id = $1 AND $2 IN (s.player_1_id, s.player_2_id, s.winner_id)
+                AND EXISTS (
+                    SELECT 1 FROM tournament_participants tp WHERE tp.tournament_id = t.id AND tp.user_id = $3
+                )
+                AND NOT EXISTS (
+                    SELECT 1 FROM tournament_participants tp WHERE tp.tournament_id = t.id AND tp.user_id = $2
+                )
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE tournaments t SET winner_id = $3
+            FROM games g
+            WHERE g.id = t.game_id AND g.group_id = $1 AND t.winner_id = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM tournament_participants tp WHERE tp.tournament_id = t.id AND tp.user_id = $2
+                )
+            "#,
+        )
+        .bind(group_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -23,6 +23,37 @@ impl UserRepo {
         .await
     }
 
+    /// Creates a player without an account. They get an address nobody can sign in with
+    pub async fn create_guest<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        name: &str,
+        avatar: Avatar,
+        avatar_colour: AvatarColour,
+    ) -> Result<UserDb, sqlx::Error> {
+        sqlx::query_as::<_, UserDb>(
+            "INSERT INTO users (name, email, password_hash, avatar, avatar_colour) VALUES ($1, 'guest-' || gen_random_uuid() || '@guest.invalid', '', $2, $3) RETURNING *",
+        )
+        .bind(name)
+        .bind(avatar)
+        .bind(avatar_colour)
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn delete<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn update<'e>(
         &self,
         executor: impl PgExecutor<'e, Database = Postgres>,
@@ -105,4 +136,26 @@ impl UserRepo {
 
         Ok(())
     }
+
+    /// Clears everything that identifies a user and invalidates their sessions. The row is kept so
+    /// history they were part of (matches they recorded, scores they chose to keep) stays intact
+    pub async fn anonymise<'e>(
+        &self,
+        executor: impl PgExecutor<'e, Database = Postgres>,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET name = 'Deleted user', email = 'deleted-' || id || '@deleted.invalid', password_hash = '',
+                email_verified = false, session_version = session_version + 1
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,140 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::AppError,
+    models::{
+        activity::{ActivityCursor, ActivityDb, ActivityKind, NewActivity},
+        game::GameDb,
+        game_match::MatchDb,
+        group::GroupMemberDb,
+        stats::MedalsThresholds,
+    },
+};
+
+pub const DEFAULT_ACTIVITY_PAGE_SIZE: i64 = 20;
+pub const MAX_ACTIVITY_PAGE_SIZE: i64 = 100;
+
+/// Gets a page of what happened in a group, newest first
+pub async fn get_activity(
+    state: &AppState,
+    member: GroupMemberDb,
+    cursor: Option<ActivityCursor>,
+    limit: Option<i64>,
+) -> Result<(Vec<ActivityDb>, Option<ActivityCursor>), AppError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_ACTIVITY_PAGE_SIZE)
+        .clamp(1, MAX_ACTIVITY_PAGE_SIZE);
+
+    // Fetch one extra to find out if there is another page
+    let mut activity = state
+        .activity_repo
+        .get_page(&state.pool, member.group_id, cursor, limit + 1)
+        .await?;
+
+    let next_cursor = if activity.len() as i64 > limit {
+        activity.truncate(limit as usize);
+        activity.last().map(|a| ActivityCursor {
+            created_at: a.created_at,
+            id: a.id,
+        })
+    } else {
+        None
+    };
+
+    Ok((activity, next_cursor))
+}
+
+/// Adds a newly recorded match to its group's feed, along with any personal bests set or medals
+/// earned in it
+pub async fn record_match_activity(
+    state: &AppState,
+    tx: &mut PgConnection,
+    game: &GameDb,
+    game_match: &MatchDb,
+    recorded_by: Uuid,
+) -> Result<(), AppError> {
+    state
+        .activity_repo
+        .create(
+            &mut *tx,
+            NewActivity {
+                user_id: Some(recorded_by),
+                game_id: Some(game.id),
+                season_id: Some(game_match.season_id),
+                match_id: Some(game_match.id),
+                ..NewActivity::new(game.group_id, ActivityKind::MatchRecorded)
+            },
+        )
+        .await?;
+
+    record_match_highlights(state, tx, game, game_match).await
+}
+
+/// Adds any personal bests set or medals earned in a match to its group's feed
+pub async fn record_match_highlights(
+    state: &AppState,
+    tx: &mut PgConnection,
+    game: &GameDb,
+    game_match: &MatchDb,
+) -> Result<(), AppError> {
+    // Games recording placements or outcomes have no scores to compare
+    let scores: Vec<(Uuid, i32)> = game_match
+        .scores
+        .iter()
+        .filter_map(|s| s.score.map(|score| (s.user_id, score)))
+        .collect();
+
+    if scores.is_empty() {
+        return Ok(());
+    }
+
+    let user_ids: Vec<Uuid> = scores.iter().map(|(user_id, _)| *user_id).collect();
+    let best_scores: HashMap<Uuid, i32> = state
+        .match_repo
+        .get_best_scores(&mut *tx, game.id, &user_ids, game_match.id)
+        .await?
+        .into_iter()
+        .collect();
+
+    let thresholds = MedalsThresholds::from(game);
+
+    for (user_id, score) in scores {
+        let highlight = |kind| NewActivity {
+            user_id: Some(user_id),
+            game_id: Some(game.id),
+            season_id: Some(game_match.season_id),
+            match_id: Some(game_match.id),
+            score: Some(score),
+            ..NewActivity::new(game.group_id, kind)
+        };
+
+        // A player's first score has nothing to beat yet
+        if best_scores.get(&user_id).is_some_and(|best| {
+            game.score_direction.compare(score as f64, *best as f64) == Ordering::Greater
+        }) {
+            state
+                .activity_repo
+                .create(&mut *tx, highlight(ActivityKind::PersonalBest))
+                .await?;
+        }
+
+        if let Some(medal) = thresholds.medal(score) {
+            state
+                .activity_repo
+                .create(
+                    &mut *tx,
+                    NewActivity {
+                        medal: Some(medal),
+                        ..highlight(ActivityKind::MedalEarned)
+                    },
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -146,6 +146,21 @@ impl EmailService {
             .await
     }
 
+    pub async fn send_account_deleted_email(
+        &self,
+        address: &str,
+        name: &str,
+    ) -> Result<(), AppError> {
+        let subject = "Your account has been deleted";
+        let template = include_str!("../email_templates/account_deleted.html");
+
+        let html_body = template.replace("{{name}}", name);
+
+        self.provider
+            .send_raw(FromAddress::Accounts, address, subject, &html_body)
+            .await
+    }
+
     fn get_verification_link(token: &Uuid) -> String {
         let frontend_base = env::var("FRONTEND_URL").expect("FRONTEND_URL must be set");
         format!("{}/verify-email/{}", frontend_base, token)
@@ -0,0 +1,109 @@
+use std::io::{Cursor, Seek, Write};
+
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    AppState,
+    errors::{AppError, GroupError},
+    models::{
+        export::GroupExport,
+        group::{GroupMemberDb, OrderBy},
+        stats::OrderDir,
+    },
+    policies::GroupAction,
+};
+
+/// Gathers everything recorded for a group. Any member can export their group, but emails are
+/// only included for those allowed to see them
+pub async fn export_group(
+    state: &AppState,
+    member: GroupMemberDb,
+) -> Result<GroupExport, AppError> {
+    // Read from a single snapshot so matches recorded mid-export can't leave it inconsistent
+    let mut tx = state.pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let group = state
+        .group_repo
+        .find_by_id(&mut *tx, member.group_id)
+        .await
+        .map_err(GroupError::Database)?
+        .ok_or(GroupError::NotFound)?;
+
+    let members = state
+        .group_repo
+        .get_members(&mut *tx, group.id, OrderBy::Name, OrderDir::Ascending)
+        .await
+        .map_err(GroupError::Database)?;
+
+    let games = state
+        .game_repo
+        .get_games_in_group(&mut *tx, group.id)
+        .await?;
+
+    let game_ids: Vec<Uuid> = games.iter().map(|g| g.id).collect();
+    let seasons = state.season_repo.get_for_games(&mut *tx, &game_ids).await?;
+    let matches = state.match_repo.get_for_games(&mut *tx, &game_ids).await?;
+    let scores = state
+        .match_repo
+        .get_scores_for_games(&mut *tx, &game_ids)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(GroupExport {
+        exported_at: Utc::now(),
+        group,
+        members,
+        games,
+        seasons,
+        matches,
+        scores,
+        include_emails: member.role.can_perform(GroupAction::ViewEmails),
+    })
+}
+
+/// Bundles an export into a zip with a CSV file for each kind of data
+pub fn export_to_zip(export: &GroupExport) -> Result<Vec<u8>, AppError> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    write_csv(&mut zip, "members.csv", &export.member_rows())?;
+    write_csv(&mut zip, "games.csv", &export.game_rows())?;
+    write_csv(&mut zip, "seasons.csv", &export.season_rows())?;
+    write_csv(&mut zip, "matches.csv", &export.match_rows())?;
+    write_csv(&mut zip, "scores.csv", &export.score_rows())?;
+
+    let cursor = zip
+        .finish()
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    Ok(cursor.into_inner())
+}
+
+fn write_csv<W: Write + Seek, T: Serialize>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    rows: &[T],
+) -> Result<(), AppError> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(name, options)
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    let mut writer = csv::Writer::from_writer(zip);
+    for row in rows {
+        writer
+            .serialize(row)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::{AppError, FixtureError, GroupError},
+    models::{
+        fixture::{CreateFixtureReq, FixtureDb, FixturePlayerDb, FixtureRsvp, FixtureWithPlayers},
+        game::GameDb,
+        game_match::{CreateMatchReq, MatchDb},
+        group::GroupMemberDb,
+        stats::{MatchChange, MatchChangedEvent},
+    },
+    policies::GroupAction,
+    services::{game::fetch_game_guarded, game_match::record_match},
+};
+
+pub async fn create_fixture(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    payload: CreateFixtureReq,
+) -> Result<FixtureWithPlayers, AppError> {
+    let (game, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::CreateFixture) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    if payload.scheduled_at <= Utc::now() {
+        return Err(FixtureError::ScheduledInPast.into());
+    }
+
+    let mut seen = HashSet::new();
+    if !payload.player_ids.iter().all(|id| seen.insert(*id)) {
+        return Err(FixtureError::DuplicatePlayer.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let all_members = state
+        .group_repo
+        .are_members(&mut *tx, game.group_id, &payload.player_ids)
+        .await?;
+
+    if !all_members {
+        return Err(FixtureError::PlayerNotMember.into());
+    }
+
+    let fixture = state
+        .fixture_repo
+        .create(
+            &mut tx,
+            game.id,
+            payload.scheduled_at,
+            user_id,
+            &payload.player_ids,
+        )
+        .await?;
+
+    tx.commit().await?;
+
+    with_players(state, fixture).await
+}
+
+/// Gets the fixtures of a game that are still waiting for results
+pub async fn get_fixtures(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+) -> Result<Vec<FixtureWithPlayers>, AppError> {
+    let (game, _) = fetch_game_guarded(state, game_id, user_id).await?;
+
+    let fixtures = state
+        .fixture_repo
+        .get_unplayed(&state.pool, game.id)
+        .await?;
+
+    let fixture_ids: Vec<Uuid> = fixtures.iter().map(|f| f.id).collect();
+    let players = state
+        .fixture_repo
+        .get_players(&state.pool, &fixture_ids)
+        .await?;
+
+    let mut players_by_fixture: HashMap<Uuid, Vec<FixturePlayerDb>> = HashMap::new();
+    for player in players {
+        players_by_fixture
+            .entry(player.fixture_id)
+            .or_default()
+            .push(player);
+    }
+
+    let fixtures = fixtures
+        .into_iter()
+        .map(|fixture| FixtureWithPlayers {
+            players: players_by_fixture.remove(&fixture.id).unwrap_or_default(),
+            fixture,
+        })
+        .collect();
+
+    Ok(fixtures)
+}
+
+pub async fn get_fixture(
+    state: &AppState,
+    user_id: Uuid,
+    fixture_id: Uuid,
+) -> Result<FixtureWithPlayers, AppError> {
+    let (fixture, _, _) = fetch_fixture_guarded(state, fixture_id, user_id).await?;
+
+    with_players(state, fixture).await
+}
+
+/// Responds to a fixture. Members who weren't invited can still say they're coming
+pub async fn rsvp(
+    state: &AppState,
+    user_id: Uuid,
+    fixture_id: Uuid,
+    rsvp: FixtureRsvp,
+) -> Result<FixtureWithPlayers, AppError> {
+    let (fixture, _, member) = fetch_fixture_guarded(state, fixture_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::RsvpFixture) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    if rsvp == FixtureRsvp::Invited {
+        return Err(FixtureError::InvalidRsvp.into());
+    }
+
+    if fixture.match_id.is_some() {
+        return Err(FixtureError::AlreadyPlayed.into());
+    }
+
+    state
+        .fixture_repo
+        .set_rsvp(&state.pool, fixture.id, user_id, rsvp)
+        .await?;
+
+    with_players(state, fixture).await
+}
+
+/// Cancels a fixture, which can be done by whoever scheduled it
+pub async fn delete_fixture(
+    state: &AppState,
+    user_id: Uuid,
+    fixture_id: Uuid,
+) -> Result<(), AppError> {
+    let (fixture, _, member) = fetch_fixture_guarded(state, fixture_id, user_id).await?;
+    if fixture.created_by != user_id && !member.role.can_perform(GroupAction::DeleteFixture) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    if fixture.match_id.is_some() {
+        return Err(FixtureError::AlreadyPlayed.into());
+    }
+
+    state.fixture_repo.delete(&state.pool, fixture.id).await?;
+
+    Ok(())
+}
+
+/// Enters the results of a fixture, recording it as a match played at its scheduled time
+pub async fn record_fixture_match(
+    state: &AppState,
+    user_id: Uuid,
+    fixture_id: Uuid,
+    mut payload: CreateMatchReq,
+) -> Result<MatchDb, AppError> {
+    let (_, game, member) = fetch_fixture_guarded(state, fixture_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::CreateMatch) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Locked so results can't be entered twice
+    let fixture = state
+        .fixture_repo
+        .get_for_update(&mut tx, fixture_id)
+        .await?
+        .ok_or(FixtureError::NotFound)?;
+
+    if fixture.match_id.is_some() {
+        return Err(FixtureError::AlreadyPlayed.into());
+    }
+
+    // Without a time given, the match was played as planned, or now if played early
+    payload.played_at = payload
+        .played_at
+        .or(Some(fixture.scheduled_at.min(Utc::now())));
+
+    let game_match = record_match(state, &mut tx, &game, user_id, payload).await?;
+
+    state
+        .fixture_repo
+        .set_match(&mut tx, fixture.id, game_match.id)
+        .await?;
+
+    tx.commit().await?;
+
+    // Invalidate cache
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    state.live_updates.publish(MatchChangedEvent {
+        change: MatchChange::Recorded,
+        game_id: game.id,
+        match_id: game_match.id,
+        season_id: game_match.season_id,
+    });
+
+    Ok(game_match)
+}
+
+async fn with_players(
+    state: &AppState,
+    fixture: FixtureDb,
+) -> Result<FixtureWithPlayers, AppError> {
+    let players = state
+        .fixture_repo
+        .get_players(&state.pool, &[fixture.id])
+        .await?;
+
+    Ok(FixtureWithPlayers { fixture, players })
+}
+
+pub async fn fetch_fixture_guarded(
+    state: &AppState,
+    fixture_id: Uuid,
+    user_id: Uuid,
+) -> Result<(FixtureDb, GameDb, GroupMemberDb), AppError> {
+    let fixture = state
+        .fixture_repo
+        .get(&state.pool, fixture_id)
+        .await?
+        .ok_or(FixtureError::NotFound)?;
+
+    let (game, member) = fetch_game_guarded(state, fixture.game_id, user_id).await?;
+
+    Ok((fixture, game, member))
+}
@@ -1,10 +1,14 @@
 use crate::AppState;
 use crate::errors::{AppError, GameError, GroupError};
-use crate::models::game::{CreateGameReq, GameDb, UpdateGameReq};
+use crate::models::activity::{ActivityKind, NewActivity};
+use crate::models::game::{
+    CreateGameReq, GameDb, GameSettings, Interval, MatchResultType, ScoringMetric, SeasonAlignment,
+    UpdateGameReq,
+};
 use crate::models::group::GroupMemberDb;
 use crate::models::season::SeasonDb;
 use crate::policies::GroupAction;
-
+use crate::services::season::catch_up_seasons;
 use chrono::Utc;
 use uuid::Uuid;
 
@@ -21,6 +25,24 @@ pub async fn create_game(
         return Err(GameError::MaxLessThanMin.into());
     }
 
+    if payload.min_players_per_team > payload.max_players_per_team {
+        return Err(GameError::MaxTeamSizeLessThanMin.into());
+    }
+
+    if payload.metric == ScoringMetric::AverageScore
+        && payload.result_type != MatchResultType::Score
+    {
+        return Err(GameError::MetricRequiresScores.into());
+    }
+
+    validate_season_schedule(
+        state,
+        payload.season_duration,
+        payload.season_alignment,
+        &payload.season_timezone,
+    )
+    .await?;
+
     let mut tx = state.pool.begin().await?;
 
     let game = state
@@ -28,19 +50,36 @@ pub async fn create_game(
         .create(
             &mut tx,
             member.group_id,
-            &payload.name,
-            payload.min_players_per_match,
-            payload.max_players_per_match,
-            payload.metric,
-            payload.season_duration,
-            payload.medal_scores.and_then(|s| s.star),
-            payload.medal_scores.and_then(|s| s.gold),
-            payload.medal_scores.and_then(|s| s.silver),
-            payload.medal_scores.and_then(|s| s.bronze),
+            payload.result_type,
+            &GameSettings {
+                name: &payload.name,
+                min_players_per_match: payload.min_players_per_match,
+                max_players_per_match: payload.max_players_per_match,
+                min_players_per_team: payload.min_players_per_team,
+                max_players_per_team: payload.max_players_per_team,
+                metric: payload.metric,
+                score_direction: payload.score_direction,
+                season_duration: payload.season_duration,
+                season_alignment: payload.season_alignment,
+                season_timezone: &payload.season_timezone,
+                medal_scores: payload.medal_scores,
+            },
         )
         .await
         .map_err(GameError::Database)?;
 
+    state
+        .activity_repo
+        .create(
+            &mut *tx,
+            NewActivity {
+                user_id: Some(member.user_id),
+                game_id: Some(game.id),
+                ..NewActivity::new(member.group_id, ActivityKind::GameCreated)
+            },
+        )
+        .await?;
+
     tx.commit().await?;
 
     Ok(game)
@@ -57,47 +96,75 @@ pub async fn update_game(
         return Err(GroupError::Forbidden.into());
     }
 
+    if payload.min_players_per_match > payload.max_players_per_match {
+        return Err(GameError::MaxLessThanMin.into());
+    }
+
+    let settings = payload.settings(&game);
+    if settings.min_players_per_team > settings.max_players_per_team {
+        return Err(GameError::MaxTeamSizeLessThanMin.into());
+    }
+
+    // The result type is fixed on creation, as existing matches would no longer be valid
+    if payload.metric == ScoringMetric::AverageScore && game.result_type != MatchResultType::Score {
+        return Err(GameError::MetricRequiresScores.into());
+    }
+
+    validate_season_schedule(
+        state,
+        settings.season_duration,
+        settings.season_alignment,
+        settings.season_timezone,
+    )
+    .await?;
+
     let mut tx = state.pool.begin().await?;
+
+    // Seasons that already ended did so under the old schedule
+    let now = Utc::now();
+    let latest_season = state.season_repo.get_latest(&mut tx, game.id).await?;
+    let latest_season = catch_up_seasons(state, &mut tx, latest_season, now).await?;
+
+    if payload
+        .next_season_start
+        .is_some_and(|start| start <= latest_season.start_date)
+    {
+        return Err(GameError::SeasonOverlap.into());
+    }
+
     let game = state
         .game_repo
-        .update(
-            &mut tx,
-            game.id,
-            &payload.name,
-            payload.min_players_per_match,
-            payload.max_players_per_match,
-            payload.metric,
-            payload.season_duration,
-            payload.medal_scores.and_then(|s| s.star),
-            payload.medal_scores.and_then(|s| s.gold),
-            payload.medal_scores.and_then(|s| s.silver),
-            payload.medal_scores.and_then(|s| s.bronze),
-        )
+        .update(&mut tx, game.id, &settings)
         .await
         .map_err(GameError::Database)?;
 
-    let latest_season = state.season_repo.get_latest(&mut tx, game.id).await?;
-    let mut latest_season = state
+    let latest_season = state
         .season_repo
-        .update_season_end_date(&mut tx, latest_season.id, payload.next_season_start)
+        .reschedule_latest(&mut tx, latest_season.id, payload.next_season_start, now)
         .await?;
 
-    if let Some(mut next_season_start) = payload.next_season_start {
-        while next_season_start < Utc::now() {
-            latest_season = state
-                .season_repo
-                .new_season(&mut tx, &latest_season)
-                .await?;
-
-            next_season_start = match latest_season.end_date {
-                Some(val) => val,
-                None => break,
-            };
-        }
-    }
+    catch_up_seasons(state, &mut tx, latest_season, now).await?;
+
+    state
+        .activity_repo
+        .create(
+            &mut *tx,
+            NewActivity {
+                user_id: Some(user_id),
+                game_id: Some(game.id),
+                ..NewActivity::new(game.group_id, ActivityKind::GameUpdated)
+            },
+        )
+        .await?;
 
     tx.commit().await?;
 
+    // Scoreboards may be ordered or scored differently now
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
     Ok(game)
 }
 
@@ -150,6 +217,27 @@ pub async fn get_seasons(
     Ok(seasons)
 }
 
+async fn validate_season_schedule(
+    state: &AppState,
+    duration: Option<Interval>,
+    alignment: SeasonAlignment,
+    timezone: &str,
+) -> Result<(), AppError> {
+    if duration.is_none() && alignment != SeasonAlignment::None {
+        return Err(GameError::AlignmentRequiresDuration.into());
+    }
+
+    if !state
+        .game_repo
+        .is_valid_timezone(&state.pool, timezone)
+        .await?
+    {
+        return Err(GameError::InvalidTimezone.into());
+    }
+
+    Ok(())
+}
+
 pub async fn fetch_game_guarded(
     state: &AppState,
     game_id: Uuid,
@@ -1,11 +1,29 @@
 use crate::AppState;
 use crate::errors::{AppError, GroupError, MatchError};
-use crate::models::game_match::{CreateMatchReq, MatchDb, MatchScoreDb};
+use crate::models::game::{GameDb, MatchResultType};
+use crate::models::game_match::{
+    CreateMatchReq, CreateMatchScoreReq, MatchChanges, MatchCursor, MatchDb, MatchDetailsDb,
+    MatchLeaderboardEntryDb, MatchOutcome, MatchRevisionAction, MatchRevisionWithScores,
+    MatchScoreChange, MatchScoreDb, MatchWithLeaderboard, UpdateMatchReq,
+};
+use crate::models::group::GroupMemberDb;
+use crate::models::stats::{MatchChange, MatchChangedEvent};
 use crate::policies::GroupAction;
+use crate::services::activity::{record_match_activity, record_match_highlights};
 use crate::services::game::fetch_game_guarded;
+use crate::services::season::{refresh_season_results, season_at};
 
+use chrono::Utc;
+use sqlx::PgConnection;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+pub const DEFAULT_MATCHES_PAGE_SIZE: i64 = 20;
+pub const MAX_MATCHES_PAGE_SIZE: i64 = 100;
+
+// Score, placement and outcome of a player, only one of which is set
+type MatchResult = (Option<i32>, Option<i32>, Option<MatchOutcome>);
+
 pub async fn create_match(
     state: &AppState,
     game_id: Uuid,
@@ -18,56 +36,644 @@ pub async fn create_match(
         return Err(GroupError::Forbidden.into());
     }
 
-    if payload.scores.len() < game.min_players_per_match as usize
-        || payload.scores.len() > game.max_players_per_match as usize
-    {
-        return Err(MatchError::IncorrectNumberOfScores.into());
+    let mut tx = state.pool.begin().await?;
+    let game_match = record_match(state, &mut tx, &game, user_id, payload).await?;
+    tx.commit().await?;
+
+    // Invalidate cache
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game_id)
+        .await?;
+
+    state.live_updates.publish(MatchChangedEvent {
+        change: MatchChange::Recorded,
+        game_id,
+        match_id: game_match.id,
+        season_id: game_match.season_id,
+    });
+
+    Ok(game_match)
+}
+
+/// Validates and records a match in the season running when it was played, as part of a wider
+/// transaction
+pub async fn record_match(
+    state: &AppState,
+    tx: &mut PgConnection,
+    game: &GameDb,
+    user_id: Uuid,
+    payload: CreateMatchReq,
+) -> Result<MatchDb, AppError> {
+    let (scores, player_ids) = validate_scores(game, payload.scores)?;
+
+    let now = Utc::now();
+    let played_at = payload.played_at.unwrap_or(now);
+    if played_at > now {
+        return Err(MatchError::PlayedInFuture.into());
     }
 
-    let mut scores = Vec::with_capacity(payload.scores.len());
-    let mut player_ids = Vec::with_capacity(payload.scores.len());
+    // Verify all users are members of the group
+    let all_members = state
+        .group_repo
+        .are_members(&mut *tx, game.group_id, &player_ids)
+        .await?;
 
-    for s in payload.scores {
-        // Prevent duplicate scoring for the same user in one match
-        if player_ids.contains(&s.user_id) {
-            return Err(MatchError::DuplicatePlayer.into());
-        }
+    if !all_members {
+        return Err(MatchError::OneOrMorePlayersNotMember.into());
+    }
 
-        player_ids.push(s.user_id);
+    let season = season_at(state, tx, game.id, played_at).await?;
 
-        scores.push(MatchScoreDb {
-            user_id: s.user_id,
-            score: s.score,
-        })
+    let game_match = state
+        .match_repo
+        .create(tx, game.id, season.id, user_id, played_at, scores)
+        .await
+        .map_err(MatchError::Database)?;
+
+    record_match_activity(state, tx, game, &game_match, user_id).await?;
+
+    // Back-dated matches may belong to a season that has already ended
+    refresh_season_results(state, tx, game.id, &[season.id]).await?;
+
+    Ok(game_match)
+}
+
+pub async fn update_match(
+    state: &AppState,
+    match_id: Uuid,
+    user_id: Uuid,
+    payload: UpdateMatchReq,
+) -> Result<MatchDb, AppError> {
+    let (_, game, member) = fetch_match_guarded(state, match_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::UpdateMatch) {
+        return Err(GroupError::Forbidden.into());
     }
 
+    let (scores, player_ids) = validate_scores(&game, payload.scores)?;
+
     let mut tx = state.pool.begin().await?;
 
-    // Verify all users are members of the group
+    let game_match = state
+        .match_repo
+        .get_for_update(&mut tx, match_id)
+        .await?
+        .ok_or(MatchError::NotFound)?;
+
+    // Brackets have already moved players on based on the result
+    if state
+        .tournament_repo
+        .is_match_in_tournament(&mut *tx, match_id)
+        .await?
+    {
+        return Err(MatchError::InTournament.into());
+    }
+
+    let previous_scores = state.match_repo.get_scores(&mut *tx, match_id).await?;
+
+    // Players already in the match may have left the group since, so only new players are checked
+    let new_player_ids: Vec<Uuid> = player_ids
+        .into_iter()
+        .filter(|id| !previous_scores.iter().any(|s| s.user_id == *id))
+        .collect();
+
     let all_members = state
         .group_repo
-        .are_members(&mut *tx, game.group_id, &player_ids)
+        .are_members(&mut *tx, game.group_id, &new_player_ids)
         .await?;
 
     if !all_members {
         return Err(MatchError::OneOrMorePlayersNotMember.into());
     }
 
-    let latest_season = state.season_repo.get_latest(&mut tx, game_id).await?;
+    let changes = MatchChanges {
+        scores: diff_scores(&previous_scores, &scores),
+        played_at: payload
+            .played_at
+            .filter(|played_at| *played_at != game_match.played_at)
+            .map(|played_at| (game_match.played_at, played_at)),
+    };
+
+    // Nothing changed, so there is nothing to record
+    if changes.is_empty() {
+        return Ok(MatchDb {
+            id: game_match.id,
+            game_id: game_match.game_id,
+            season_id: game_match.season_id,
+            played_at: game_match.played_at,
+            scores: previous_scores,
+        });
+    }
+
+    // Back-dated matches move to the season running at the time
+    let mut season_id = game_match.season_id;
+    if let Some((_, played_at)) = changes.played_at {
+        if played_at > Utc::now() {
+            return Err(MatchError::PlayedInFuture.into());
+        }
+
+        season_id = season_at(state, &mut tx, game.id, played_at).await?.id;
+        state
+            .match_repo
+            .update_played_at(&mut tx, match_id, played_at, season_id)
+            .await
+            .map_err(MatchError::Database)?;
+    }
+
+    let scores = state
+        .match_repo
+        .replace_scores(&mut tx, match_id, scores)
+        .await
+        .map_err(MatchError::Database)?;
+
+    let updated_match = MatchDb {
+        id: game_match.id,
+        game_id: game_match.game_id,
+        season_id,
+        played_at: changes
+            .played_at
+            .map_or(game_match.played_at, |(_, new)| new),
+        scores,
+    };
+
+    // Highlights are worked out again from the new scores
+    state
+        .activity_repo
+        .delete_match_highlights(&mut *tx, match_id)
+        .await?;
+    record_match_highlights(state, &mut tx, &game, &updated_match).await?;
+
+    state
+        .match_repo
+        .create_revision(
+            &mut tx,
+            match_id,
+            game.id,
+            MatchRevisionAction::Update,
+            user_id,
+            changes,
+        )
+        .await?;
+
+    // Both the season the match was in and the one it moved to may have already ended
+    let mut season_ids = vec![game_match.season_id];
+    if season_id != game_match.season_id {
+        season_ids.push(season_id);
+    }
+
+    refresh_season_results(state, &mut tx, game.id, &season_ids).await?;
+
+    tx.commit().await?;
+
+    // Invalidate cache
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    state.live_updates.publish(MatchChangedEvent {
+        change: MatchChange::Updated,
+        game_id: game.id,
+        match_id: updated_match.id,
+        season_id: updated_match.season_id,
+    });
+
+    Ok(updated_match)
+}
+
+pub async fn delete_match(state: &AppState, match_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+    let (_, game, member) = fetch_match_guarded(state, match_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::DeleteMatch) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
 
     let game_match = state
         .match_repo
-        .create(&mut tx, game_id, latest_season.id, user_id, scores)
+        .get_for_update(&mut tx, match_id)
+        .await?
+        .ok_or(MatchError::NotFound)?;
+
+    if state
+        .tournament_repo
+        .is_match_in_tournament(&mut *tx, match_id)
+        .await?
+    {
+        return Err(MatchError::InTournament.into());
+    }
+
+    let previous_scores = state.match_repo.get_scores(&mut *tx, match_id).await?;
+    let changes = MatchChanges {
+        scores: diff_scores(&previous_scores, &[]),
+        played_at: None,
+    };
+
+    state
+        .match_repo
+        .create_revision(
+            &mut tx,
+            match_id,
+            game.id,
+            MatchRevisionAction::Delete,
+            user_id,
+            changes,
+        )
+        .await?;
+
+    state
+        .match_repo
+        .delete(&mut tx, match_id)
         .await
         .map_err(MatchError::Database)?;
 
+    state
+        .activity_repo
+        .delete_match_highlights(&mut *tx, match_id)
+        .await?;
+
+    refresh_season_results(state, &mut tx, game.id, &[game_match.season_id]).await?;
+
     tx.commit().await?;
 
     // Invalidate cache
     state
         .stats_cache_invalidator
-        .invalidate_game_stats(game_id)
+        .invalidate_game_stats(game.id)
         .await?;
 
-    Ok(game_match)
+    state.live_updates.publish(MatchChangedEvent {
+        change: MatchChange::Deleted,
+        game_id: game.id,
+        match_id,
+        season_id: game_match.season_id,
+    });
+
+    Ok(())
+}
+
+pub async fn get_match_revisions(
+    state: &AppState,
+    match_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<MatchRevisionWithScores>, AppError> {
+    let mut tx = state.pool.begin().await?;
+    let game_match = state.match_repo.get(&mut *tx, match_id).await?;
+    let revisions = state.match_repo.get_revisions(&mut tx, match_id).await?;
+    tx.commit().await?;
+
+    let game_id = match game_match {
+        Some(game_match) => game_match.game_id,
+
+        // Revisions outlive the match, so a deleted match's game is found through them
+        None => revisions
+            .first()
+            .map(|r| r.revision.game_id)
+            .ok_or(MatchError::NotFound)?,
+    };
+
+    let (_, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::ViewMatchRevisions) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    Ok(revisions)
+}
+
+pub async fn get_match(
+    state: &AppState,
+    match_id: Uuid,
+    user_id: Uuid,
+) -> Result<MatchWithLeaderboard, AppError> {
+    fetch_match_guarded(state, match_id, user_id).await?;
+
+    let details = state
+        .match_repo
+        .get_summary(&state.pool, match_id)
+        .await?
+        .ok_or(MatchError::NotFound)?;
+
+    let scores = state
+        .match_repo
+        .get_leaderboards(&state.pool, &[match_id])
+        .await?;
+
+    Ok(MatchWithLeaderboard { details, scores })
+}
+
+/// Gets a page of matches for a game, along with the cursor for the next page (if there is one)
+pub async fn get_matches(
+    state: &AppState,
+    game_id: Uuid,
+    user_id: Uuid,
+    season_id: Option<Uuid>,
+    player_id: Option<Uuid>,
+    cursor: Option<MatchCursor>,
+    limit: Option<i64>,
+) -> Result<(Vec<MatchWithLeaderboard>, Option<MatchCursor>), AppError> {
+    let (game, _) = fetch_game_guarded(state, game_id, user_id).await?;
+    let limit = limit
+        .unwrap_or(DEFAULT_MATCHES_PAGE_SIZE)
+        .clamp(1, MAX_MATCHES_PAGE_SIZE);
+
+    // Fetch one extra to find out if there is another page
+    let mut summaries = state
+        .match_repo
+        .get_summaries(
+            &state.pool,
+            game.id,
+            season_id,
+            player_id,
+            cursor,
+            limit + 1,
+        )
+        .await?;
+
+    let next_cursor = if summaries.len() as i64 > limit {
+        summaries.truncate(limit as usize);
+        summaries.last().map(|m| MatchCursor {
+            played_at: m.played_at,
+            id: m.id,
+        })
+    } else {
+        None
+    };
+
+    let match_ids: Vec<Uuid> = summaries.iter().map(|m| m.id).collect();
+    let leaderboards = state
+        .match_repo
+        .get_leaderboards(&state.pool, &match_ids)
+        .await?;
+
+    let mut scores_by_match: HashMap<Uuid, Vec<MatchLeaderboardEntryDb>> = HashMap::new();
+    for entry in leaderboards {
+        scores_by_match
+            .entry(entry.match_id)
+            .or_default()
+            .push(entry);
+    }
+
+    let matches = summaries
+        .into_iter()
+        .map(|details| MatchWithLeaderboard {
+            scores: scores_by_match.remove(&details.id).unwrap_or_default(),
+            details,
+        })
+        .collect();
+
+    Ok((matches, next_cursor))
+}
+
+pub async fn fetch_match_guarded(
+    state: &AppState,
+    match_id: Uuid,
+    user_id: Uuid,
+) -> Result<(MatchDetailsDb, GameDb, GroupMemberDb), AppError> {
+    let game_match = state
+        .match_repo
+        .get(&state.pool, match_id)
+        .await?
+        .ok_or(MatchError::NotFound)?;
+
+    let (game, member) = fetch_game_guarded(state, game_match.game_id, user_id).await?;
+
+    Ok((game_match, game, member))
+}
+
+/// Checks the scores are valid for the game, returning them along with the IDs of the players
+pub fn validate_scores(
+    game: &GameDb,
+    payload: Vec<CreateMatchScoreReq>,
+) -> Result<(Vec<MatchScoreDb>, Vec<Uuid>), AppError> {
+    if payload.len() < game.min_players_per_match as usize
+        || payload.len() > game.max_players_per_match as usize
+    {
+        return Err(MatchError::IncorrectNumberOfScores.into());
+    }
+
+    validate_results(game, &payload)?;
+    validate_teams(game, &payload)?;
+
+    let mut scores = Vec::with_capacity(payload.len());
+    let mut player_ids = Vec::with_capacity(payload.len());
+
+    for s in payload {
+        // Prevent duplicate scoring for the same user in one match
+        if player_ids.contains(&s.user_id) {
+            return Err(MatchError::DuplicatePlayer.into());
+        }
+
+        player_ids.push(s.user_id);
+
+        scores.push(MatchScoreDb {
+            user_id: s.user_id,
+            score: s.score,
+            placement: s.placement,
+            outcome: s.outcome,
+            team: s.team,
+        })
+    }
+
+    Ok((scores, player_ids))
+}
+
+/// Checks each player has exactly the kind of result the game records
+fn validate_results(game: &GameDb, payload: &[CreateMatchScoreReq]) -> Result<(), AppError> {
+    let matches_result_type = |s: &CreateMatchScoreReq| match game.result_type {
+        MatchResultType::Score => s.score.is_some() && s.placement.is_none() && s.outcome.is_none(),
+        MatchResultType::Placement => {
+            s.score.is_none() && s.placement.is_some() && s.outcome.is_none()
+        }
+        MatchResultType::WinLoss => {
+            s.score.is_none() && s.placement.is_none() && s.outcome.is_some()
+        }
+    };
+
+    if !payload.iter().all(matches_result_type) {
+        return Err(MatchError::ResultTypeMismatch.into());
+    }
+
+    if game.result_type == MatchResultType::WinLoss {
+        let has_outcome =
+            |outcome: MatchOutcome| payload.iter().any(|s| s.outcome == Some(outcome));
+
+        if has_outcome(MatchOutcome::Win) == has_outcome(MatchOutcome::Draw) {
+            return Err(MatchError::InvalidOutcomes.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks every team has a valid number of players, and that its players share the same result
+fn validate_teams(game: &GameDb, payload: &[CreateMatchScoreReq]) -> Result<(), AppError> {
+    let players_in_teams = payload.iter().filter(|s| s.team.is_some()).count();
+    if players_in_teams != 0 && players_in_teams != payload.len() {
+        return Err(MatchError::PartialTeams.into());
+    }
+
+    // Team -> (number of players, result)
+    let mut teams: HashMap<i32, (i32, MatchResult)> = HashMap::new();
+    for s in payload {
+        if let Some(team) = s.team {
+            let result = (s.score, s.placement, s.outcome);
+            let (size, team_result) = teams.entry(team).or_insert((0, result));
+            if *team_result != result {
+                return Err(MatchError::TeamResultMismatch.into());
+            }
+
+            *size += 1;
+        }
+    }
+
+    // Without teams, everyone is in a team of their own
+    let team_sizes: Vec<i32> = if teams.is_empty() {
+        vec![1; payload.len()]
+    } else {
+        teams.values().map(|(size, _)| *size).collect()
+    };
+
+    if team_sizes
+        .iter()
+        .any(|size| *size < game.min_players_per_team || *size > game.max_players_per_team)
+    {
+        return Err(MatchError::IncorrectTeamSize.into());
+    }
+
+    Ok(())
+}
+
+/// Lists every player whose result or team was added, removed or changed
+fn diff_scores(previous: &[MatchScoreDb], new: &[MatchScoreDb]) -> Vec<MatchScoreChange> {
+    let mut changes = Vec::new();
+
+    for prev in previous {
+        let new_score = new.iter().find(|s| s.user_id == prev.user_id);
+        let unchanged = new_score.is_some_and(|s| {
+            s.score == prev.score
+                && s.placement == prev.placement
+                && s.outcome == prev.outcome
+                && s.team == prev.team
+        });
+
+        if !unchanged {
+            changes.push(MatchScoreChange {
+                user_id: prev.user_id,
+                previous_score: prev.score,
+                new_score: new_score.and_then(|s| s.score),
+                previous_placement: prev.placement,
+                new_placement: new_score.and_then(|s| s.placement),
+                previous_outcome: prev.outcome,
+                new_outcome: new_score.and_then(|s| s.outcome),
+                previous_team: prev.team,
+                new_team: new_score.and_then(|s| s.team),
+            });
+        }
+    }
+
+    for s in new {
+        if !previous.iter().any(|prev| prev.user_id == s.user_id) {
+            changes.push(MatchScoreChange {
+                user_id: s.user_id,
+                previous_score: None,
+                new_score: s.score,
+                previous_placement: None,
+                new_placement: s.placement,
+                previous_outcome: None,
+                new_outcome: s.outcome,
+                previous_team: None,
+                new_team: s.team,
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(user_id: Uuid, score: i32) -> MatchScoreDb {
+        MatchScoreDb {
+            user_id,
+            score: Some(score),
+            placement: None,
+            outcome: None,
+            team: None,
+        }
+    }
+
+    #[test]
+    fn test_unchanged_scores_have_no_changes() {
+        let player = Uuid::new_v4();
+        let previous = vec![score(player, 10)];
+        let new = vec![score(player, 10)];
+
+        assert!(diff_scores(&previous, &new).is_empty());
+    }
+
+    #[test]
+    fn test_changed_score_is_recorded() {
+        let player = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let previous = vec![score(player, 10), score(other, 5)];
+        let new = vec![score(player, 12), score(other, 5)];
+
+        let changes = diff_scores(&previous, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].user_id, player);
+        assert_eq!(changes[0].previous_score, Some(10));
+        assert_eq!(changes[0].new_score, Some(12));
+    }
+
+    #[test]
+    fn test_removed_player_has_no_new_score() {
+        let player = Uuid::new_v4();
+        let removed = Uuid::new_v4();
+        let previous = vec![score(player, 10), score(removed, 5)];
+        let new = vec![score(player, 10)];
+
+        let changes = diff_scores(&previous, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].user_id, removed);
+        assert_eq!(changes[0].previous_score, Some(5));
+        assert_eq!(changes[0].new_score, None);
+    }
+
+    #[test]
+    fn test_added_player_has_no_previous_score() {
+        let player = Uuid::new_v4();
+        let added = Uuid::new_v4();
+        let previous = vec![score(player, 10)];
+        let new = vec![score(player, 10), score(added, 7)];
+
+        let changes = diff_scores(&previous, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].user_id, added);
+        assert_eq!(changes[0].previous_score, None);
+        assert_eq!(changes[0].new_score, Some(7));
+    }
+
+    #[test]
+    fn test_team_change_is_recorded() {
+        let player = Uuid::new_v4();
+        let previous = vec![MatchScoreDb {
+            team: Some(1),
+            ..score(player, 10)
+        }];
+        let new = vec![MatchScoreDb {
+            team: Some(2),
+            ..score(player, 10)
+        }];
+
+        let changes = diff_scores(&previous, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous_team, Some(1));
+        assert_eq!(changes[0].new_team, Some(2));
+    }
 }
@@ -1,15 +1,24 @@
 use crate::AppState;
 use crate::errors::{AppError, GroupError};
+use crate::models::activity::{ActivityKind, NewActivity};
 use crate::models::game::GameDb;
 use crate::models::group::{
     CreateGroupReq, GroupDb, GroupMemberDb, GroupMemberResponse, GroupMemberRole, GroupWithRole,
-    OrderBy, UpdateGroupReq,
+    MergeConflict, MergeMemberReq, OrderBy, UpdateGroupReq,
+};
+use crate::models::stats::{
+    GameContribution, GroupLeaderboardEntry, OrderDir, ScoreboardEntry, SeasonScope,
 };
-use crate::models::stats::OrderDir;
 use crate::policies::GroupAction;
+use crate::services::season::{refresh_season_results, seasons_by_game};
 
+use sqlx::PgConnection;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Points for finishing first in a game, with last place getting none
+pub const MAX_POINTS_PER_GAME: f64 = 100.0;
+
 pub async fn create_group(
     state: &AppState,
     owner_id: Uuid,
@@ -65,6 +74,94 @@ pub async fn get_games_in_group(state: &AppState, group_id: Uuid) -> Result<Vec<
     Ok(games)
 }
 
+/// Ranks members across every game in the group, awarding points by where they place on each
+/// game's scoreboard so games with more players don't count for more
+pub async fn get_group_leaderboard(
+    state: &AppState,
+    member: GroupMemberDb,
+    season: SeasonScope,
+) -> Result<Vec<GroupLeaderboardEntry>, AppError> {
+    // Seasons belong to a single game, so only scopes that apply to every game make sense here
+    if let SeasonScope::Id(_) = season {
+        return Err(AppError::BadRequest(
+            "Season must be either all or latest".to_string(),
+        ));
+    }
+
+    let games = get_games_in_group(state, member.group_id).await?;
+
+    let mut entries: HashMap<Uuid, GroupLeaderboardEntry> = HashMap::new();
+    for game in games {
+        let season_id = season.to_season_id(state, game.id).await?;
+        let scoreboard = state
+            .stats_service
+            .get_scoreboard_and_stats(state, member.user_id, game.id, season_id, None, None)
+            .await?;
+
+        let players = scoreboard.entries.len() as i64;
+        for scoreboard_entry in scoreboard.entries {
+            let points = placement_points(scoreboard_entry.rank, players);
+            let entry = entries
+                .entry(scoreboard_entry.user_id)
+                .or_insert_with(|| new_group_leaderboard_entry(&scoreboard_entry));
+
+            entry.points += points;
+            entry.games_played += 1;
+            entry.contributions.push(GameContribution {
+                game_id: game.id,
+                game_name: game.name.clone(),
+                rank: scoreboard_entry.rank,
+                players,
+                points,
+            });
+        }
+    }
+
+    let mut entries: Vec<_> = entries.into_values().collect();
+    entries.sort_by(|a, b| {
+        b.points
+            .total_cmp(&a.points)
+            .then_with(|| a.user_name.cmp(&b.user_name))
+    });
+
+    // Players with the same points share a rank
+    let mut prev_points = None;
+    for (index, entry) in entries.iter_mut().enumerate() {
+        entry.rank = match prev_points {
+            Some((points, rank)) if points == entry.points => rank,
+            _ => index as i32 + 1,
+        };
+        prev_points = Some((entry.points, entry.rank));
+        entry
+            .contributions
+            .sort_by(|a, b| b.points.total_cmp(&a.points));
+    }
+
+    Ok(entries)
+}
+
+/// Scales a rank on a scoreboard of the given size to between 0 and `MAX_POINTS_PER_GAME`
+fn placement_points(rank: i32, players: i64) -> f64 {
+    if players <= 1 {
+        return MAX_POINTS_PER_GAME;
+    }
+
+    MAX_POINTS_PER_GAME * (players - rank as i64) as f64 / (players - 1) as f64
+}
+
+fn new_group_leaderboard_entry(entry: &ScoreboardEntry) -> GroupLeaderboardEntry {
+    GroupLeaderboardEntry {
+        user_id: entry.user_id,
+        user_name: entry.user_name.clone(),
+        user_avatar: entry.user_avatar.clone(),
+        user_avatar_colour: entry.user_avatar_colour.clone(),
+        rank: 0,
+        points: 0.0,
+        games_played: 0,
+        contributions: Vec::new(),
+    }
+}
+
 pub async fn get_group(state: &AppState, member: GroupMemberDb) -> Result<GroupWithRole, AppError> {
     let group = state
         .group_repo
@@ -142,12 +239,7 @@ pub async fn remove_group_member(
     let is_self = member.user_id == member_to_remove_id;
 
     // Can remove if it's yourself, or someone lower rank
-    if is_self {
-        // Leaving group - allow unless owner
-        if member.role == GroupMemberRole::Owner {
-            return Err(GroupError::Forbidden.into());
-        }
-    } else {
+    if !is_self {
         // Removing someone else - check perms
         if !member
             .role
@@ -157,12 +249,21 @@ pub async fn remove_group_member(
         }
     }
 
+    let mut tx = state.pool.begin().await?;
+
+    // Owners can leave as long as another owner stays behind
+    if member_to_be_deleted.role == GroupMemberRole::Owner {
+        ensure_other_owner(state, &mut tx, member.group_id, member_to_remove_id).await?;
+    }
+
     state
         .group_repo
-        .remove_member(&state.pool, member.group_id, member_to_remove_id)
+        .remove_member(&mut *tx, member.group_id, member_to_remove_id)
         .await
         .map_err(GroupError::Database)?;
 
+    tx.commit().await?;
+
     Ok(())
 }
 
@@ -179,6 +280,10 @@ pub async fn set_member_role(
         .await?
         .ok_or(GroupError::MemberNotFound)?;
 
+    if member_to_be_updated.role == GroupMemberRole::Guest || role == GroupMemberRole::Guest {
+        return Err(GroupError::GuestRole.into());
+    }
+
     if !user_member
         .role
         .can_perform(GroupAction::UpdateRole(member_to_be_updated.role, role))
@@ -186,11 +291,356 @@ pub async fn set_member_role(
         return Err(GroupError::Forbidden.into());
     }
 
+    let mut tx = state.pool.begin().await?;
+
+    if member_to_be_updated.role == GroupMemberRole::Owner && role != GroupMemberRole::Owner {
+        ensure_other_owner(state, &mut tx, user_member.group_id, member_to_set_id).await?;
+    }
+
     let member = state
         .group_repo
-        .update_member_role(&state.pool, user_member.group_id, member_to_set_id, role)
+        .update_member_role(&mut *tx, user_member.group_id, member_to_set_id, role)
         .await
         .map_err(GroupError::Database)?;
 
+    if member_to_be_updated.role != role {
+        state
+            .activity_repo
+            .create(
+                &mut *tx,
+                NewActivity {
+                    user_id: Some(member_to_set_id),
+                    actor_id: Some(user_member.user_id),
+                    role: Some(role),
+                    ..NewActivity::new(user_member.group_id, ActivityKind::RoleChanged)
+                },
+            )
+            .await?;
+    }
+
+    tx.commit().await?;
+
     Ok(member)
 }
+
+/// Hands a group over to another member. They become an owner and the current owner becomes an admin
+pub async fn transfer_ownership(
+    state: &AppState,
+    member: GroupMemberDb,
+    new_owner_id: Uuid,
+) -> Result<GroupMemberDb, AppError> {
+    if !member.role.can_perform(GroupAction::TransferOwnership) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    // Already theirs
+    if new_owner_id == member.user_id {
+        return Ok(member);
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Locked so the current owner can't lose ownership some other way meanwhile
+    let owners = state
+        .group_repo
+        .lock_owners(&mut tx, member.group_id)
+        .await
+        .map_err(GroupError::Database)?;
+
+    if !owners.contains(&member.user_id) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let target = state
+        .group_repo
+        .get_member(&mut *tx, member.group_id, new_owner_id)
+        .await?
+        .ok_or(GroupError::MemberNotFound)?;
+
+    if !target.role.can_own_group() {
+        return Err(GroupError::GuestRole.into());
+    }
+
+    let new_owner = state
+        .group_repo
+        .update_member_role(
+            &mut *tx,
+            member.group_id,
+            new_owner_id,
+            GroupMemberRole::Owner,
+        )
+        .await
+        .map_err(GroupError::Database)?;
+
+    state
+        .group_repo
+        .update_member_role(
+            &mut *tx,
+            member.group_id,
+            member.user_id,
+            GroupMemberRole::Admin,
+        )
+        .await
+        .map_err(GroupError::Database)?;
+
+    // Another owner taking over keeps the role they already had
+    let role_changes = [
+        (target.role, new_owner_id, GroupMemberRole::Owner),
+        (member.role, member.user_id, GroupMemberRole::Admin),
+    ];
+
+    for (_, user_id, role) in role_changes
+        .into_iter()
+        .filter(|(previous, _, role)| previous != role)
+    {
+        state
+            .activity_repo
+            .create(
+                &mut *tx,
+                NewActivity {
+                    user_id: Some(user_id),
+                    actor_id: Some(member.user_id),
+                    role: Some(role),
+                    ..NewActivity::new(member.group_id, ActivityKind::RoleChanged)
+                },
+            )
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(new_owner)
+}
+
+/// Combines two members who are the same person. Results of the merged member move over to the
+/// one kept, and where both played in the same match only one of their results stays. The merged
+/// member then leaves the group, or is deleted if they're a guest
+pub async fn merge_members(
+    state: &AppState,
+    member: GroupMemberDb,
+    from_user_id: Uuid,
+    payload: MergeMemberReq,
+) -> Result<(), AppError> {
+    if from_user_id == payload.into_user_id {
+        return Err(GroupError::MergeIntoSelf.into());
+    }
+
+    let from = state
+        .group_repo
+        .get_member(&state.pool, member.group_id, from_user_id)
+        .await?
+        .ok_or(GroupError::MemberNotFound)?;
+
+    let into = state
+        .group_repo
+        .get_member(&state.pool, member.group_id, payload.into_user_id)
+        .await?
+        .ok_or(GroupError::MemberNotFound)?;
+
+    // The merged member is removed, so removing them has to be allowed too
+    if !member.role.can_perform(GroupAction::MergeMembers)
+        || !member
+            .role
+            .can_perform(GroupAction::RemoveMember(from.role))
+    {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    if into.role == GroupMemberRole::Guest && from.role != GroupMemberRole::Guest {
+        return Err(GroupError::MergeIntoGuest.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    if from.role == GroupMemberRole::Owner {
+        ensure_other_owner(state, &mut tx, member.group_id, from.user_id).await?;
+    }
+
+    // Every match affected has the merged member in it, including those where the other result wins
+    // and those deleted for no longer being valid
+    let scores: Vec<_> = state
+        .match_repo
+        .get_user_scores(&mut *tx, from.user_id)
+        .await?
+        .into_iter()
+        .filter(|s| s.group_id == member.group_id)
+        .collect();
+
+    let (dropped, kept) = match payload.conflicts {
+        MergeConflict::KeepTarget => (from.user_id, into.user_id),
+        MergeConflict::KeepSource => (into.user_id, from.user_id),
+    };
+
+    state
+        .match_repo
+        .delete_shared_scores(&mut tx, member.group_id, dropped, kept)
+        .await?;
+
+    move_history(state, &mut tx, member.group_id, from.user_id, into.user_id).await?;
+
+    if from.role == GroupMemberRole::Guest {
+        state.user_repo.delete(&mut *tx, from.user_id).await?;
+    } else {
+        state
+            .group_repo
+            .remove_member(&mut *tx, member.group_id, from.user_id)
+            .await
+            .map_err(GroupError::Database)?;
+    }
+
+    let seasons_by_game = seasons_by_game(&scores);
+    for (game_id, season_ids) in &seasons_by_game {
+        refresh_season_results(state, &mut tx, *game_id, season_ids).await?;
+    }
+
+    tx.commit().await?;
+
+    for game_id in seasons_by_game.into_keys() {
+        // Invalidate cache
+        state
+            .stats_cache_invalidator
+            .invalidate_game_stats(game_id)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Moves everything a player took part in within a group over to another player. Any matches they
+/// both played in must have been dealt with beforehand
+pub async fn move_history(
+    state: &AppState,
+    tx: &mut PgConnection,
+    group_id: Uuid,
+    from_user_id: Uuid,
+    to_user_id: Uuid,
+) -> Result<(), AppError> {
+    state
+        .match_repo
+        .reassign_scores(tx, group_id, from_user_id, to_user_id)
+        .await?;
+
+    state
+        .tournament_repo
+        .reassign_player(tx, group_id, from_user_id, to_user_id)
+        .await?;
+
+    state
+        .fixture_repo
+        .reassign_player(tx, group_id, from_user_id, to_user_id)
+        .await?;
+
+    state
+        .activity_repo
+        .reassign_user(tx, group_id, from_user_id, to_user_id)
+        .await?;
+
+    Ok(())
+}
+
+/// Makes sure every group a departing user owns keeps an owner. Each goes to the member chosen
+/// for it, or otherwise to the longest-serving admin, and groups nobody else is in are deleted
+pub async fn hand_over_groups(
+    state: &AppState,
+    tx: &mut PgConnection,
+    user_id: Uuid,
+    new_owners: &HashMap<Uuid, Uuid>,
+) -> Result<(), AppError> {
+    let owned_groups = state
+        .group_repo
+        .get_owned_groups(&mut *tx, user_id)
+        .await
+        .map_err(GroupError::Database)?;
+
+    for group in owned_groups {
+        let owners = state
+            .group_repo
+            .lock_owners(tx, group.id)
+            .await
+            .map_err(GroupError::Database)?;
+
+        let successor = match new_owners.get(&group.id).filter(|id| **id != user_id) {
+            Some(new_owner_id) => {
+                let new_owner = state
+                    .group_repo
+                    .get_member(&mut *tx, group.id, *new_owner_id)
+                    .await?
+                    .ok_or(GroupError::MemberNotFound)?;
+
+                if !new_owner.role.can_own_group() {
+                    return Err(GroupError::GuestRole.into());
+                }
+
+                Some(new_owner)
+            }
+
+            // Other owners carry on without them
+            None if owners.iter().any(|id| *id != user_id) => continue,
+
+            None => state
+                .group_repo
+                .get_successor(&mut *tx, group.id, user_id)
+                .await
+                .map_err(GroupError::Database)?,
+        };
+
+        match successor {
+            Some(successor) if successor.role != GroupMemberRole::Owner => {
+                state
+                    .group_repo
+                    .update_member_role(
+                        &mut *tx,
+                        group.id,
+                        successor.user_id,
+                        GroupMemberRole::Owner,
+                    )
+                    .await
+                    .map_err(GroupError::Database)?;
+
+                state
+                    .activity_repo
+                    .create(
+                        &mut *tx,
+                        NewActivity {
+                            user_id: Some(successor.user_id),
+                            actor_id: Some(user_id),
+                            role: Some(GroupMemberRole::Owner),
+                            ..NewActivity::new(group.id, ActivityKind::RoleChanged)
+                        },
+                    )
+                    .await?;
+            }
+            Some(_) => {}
+            None => {
+                state
+                    .group_repo
+                    .delete(&mut *tx, group.id)
+                    .await
+                    .map_err(GroupError::Database)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a group has an owner other than `user_id`, before they stop being one. Owners are locked
+/// so two owners can't both step down at once
+async fn ensure_other_owner(
+    state: &AppState,
+    tx: &mut PgConnection,
+    group_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let owners = state
+        .group_repo
+        .lock_owners(tx, group_id)
+        .await
+        .map_err(GroupError::Database)?;
+
+    if !owners.iter().any(|id| *id != user_id) {
+        return Err(GroupError::LastOwner.into());
+    }
+
+    Ok(())
+}
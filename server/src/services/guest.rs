@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::{AppError, GroupError},
+    models::group::{
+        CreateGuestReq, GroupMemberDb, GroupMemberDetailsDb, GroupMemberResponse, GroupMemberRole,
+    },
+    policies::GroupAction,
+    services::{group::move_history, season::seasons_by_game},
+};
+
+/// Adds a player without an account to a group, so they can be included in matches
+pub async fn create_guest(
+    state: &AppState,
+    member: GroupMemberDb,
+    payload: CreateGuestReq,
+) -> Result<GroupMemberResponse, AppError> {
+    if !member.role.can_perform(GroupAction::CreateGuest) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let guest = state
+        .user_repo
+        .create_guest(
+            &mut *tx,
+            &payload.name,
+            payload.avatar,
+            payload.avatar_colour,
+        )
+        .await?;
+
+    state
+        .group_repo
+        .add_member(&mut *tx, member.group_id, guest.id, GroupMemberRole::Guest)
+        .await
+        .map_err(GroupError::Database)?;
+
+    tx.commit().await?;
+
+    let guest = GroupMemberDetailsDb {
+        id: guest.id,
+        role: GroupMemberRole::Guest,
+        email: guest.email,
+        name: guest.name,
+        joined_at: guest.created_at,
+        created_at: guest.created_at,
+        avatar: guest.avatar,
+        avatar_colour: guest.avatar_colour,
+    };
+
+    Ok(GroupMemberResponse::from_db(guest, member.role))
+}
+
+/// Hands a guest's place in a group to the user who claimed it, then removes the guest. Returns
+/// the seasons of each game they played in, whose results need refreshing once committed
+pub async fn claim_guest(
+    state: &AppState,
+    tx: &mut PgConnection,
+    group_id: Uuid,
+    guest_id: Uuid,
+    user_id: Uuid,
+) -> Result<HashMap<Uuid, Vec<Uuid>>, AppError> {
+    let guest = state
+        .group_repo
+        .get_member(&mut *tx, group_id, guest_id)
+        .await?
+        .filter(|m| m.role == GroupMemberRole::Guest)
+        .ok_or(GroupError::MemberNotFound)?;
+
+    // Someone can't have played against themselves
+    if state
+        .match_repo
+        .share_matches(&mut *tx, group_id, guest.user_id, user_id)
+        .await?
+    {
+        return Err(GroupError::SharedMatches.into());
+    }
+
+    // A guest only ever belongs to one group
+    let scores = state
+        .match_repo
+        .get_user_scores(&mut *tx, guest.user_id)
+        .await?;
+
+    move_history(state, tx, group_id, guest.user_id, user_id).await?;
+
+    state.user_repo.delete(&mut *tx, guest.user_id).await?;
+
+    Ok(seasons_by_game(&scores))
+}
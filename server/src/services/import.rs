@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, GroupError, MatchError},
+    models::{
+        game::GameDb,
+        game_match::{CreateMatchReq, CreateMatchScoreReq, MatchOutcome, MatchScoreDb},
+        group::{GroupMemberDetailsDb, OrderBy},
+        import::{
+            ImportFormat, ImportMatch, ImportMatchReq, ImportReport, ImportRowError, ImportScoreReq,
+        },
+        stats::{MatchChange, MatchChangedEvent, OrderDir},
+    },
+    policies::GroupAction,
+    services::{
+        game::fetch_game_guarded,
+        game_match::validate_scores,
+        season::{refresh_season_results, season_at},
+    },
+};
+
+pub const MAX_IMPORT_MATCHES: usize = 5000;
+
+/// A row of a CSV import. Rows with the same `match` (or the same date, without one) are one match
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    #[serde(rename = "match", default)]
+    match_key: Option<String>,
+    date: String,
+    player: String,
+    score: Option<i32>,
+    placement: Option<i32>,
+    outcome: Option<MatchOutcome>,
+    team: Option<i32>,
+}
+
+/// Matches ready to be inserted, along with the row they came from
+struct PreparedMatch {
+    row: usize,
+    played_at: DateTime<Utc>,
+    scores: Vec<MatchScoreDb>,
+}
+
+/// Imports historical matches for a game. Either every match is imported or none are, and a dry
+/// run checks everything without saving
+pub async fn import_matches(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    format: ImportFormat,
+    body: &str,
+    dry_run: bool,
+) -> Result<ImportReport, AppError> {
+    let (game, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::ImportMatches) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let (matches, mut errors) = match format {
+        ImportFormat::Csv => parse_csv(body)?,
+        ImportFormat::Json => (parse_json(body)?, Vec::new()),
+    };
+
+    if matches.len() > MAX_IMPORT_MATCHES {
+        return Err(MatchError::ImportTooLarge(MAX_IMPORT_MATCHES).into());
+    }
+
+    let members = state
+        .group_repo
+        .get_members(
+            &state.pool,
+            game.group_id,
+            OrderBy::Name,
+            OrderDir::Ascending,
+        )
+        .await?;
+
+    let now = Utc::now();
+    let mut prepared = Vec::with_capacity(matches.len());
+    for game_match in matches {
+        match prepare_match(&game, &members, game_match, now) {
+            Ok(game_match) => prepared.push(game_match),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    // Oldest first, so seasons are rolled over in order
+    prepared.sort_by_key(|m| m.played_at);
+
+    let mut tx = state.pool.begin().await?;
+
+    // History from before the game was created belongs to its first season
+    if let Some(earliest) = prepared.first() {
+        state
+            .season_repo
+            .extend_first_season(&mut tx, game.id, earliest.played_at)
+            .await?;
+    }
+
+    let mut recorded = Vec::new();
+    let mut season_ids = Vec::new();
+    for game_match in prepared {
+        let season = match season_at(state, &mut tx, game.id, game_match.played_at).await {
+            Ok(season) => season,
+            Err(AppError::Match(err @ MatchError::NoSeasonAtTime)) => {
+                errors.push(ImportRowError {
+                    row: game_match.row,
+                    error: err.to_string(),
+                });
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let game_match = state
+            .match_repo
+            .create(
+                &mut tx,
+                game.id,
+                season.id,
+                user_id,
+                game_match.played_at,
+                game_match.scores,
+            )
+            .await
+            .map_err(MatchError::Database)?;
+
+        recorded.push(MatchChangedEvent {
+            change: MatchChange::Recorded,
+            game_id: game.id,
+            match_id: game_match.id,
+            season_id: season.id,
+        });
+        if !season_ids.contains(&season.id) {
+            season_ids.push(season.id);
+        }
+    }
+
+    errors.sort_by_key(|e| e.row);
+
+    if dry_run || !errors.is_empty() {
+        tx.rollback().await?;
+
+        return Ok(ImportReport {
+            dry_run,
+            imported: false,
+            matches: recorded.len(),
+            errors,
+        });
+    }
+
+    refresh_season_results(state, &mut tx, game.id, &season_ids).await?;
+
+    tx.commit().await?;
+
+    // Invalidate cache
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    let matches = recorded.len();
+    for event in recorded {
+        state.live_updates.publish(event);
+    }
+
+    Ok(ImportReport {
+        dry_run,
+        imported: true,
+        matches,
+        errors,
+    })
+}
+
+/// Reads the matches of a CSV import. Rows that can't be read are reported rather than failing the
+/// whole import
+fn parse_csv(body: &str) -> Result<(Vec<ImportMatch>, Vec<ImportRowError>), AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| MatchError::InvalidImport(e.to_string()))?
+        .clone();
+
+    for column in ["date", "player"] {
+        if !headers.iter().any(|h| h == column) {
+            return Err(MatchError::InvalidImport(format!("Missing '{column}' column")).into());
+        }
+    }
+
+    let mut matches: Vec<ImportMatch> = Vec::new();
+    let mut errors = Vec::new();
+    let mut match_indexes: HashMap<String, usize> = HashMap::new();
+
+    for result in reader.records() {
+        let record = result.map_err(|e| MatchError::InvalidImport(e.to_string()))?;
+        let line = record.position().map_or(0, |p| p.line() as usize);
+
+        let row: CsvRow = match record.deserialize(Some(&headers)) {
+            Ok(row) => row,
+            Err(_) => {
+                errors.push(ImportRowError {
+                    row: line,
+                    error: "Row could not be read, check each value suits its column".to_string(),
+                });
+                continue;
+            }
+        };
+
+        let key = row
+            .match_key
+            .filter(|k| !k.is_empty())
+            .unwrap_or_else(|| row.date.clone());
+
+        let score = ImportScoreReq {
+            player: row.player,
+            score: row.score,
+            placement: row.placement,
+            outcome: row.outcome,
+            team: row.team,
+        };
+
+        match match_indexes.get(&key) {
+            Some(&index) => matches[index].scores.push(score),
+            None => {
+                match_indexes.insert(key, matches.len());
+                matches.push(ImportMatch {
+                    row: line,
+                    played_at: row.date,
+                    scores: vec![score],
+                });
+            }
+        }
+    }
+
+    Ok((matches, errors))
+}
+
+/// Reads the matches of a JSON import, where the row of a match is its position in the array
+fn parse_json(body: &str) -> Result<Vec<ImportMatch>, AppError> {
+    let matches: Vec<ImportMatchReq> =
+        serde_json::from_str(body).map_err(|e| MatchError::InvalidImport(e.to_string()))?;
+
+    Ok(matches
+        .into_iter()
+        .enumerate()
+        .map(|(i, m)| ImportMatch {
+            row: i + 1,
+            played_at: m.played_at,
+            scores: m.scores,
+        })
+        .collect())
+}
+
+/// Checks a match with the same rules as recording one, finding each player among the members
+fn prepare_match(
+    game: &GameDb,
+    members: &[GroupMemberDetailsDb],
+    game_match: ImportMatch,
+    now: DateTime<Utc>,
+) -> Result<PreparedMatch, ImportRowError> {
+    let row = game_match.row;
+    let to_error = |error: String| ImportRowError { row, error };
+
+    let played_at = parse_played_at(&game_match.played_at)
+        .ok_or_else(|| to_error(format!("Invalid date '{}'", game_match.played_at)))?;
+
+    if played_at > now {
+        return Err(to_error(MatchError::PlayedInFuture.to_string()));
+    }
+
+    let scores = game_match
+        .scores
+        .into_iter()
+        .map(|s| {
+            Ok(CreateMatchScoreReq {
+                user_id: find_member(members, &s.player)?,
+                score: s.score,
+                placement: s.placement,
+                outcome: s.outcome,
+                team: s.team,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(to_error)?;
+
+    let payload = CreateMatchReq {
+        scores,
+        played_at: Some(played_at),
+    };
+
+    payload.validate().map_err(|e| to_error(e.to_string()))?;
+
+    let (scores, _) = validate_scores(game, payload.scores).map_err(|e| to_error(e.to_string()))?;
+
+    Ok(PreparedMatch {
+        row,
+        played_at,
+        scores,
+    })
+}
+
+/// Finds a member by email, or by name as long as only one member has it
+fn find_member(members: &[GroupMemberDetailsDb], player: &str) -> Result<Uuid, String> {
+    if let Some(member) = members
+        .iter()
+        .find(|m| m.email.eq_ignore_ascii_case(player))
+    {
+        return Ok(member.id);
+    }
+
+    let mut named = members
+        .iter()
+        .filter(|m| m.name.trim().eq_ignore_ascii_case(player));
+
+    match (named.next(), named.next()) {
+        (Some(member), None) => Ok(member.id),
+        (Some(_), Some(_)) => Err(format!(
+            "More than one member is called '{player}', use their email instead"
+        )),
+        (None, _) => Err(format!("No member of this group matches '{player}'")),
+    }
+}
+
+/// Reads a date with an optional time. Anything without a time zone is treated as UTC
+fn parse_played_at(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.with_timezone(&Utc));
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date_time.and_utc());
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date_time| date_time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_csv_rows_are_grouped_into_matches() {
+        let body = "date,player,score\n\
+                    2024-03-01,Alice,10\n\
+                    2024-03-01,bob@example.com,7\n\
+                    2024-03-02,Alice,not a number\n\
+                    2024-03-08,Bob,3\n";
+
+        let (matches, errors) = parse_csv(body).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].row, 2);
+        assert_eq!(matches[0].scores.len(), 2);
+        assert_eq!(matches[0].scores[1].player, "bob@example.com");
+        assert_eq!(matches[1].row, 5);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 4);
+    }
+
+    #[test]
+    fn test_csv_match_column_separates_matches_on_the_same_day() {
+        let body = "match,date,player,placement\n\
+                    1,2024-03-01,Alice,1\n\
+                    1,2024-03-01,Bob,2\n\
+                    2,2024-03-01,Alice,2\n\
+                    2,2024-03-01,Bob,1\n";
+
+        let (matches, errors) = parse_csv(body).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].scores[1].placement, Some(1));
+    }
+
+    #[test]
+    fn test_csv_without_required_columns_is_rejected() {
+        assert!(parse_csv("when,who,score\n2024-03-01,Alice,10\n").is_err());
+    }
+
+    #[test]
+    fn test_played_at_formats() {
+        let expected = Utc.with_ymd_and_hms(2024, 3, 1, 19, 30, 0).unwrap();
+
+        assert_eq!(parse_played_at("2024-03-01T19:30:00Z"), Some(expected));
+        assert_eq!(parse_played_at("2024-03-01T20:30:00+01:00"), Some(expected));
+        assert_eq!(parse_played_at("2024-03-01 19:30"), Some(expected));
+        assert_eq!(
+            parse_played_at("2024-03-01"),
+            Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(parse_played_at("01/03/2024"), None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Months, Utc};
 use uuid::Uuid;
 
@@ -5,11 +7,13 @@ use crate::{
     AppState,
     errors::{AppError, GroupError, InviteError},
     models::{
-        group::GroupMemberDb,
+        activity::{ActivityKind, NewActivity},
+        group::{GroupMemberDb, GroupMemberRole},
         invite::{CreateInviteReq, InviteDb, InviteWithCreatedByNameDb},
         user::UserDb,
     },
     policies::GroupAction,
+    services::{self, season::refresh_season_results},
 };
 
 pub async fn create_link(
@@ -21,6 +25,19 @@ pub async fn create_link(
         return Err(GroupError::Forbidden.into());
     }
 
+    if payload.role == GroupMemberRole::Guest {
+        return Err(GroupError::GuestRole.into());
+    }
+
+    if let Some(guest_id) = payload.guest_id {
+        state
+            .group_repo
+            .get_member(&state.pool, creator.group_id, guest_id)
+            .await?
+            .filter(|m| m.role == GroupMemberRole::Guest)
+            .ok_or(GroupError::MemberNotFound)?;
+    }
+
     // Expires at - default to 1 month from now
     let expires_at =
         match payload.expires_at {
@@ -47,6 +64,7 @@ pub async fn create_link(
                 .map(|e| e.address)
                 .collect(),
             Some(expires_at),
+            payload.guest_id,
         )
         .await
         .map_err(InviteError::Database)?)
@@ -87,8 +105,41 @@ pub async fn accept_invite(
         .increment_uses(&mut *tx, invite_code)
         .await?;
 
+    // Take over the guest's results
+    let seasons_by_game = match invite.guest_id {
+        Some(guest_id) => {
+            services::guest::claim_guest(state, &mut tx, invite.group_id, guest_id, user.id).await?
+        }
+        None => HashMap::new(),
+    };
+
+    state
+        .activity_repo
+        .create(
+            &mut *tx,
+            NewActivity {
+                user_id: Some(user.id),
+                actor_id: Some(invite.created_by),
+                role: Some(invite.role),
+                ..NewActivity::new(invite.group_id, ActivityKind::MemberJoined)
+            },
+        )
+        .await?;
+
+    for (game_id, season_ids) in &seasons_by_game {
+        refresh_season_results(state, &mut tx, *game_id, season_ids).await?;
+    }
+
     tx.commit().await?;
 
+    for game_id in seasons_by_game.into_keys() {
+        // Invalidate cache
+        state
+            .stats_cache_invalidator
+            .invalidate_game_stats(game_id)
+            .await?;
+    }
+
     Ok(())
 }
 
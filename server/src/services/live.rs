@@ -0,0 +1,147 @@
+use axum::response::sse::Event;
+use futures_util::{Stream, stream};
+use tokio::sync::broadcast::{self, Receiver, Sender, error::RecvError};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::{AppError, GameError, GroupError},
+    models::stats::{
+        LiveScoreboardEvent, LiveScoreboardParams, MatchChangedEvent, ScoreboardResponse,
+        SeasonScope,
+    },
+    services::game::fetch_game_guarded,
+};
+
+/// How many matches a slow client can fall behind on before it's told to refetch instead
+const LIVE_UPDATES_CAPACITY: usize = 64;
+
+/// Tells live scoreboards about matches being recorded, edited or deleted
+// NOTE: updates only reach clients connected to the same server the match was changed on
+pub struct LiveUpdates {
+    sender: Sender<MatchChangedEvent>,
+}
+
+impl Default for LiveUpdates {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(LIVE_UPDATES_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl LiveUpdates {
+    pub fn publish(&self, event: MatchChangedEvent) {
+        // Nobody may be watching, which is fine
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> Receiver<MatchChangedEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Streams an event for each match changed in a game, for as long as the user can see it
+pub async fn scoreboard_updates(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    params: LiveScoreboardParams,
+) -> Result<impl Stream<Item = Result<Event, axum::Error>> + use<>, AppError> {
+    fetch_game_guarded(state, game_id, user_id).await?;
+
+    let subscription = Subscription {
+        receiver: state.live_updates.subscribe(),
+        state: state.clone(),
+        user_id,
+        game_id,
+        params,
+    };
+
+    Ok(stream::unfold(
+        subscription,
+        |mut subscription| async move {
+            let event = subscription.next_event().await?;
+            Some((event, subscription))
+        },
+    ))
+}
+
+struct Subscription {
+    receiver: Receiver<MatchChangedEvent>,
+    state: AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    params: LiveScoreboardParams,
+}
+
+impl Subscription {
+    /// Waits for the next change to the game's matches. Returns `None` once the user can no longer
+    /// see it
+    async fn next_event(&mut self) -> Option<Result<Event, axum::Error>> {
+        loop {
+            let event = match self.receiver.recv().await {
+                Ok(event) if event.game_id == self.game_id => event,
+                Ok(_) => continue,
+
+                // Some matches were missed, so the client should fetch the scoreboard itself
+                Err(RecvError::Lagged(_)) => return Some(Ok(resync())),
+                Err(RecvError::Closed) => return None,
+            };
+
+            // They may have left the group, or the game been deleted, since subscribing. Anything
+            // else is likely to pass, so the client is left to catch up by itself
+            match fetch_game_guarded(&self.state, self.game_id, self.user_id).await {
+                Ok(_) => {}
+                Err(AppError::Group(GroupError::MemberNotFound))
+                | Err(AppError::Game(GameError::NotFound)) => return None,
+                Err(_) => return Some(Ok(resync())),
+            }
+
+            let scoreboard = if self.params.include_scoreboard {
+                match self.scoreboard().await {
+                    Ok(scoreboard) => Some(scoreboard),
+                    Err(_) => return Some(Ok(resync())),
+                }
+            } else {
+                None
+            };
+
+            return Some(
+                Event::default()
+                    .event(event.change.event_name())
+                    .json_data(LiveScoreboardEvent { event, scoreboard }),
+            );
+        }
+    }
+
+    async fn scoreboard(&self) -> Result<ScoreboardResponse, AppError> {
+        // Resolved each time, as the latest season may have rolled over
+        let season_id = self
+            .params
+            .season
+            .as_ref()
+            .unwrap_or(&SeasonScope::All)
+            .to_season_id(&self.state, self.game_id)
+            .await?;
+
+        let scoreboard = self
+            .state
+            .stats_service
+            .get_scoreboard_and_stats(
+                &self.state,
+                self.user_id,
+                self.game_id,
+                season_id,
+                self.params.order_by,
+                self.params.order_dir,
+            )
+            .await?;
+
+        Ok(ScoreboardResponse::new(scoreboard, season_id))
+    }
+}
+
+/// Tells the client to fetch the scoreboard itself
+fn resync() -> Event {
+    Event::default().event("resync").data("")
+}
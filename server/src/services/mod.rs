@@ -1,9 +1,16 @@
+pub mod activity;
 pub mod auth;
 pub mod email;
+pub mod export;
+pub mod fixture;
 pub mod game;
 pub mod game_match;
 pub mod group;
+pub mod guest;
+pub mod import;
 pub mod invite;
+pub mod live;
 pub mod season;
 pub mod stats;
+pub mod tournament;
 pub mod user;
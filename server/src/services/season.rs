@@ -1,23 +1,461 @@
-use crate::{AppState, errors::AppError};
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::{AppError, GameError, GroupError, MatchError},
+    models::{
+        activity::{ActivityKind, NewActivity},
+        export::UserScoreExportDb,
+        game::GameDb,
+        season::{SeasonDb, SeasonResults, StartSeasonReq, TrophyDb, UpdateSeasonReq},
+        stats::ScoreboardEntry,
+    },
+    policies::GroupAction,
+    services::{game::fetch_game_guarded, stats::db::DbStatsProvider},
+};
 
 // NOTE: this should not accessible from the external API, only interval (clean up job)
 pub async fn check_and_update_seasons(state: &AppState) -> Result<(), AppError> {
     let mut tx = state.pool.begin().await?;
 
     // Check all seasons that have expired
-    let mut expired_seasons = state.season_repo.expired_seasons(&mut tx).await?;
+    let now = Utc::now();
+    let mut expired_seasons = state.season_repo.expired_seasons(&mut tx, now).await?;
 
     while !expired_seasons.is_empty() {
         // Create new seasons for all the expired ones
         for season in &expired_seasons {
-            state.season_repo.new_season(&mut tx, season).await?;
+            roll_over_season(state, &mut tx, season).await?;
         }
 
         // If multiple seasons have passed since last interval, create them all
-        expired_seasons = state.season_repo.expired_seasons(&mut tx).await?;
+        expired_seasons = state.season_repo.expired_seasons(&mut tx, now).await?;
     }
 
     tx.commit().await?;
 
     Ok(())
 }
+
+/// Freezes the final standings of a season that has ended, then starts the next one
+pub async fn roll_over_season(
+    state: &AppState,
+    tx: &mut PgConnection,
+    season: &SeasonDb,
+) -> Result<SeasonDb, AppError> {
+    // Open-ended seasons never expire, so must be given an end date first
+    let end_date = season.end_date.ok_or_else(|| {
+        AppError::InternalServerError("Cannot roll over an open-ended season".to_string())
+    })?;
+
+    close_season(state, tx, season).await?;
+
+    let next_season = state
+        .season_repo
+        .new_season(tx, season.game_id, end_date)
+        .await?;
+
+    Ok(next_season)
+}
+
+/// Rolls over the latest season until it is the one running at `now`
+pub async fn catch_up_seasons(
+    state: &AppState,
+    tx: &mut PgConnection,
+    mut latest_season: SeasonDb,
+    now: DateTime<Utc>,
+) -> Result<SeasonDb, AppError> {
+    while let Some(end_date) = latest_season.end_date
+        && end_date <= now
+    {
+        latest_season = roll_over_season(state, tx, &latest_season).await?;
+    }
+
+    Ok(latest_season)
+}
+
+/// Finds the season running when a match was played, rolling over any seasons that have ended
+pub async fn season_at(
+    state: &AppState,
+    tx: &mut PgConnection,
+    game_id: Uuid,
+    played_at: DateTime<Utc>,
+) -> Result<SeasonDb, AppError> {
+    // The interval job may not have rolled over a season that has just ended
+    let latest_season = state.season_repo.get_latest(tx, game_id).await?;
+    let latest_season = catch_up_seasons(state, tx, latest_season, Utc::now()).await?;
+
+    if latest_season.start_date <= played_at {
+        return Ok(latest_season);
+    }
+
+    let season = state
+        .season_repo
+        .get_at(tx, game_id, played_at)
+        .await?
+        .ok_or(MatchError::NoSeasonAtTime)?;
+
+    Ok(season)
+}
+
+/// Works out the final standings of seasons again after their matches have changed, as part of the
+/// transaction that changed them. Seasons that are still running don't have final standings, so
+/// are skipped
+pub async fn refresh_season_results(
+    state: &AppState,
+    tx: &mut PgConnection,
+    game_id: Uuid,
+    season_ids: &[Uuid],
+) -> Result<(), AppError> {
+    let now = Utc::now();
+
+    for season_id in season_ids {
+        let season = state
+            .season_repo
+            .get_season(tx, game_id, *season_id)
+            .await?;
+
+        if season.end_date.is_none_or(|end_date| end_date > now) {
+            continue;
+        }
+
+        state.season_repo.delete_results(tx, season.id).await?;
+        finalise_season(state, tx, &season).await?;
+    }
+
+    Ok(())
+}
+
+/// Groups the seasons a player has results in by game, ready for refreshing
+pub fn seasons_by_game(scores: &[UserScoreExportDb]) -> HashMap<Uuid, Vec<Uuid>> {
+    let mut seasons_by_game: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for score in scores {
+        let season_ids = seasons_by_game.entry(score.game_id).or_default();
+        if !season_ids.contains(&score.season_id) {
+            season_ids.push(score.season_id);
+        }
+    }
+
+    seasons_by_game
+}
+
+/// Freezes the final standings of a season that has just ended, and announces its winner in the
+/// group's feed
+async fn close_season(
+    state: &AppState,
+    tx: &mut PgConnection,
+    season: &SeasonDb,
+) -> Result<(), AppError> {
+    let (game, entries) = finalise_season(state, tx, season).await?;
+
+    // Seasons nobody played in have no winner
+    let winner = entries
+        .iter()
+        .find(|e| e.rank == 1 && e.matches_played > 0)
+        .map(|e| e.user_id);
+
+    state
+        .activity_repo
+        .create(
+            &mut *tx,
+            NewActivity {
+                user_id: winner,
+                game_id: Some(game.id),
+                season_id: Some(season.id),
+                ..NewActivity::new(game.group_id, ActivityKind::SeasonEnded)
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn finalise_season(
+    state: &AppState,
+    tx: &mut PgConnection,
+    season: &SeasonDb,
+) -> Result<(GameDb, Vec<ScoreboardEntry>), AppError> {
+    let game = state
+        .game_repo
+        .get(&mut *tx, season.game_id)
+        .await?
+        .ok_or(GameError::NotFound)?;
+
+    let (entries, highlights) =
+        DbStatsProvider::get_final_standings(state, &mut *tx, &game, season.id).await?;
+
+    state
+        .season_repo
+        .create_results(tx, season.id, &entries, &highlights)
+        .await?;
+
+    Ok((game, entries))
+}
+
+/// Ends the latest season now and starts a new one in its place
+pub async fn start_season(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    payload: StartSeasonReq,
+) -> Result<SeasonDb, AppError> {
+    let (game, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::StartSeason) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let now = Utc::now();
+    if payload.end_date.is_some_and(|end_date| end_date <= now) {
+        return Err(GameError::SeasonEndBeforeStart.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // The interval job may not have rolled over a season that has already ended
+    let latest_season = state.season_repo.get_latest(&mut tx, game.id).await?;
+    let latest_season = catch_up_seasons(state, &mut tx, latest_season, now).await?;
+    if latest_season.start_date >= now {
+        return Err(GameError::SeasonOverlap.into());
+    }
+
+    let ended_season = state
+        .season_repo
+        .update_season_end_date(&mut tx, latest_season.id, Some(now))
+        .await?;
+    close_season(state, &mut tx, &ended_season).await?;
+
+    let season = state
+        .season_repo
+        .create_season(
+            &mut tx,
+            game.id,
+            payload.name.as_deref(),
+            now,
+            payload.end_date,
+        )
+        .await?;
+
+    tx.commit().await?;
+
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    Ok(season)
+}
+
+/// Ends the latest season now, starting the next one as scheduled
+pub async fn end_season(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    season_id: Uuid,
+) -> Result<SeasonDb, AppError> {
+    let (game, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::EndSeason) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+    let season = state
+        .season_repo
+        .get_season(&mut tx, game.id, season_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => GameError::SeasonNotFound,
+            e => GameError::Database(e),
+        })?;
+
+    // Only the latest season can still be running
+    let now = Utc::now();
+    let latest_season = state.season_repo.get_latest(&mut tx, game.id).await?;
+    if season.id != latest_season.id || season.end_date.is_some_and(|end_date| end_date <= now) {
+        return Err(GameError::SeasonAlreadyEnded.into());
+    }
+
+    let season = state
+        .season_repo
+        .update_season_end_date(&mut tx, season.id, Some(now))
+        .await?;
+    roll_over_season(state, &mut tx, &season).await?;
+
+    tx.commit().await?;
+
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    Ok(season)
+}
+
+/// Renames a season or moves its start and end dates, as long as it doesn't overlap its neighbours.
+/// Matches are moved into whichever season now covers when they were played
+pub async fn update_season(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    season_id: Uuid,
+    payload: UpdateSeasonReq,
+) -> Result<SeasonDb, AppError> {
+    let (game, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::UpdateSeason) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    if payload
+        .end_date
+        .is_some_and(|end_date| end_date <= payload.start_date)
+    {
+        return Err(GameError::SeasonEndBeforeStart.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Newest first
+    let seasons = state.season_repo.get_seasons(&mut tx, game.id).await?;
+    let index = seasons
+        .iter()
+        .position(|s| s.id == season_id)
+        .ok_or(GameError::SeasonNotFound)?;
+
+    let newer_season = index.checked_sub(1).map(|i| &seasons[i]);
+    let older_season = seasons.get(index + 1);
+
+    // Some season has to be running for new matches to go in
+    if newer_season.is_none() && payload.start_date > Utc::now() {
+        return Err(GameError::LatestSeasonStartInFuture.into());
+    }
+
+    if let Some(newer_season) = newer_season {
+        let end_date = payload.end_date.ok_or(GameError::SeasonMustEnd)?;
+        if end_date > newer_season.start_date {
+            return Err(GameError::SeasonOverlap.into());
+        }
+    }
+
+    if let Some(older_season) = older_season
+        && older_season
+            .end_date
+            .is_none_or(|end_date| end_date > payload.start_date)
+    {
+        return Err(GameError::SeasonOverlap.into());
+    }
+
+    let mut season = state
+        .season_repo
+        .update_season(
+            &mut tx,
+            season_id,
+            payload.name.as_deref(),
+            payload.start_date,
+            payload.end_date,
+        )
+        .await?;
+
+    // Ending the latest season early starts the next one
+    let now = Utc::now();
+    if newer_season.is_none() && season.end_date.is_some_and(|end_date| end_date <= now) {
+        catch_up_seasons(state, &mut tx, season.clone(), now).await?;
+        season = state
+            .season_repo
+            .get_season(&mut tx, game.id, season_id)
+            .await?;
+    }
+
+    // A gap between seasons would leave the matches played in it without one
+    if state
+        .match_repo
+        .count_outside_seasons(&mut tx, game.id)
+        .await?
+        > 0
+    {
+        return Err(GameError::SeasonStrandsMatches.into());
+    }
+
+    let moved_season_ids = state.match_repo.reassign_seasons(&mut tx, game.id).await?;
+    refresh_season_results(state, &mut tx, game.id, &moved_season_ids).await?;
+
+    tx.commit().await?;
+
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    Ok(season)
+}
+
+pub async fn get_season_results(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    season_id: Uuid,
+) -> Result<SeasonResults, AppError> {
+    let (game, _) = fetch_game_guarded(state, game_id, user_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+    let season = state
+        .season_repo
+        .get_season(&mut tx, game.id, season_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => GameError::SeasonNotFound,
+            e => GameError::Database(e),
+        })?;
+    tx.commit().await?;
+
+    // Only seasons that have ended have results
+    let result = state
+        .season_repo
+        .get_results(&state.pool, season.id)
+        .await?
+        .ok_or(GameError::SeasonResultsNotFound)?;
+
+    let entries = state
+        .season_repo
+        .get_result_entries(&state.pool, season.id)
+        .await?;
+
+    let highlights = state
+        .season_repo
+        .get_result_highlights(&state.pool, season.id)
+        .await?
+        .into();
+
+    Ok(SeasonResults {
+        season,
+        result,
+        entries,
+        highlights,
+    })
+}
+
+pub async fn get_trophies(
+    state: &AppState,
+    user_id: Uuid,
+    player_id: Uuid,
+) -> Result<Vec<TrophyDb>, AppError> {
+    if user_id != player_id {
+        let shares_group = state
+            .group_repo
+            .users_share_group(&state.pool, user_id, player_id)
+            .await?;
+
+        if !shares_group {
+            return Err(GroupError::MemberNotFound.into());
+        }
+    }
+
+    let trophies = state
+        .season_repo
+        .get_trophies(&state.pool, player_id, user_id)
+        .await?;
+
+    Ok(trophies)
+}
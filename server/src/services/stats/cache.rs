@@ -11,7 +11,8 @@ use crate::{
     models::{
         game::OrderBy,
         stats::{
-            DistributionWithMaxMin, OrderDir, PlayerHighlightStats, PlayerMatchDb, Scoreboard,
+            DistributionWithMaxMin, HeadToHeadStats, OrderDir, PlayerHighlightStats, PlayerMatchDb,
+            PlayerPair, Scoreboard, StandingsHistory,
         },
         user::UserDb,
     },
@@ -165,6 +166,46 @@ impl<T: StatsProvider> StatsProvider for RedisCachedStatsProvider<T> {
         )
         .await
     }
+
+    async fn get_head_to_head(
+        &self,
+        state: &AppState,
+        user_id: Uuid,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+        players: PlayerPair,
+    ) -> Result<HeadToHeadStats, AppError> {
+        fetch_game_guarded(state, game_id, user_id).await?;
+        let suffix = format!(
+            "head_to_head:season:{:?}:players:{}:{}",
+            season_id, players.0, players.1
+        );
+
+        self.with_cache(
+            game_id,
+            &suffix,
+            self.inner
+                .get_head_to_head(state, user_id, game_id, season_id, players),
+        )
+        .await
+    }
+
+    async fn get_standings_history(
+        &self,
+        state: &AppState,
+        user_id: Uuid,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+    ) -> Result<StandingsHistory, AppError> {
+        fetch_game_guarded(state, game_id, user_id).await?;
+        self.with_cache(
+            game_id,
+            &format!("standings_history:season:{:?}", season_id),
+            self.inner
+                .get_standings_history(state, user_id, game_id, season_id),
+        )
+        .await
+    }
 }
 
 #[async_trait]
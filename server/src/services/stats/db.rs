@@ -4,19 +4,25 @@ use crate::{
     models::{
         game::{GameDb, OrderBy},
         stats::{
-            DistributionWithMaxMin, HighlightsResponse, OrderDir, Player, PlayerHighlightStats,
-            PlayerMatchDb, Scoreboard, ScoreboardEntry, StatsLifetime,
+            DistributionWithMaxMin, HeadToHeadStats, HighlightsResponse, OrderDir, Player,
+            PlayerHighlightStats, PlayerMatchDb, PlayerPair, RawHighlight, Scoreboard,
+            ScoreboardEntry, StandingsHistory, StatsLifetime,
         },
         user::UserDb,
     },
-    services::{game::fetch_game_guarded, stats::logic::build_scoreboard_entries},
+    services::{
+        game::fetch_game_guarded,
+        stats::logic::{build_scoreboard_entries, build_standings_history},
+    },
 };
 use async_trait::async_trait;
+use sqlx::PgConnection;
 use std::collections::HashMap;
 use uuid::Uuid;
 
 use super::StatsProvider;
-use super::logic::{get_comparator, get_player_distribution};
+use super::logic::{calculate_head_to_head, get_comparator, get_player_distribution};
+use super::rating::calculate_ratings;
 
 pub struct DbStatsProvider;
 
@@ -33,6 +39,49 @@ impl DbStatsProvider {
 
         Ok(build_scoreboard_entries(raw_data, game))
     }
+
+    /// Gets the standings and highlights of a season, to be frozen once it ends
+    pub async fn get_final_standings(
+        state: &AppState,
+        tx: &mut PgConnection,
+        game: &GameDb,
+        season_id: Uuid,
+    ) -> Result<(Vec<ScoreboardEntry>, Vec<RawHighlight>), AppError> {
+        let raw_data = state
+            .stats_repo
+            .get_all_matches(&mut *tx, game.id, Some(season_id))
+            .await?;
+        let highlights = state
+            .stats_repo
+            .get_highlights(&mut *tx, game.id, Some(season_id), game.score_direction)
+            .await?;
+
+        Ok((build_scoreboard_entries(raw_data, game), highlights))
+    }
+
+    /// Gets a player, as long as they share a group with the user
+    async fn get_shared_player(
+        state: &AppState,
+        user_id: Uuid,
+        player_id: Uuid,
+    ) -> Result<Player, AppError> {
+        let shares_group = state
+            .group_repo
+            .users_share_group(&state.pool, user_id, player_id)
+            .await?;
+
+        if !shares_group {
+            return Err(GroupError::MemberNotFound.into());
+        }
+
+        let player = state
+            .user_repo
+            .find_by_id(&state.pool, &player_id)
+            .await?
+            .ok_or(UserError::NotFound)?;
+
+        Ok(player.into())
+    }
 }
 
 #[async_trait]
@@ -54,7 +103,7 @@ impl StatsProvider for DbStatsProvider {
         let game_metric_ordering: OrderBy = game.metric.into();
         let order_by = order_by.unwrap_or(game_metric_ordering);
         if order_by != game_metric_ordering {
-            entries.sort_by(|a, b| get_comparator(order_by, a, b));
+            entries.sort_by(|a, b| get_comparator(order_by, game.score_direction, a, b));
         }
 
         if order_dir == Some(OrderDir::Ascending) {
@@ -63,7 +112,7 @@ impl StatsProvider for DbStatsProvider {
 
         let highlights: HighlightsResponse = state
             .stats_repo
-            .get_highlights(&state.pool, game_id, season_id)
+            .get_highlights(&state.pool, game_id, season_id, game.score_direction)
             .await?
             .into();
 
@@ -96,11 +145,25 @@ impl StatsProvider for DbStatsProvider {
             return Err(GroupError::MemberNotFound.into());
         }
 
-        let player_history = state
+        let mut player_history = state
             .stats_repo
             .get_player_history(&state.pool, game.id, player_id, season_id)
             .await?;
 
+        // Ratings depend on everyone's matches, so are calculated from all of them
+        let all_matches = state
+            .stats_repo
+            .get_all_matches(&state.pool, game.id, season_id)
+            .await?;
+
+        let ratings = calculate_ratings(&all_matches);
+        for m in &mut player_history {
+            if let Some(rating) = ratings.per_match.get(&(m.match_id, player_id)) {
+                m.rating = rating.rating;
+                m.rating_change = rating.change;
+            }
+        }
+
         let player = state
             .user_repo
             .find_by_id(&state.pool, &player_id)
@@ -145,6 +208,7 @@ impl StatsProvider for DbStatsProvider {
             },
             lifetime: StatsLifetime {
                 win_rate: entry.win_rate,
+                rating: entry.rating,
                 average_score: entry.average_score,
                 best_score: entry.best_score,
                 total_games: entry.matches_played,
@@ -181,11 +245,56 @@ impl StatsProvider for DbStatsProvider {
 
         let mut distributions = HashMap::<Uuid, DistributionWithMaxMin>::new();
         for player in members {
-            if let Ok(dist) = get_player_distribution(&raw_data, player.id) {
+            if let Ok(dist) = get_player_distribution(&raw_data, player.id, game.score_direction) {
                 distributions.insert(player.id, dist);
             }
         }
 
         Ok(distributions)
     }
+
+    async fn get_head_to_head(
+        &self,
+        state: &AppState,
+        user_id: Uuid,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+        players: PlayerPair,
+    ) -> Result<HeadToHeadStats, AppError> {
+        let (game, _) = fetch_game_guarded(state, game_id, user_id).await?;
+
+        let PlayerPair(first_id, second_id) = players;
+        if first_id == second_id {
+            return Err(StatsError::SamePlayer.into());
+        }
+
+        let players = [
+            Self::get_shared_player(state, user_id, first_id).await?,
+            Self::get_shared_player(state, user_id, second_id).await?,
+        ];
+
+        let raw_data = state
+            .stats_repo
+            .get_all_matches(&state.pool, game.id, season_id)
+            .await?;
+
+        Ok(calculate_head_to_head(&raw_data, players))
+    }
+
+    async fn get_standings_history(
+        &self,
+        state: &AppState,
+        user_id: Uuid,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+    ) -> Result<StandingsHistory, AppError> {
+        let (game, _) = fetch_game_guarded(state, game_id, user_id).await?;
+
+        let raw_data = state
+            .stats_repo
+            .get_all_matches(&state.pool, game.id, season_id)
+            .await?;
+
+        Ok(build_standings_history(raw_data, &game))
+    }
 }
@@ -1,27 +1,46 @@
 use crate::errors::StatsError;
-use crate::models::game::GameDb;
-use crate::models::stats::{MedalsThresholds, PlayerStats};
+use crate::models::game::{GameDb, ScoreDirection};
+use crate::models::stats::{
+    HeadToHeadMeeting, HeadToHeadPlayer, HeadToHeadStats, MedalsThresholds, Player, PlayerStats,
+    Standing, StandingsHistory, StandingsSnapshot,
+};
 use crate::models::{
     game::OrderBy,
     stats::{Distribution, DistributionWithMaxMin, RawMatchStats, ScoreboardEntry},
 };
+use crate::services::stats::rating::{INITIAL_RATING, calculate_ratings};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use uuid::Uuid;
 
 pub const MIN_MATCHES_FOR_DISTRIBUTION: usize = 5;
+pub const HEAD_TO_HEAD_RECENT_MEETINGS: usize = 5;
+
+pub fn get_comparator(
+    order: OrderBy,
+    direction: ScoreDirection,
+    a: &ScoreboardEntry,
+    b: &ScoreboardEntry,
+) -> Ordering {
+    let average_score_cmp = || match (a.average_score, b.average_score) {
+        (Some(a), Some(b)) => direction.compare(a, b),
+        _ => Ordering::Equal,
+    };
 
-pub fn get_comparator(order: OrderBy, a: &ScoreboardEntry, b: &ScoreboardEntry) -> Ordering {
     match order {
         OrderBy::WinRate => a
             .win_rate
             .total_cmp(&b.win_rate)
-            .then_with(|| a.average_score.total_cmp(&b.average_score))
+            .then_with(average_score_cmp)
+            .then_with(|| a.matches_played.cmp(&b.matches_played)),
+
+        OrderBy::AverageScore => average_score_cmp()
+            .then_with(|| a.win_rate.total_cmp(&b.win_rate))
             .then_with(|| a.matches_played.cmp(&b.matches_played)),
 
-        OrderBy::AverageScore => a
-            .average_score
-            .total_cmp(&b.average_score)
+        OrderBy::Rating => a
+            .rating
+            .total_cmp(&b.rating)
             .then_with(|| a.win_rate.total_cmp(&b.win_rate))
             .then_with(|| a.matches_played.cmp(&b.matches_played)),
 
@@ -29,7 +48,7 @@ pub fn get_comparator(order: OrderBy, a: &ScoreboardEntry, b: &ScoreboardEntry)
             .user_name
             .cmp(&b.user_name)
             .then_with(|| a.win_rate.total_cmp(&b.win_rate))
-            .then_with(|| a.average_score.total_cmp(&b.average_score))
+            .then_with(average_score_cmp)
             .then_with(|| a.matches_played.cmp(&b.matches_played)),
     }
     .then_with(|| a.user_name.cmp(&b.user_name))
@@ -46,18 +65,29 @@ pub fn calculate_stats(matches: &[RawMatchStats], thresholds: &MedalsThresholds)
     }
 
     for m in matches {
-        sum_score += m.score;
-        stats.best_score = stats.best_score.max(m.score);
-
         if m.rank_in_match == 1 {
             stats.wins += 1;
         }
 
-        stats.medals.count_medal(m.score, thresholds);
+        // Games recording placements or outcomes have no score based stats
+        let Some(score) = m.score else {
+            continue;
+        };
+
+        sum_score += score;
+        stats.best_score = Some(
+            stats
+                .best_score
+                .map_or(score, |best| thresholds.direction.best(best, score)),
+        );
+
+        stats.medals.count_medal(score, thresholds);
     }
 
+    // Matches are ordered from most recent
+    stats.current_streak = matches.iter().take_while(|m| m.rank_in_match == 1).count() as i64;
     stats.matches_played = count;
-    stats.average_score = sum_score as f64 / count as f64;
+    stats.average_score = stats.best_score.map(|_| sum_score as f64 / count as f64);
     stats.win_rate = stats.wins as f64 / count as f64;
 
     stats
@@ -66,17 +96,19 @@ pub fn calculate_stats(matches: &[RawMatchStats], thresholds: &MedalsThresholds)
 pub fn get_player_distribution(
     all_matches: &[RawMatchStats],
     player_id: Uuid,
+    score_direction: ScoreDirection,
 ) -> Result<DistributionWithMaxMin, StatsError> {
-    let player_data: Vec<_> = all_matches
+    // Games recording placements or outcomes have no scores to distribute
+    let scores: Vec<_> = all_matches
         .iter()
         .filter(|m| m.user_id == player_id)
+        .filter_map(|m| m.score)
         .collect();
 
-    if player_data.len() < MIN_MATCHES_FOR_DISTRIBUTION {
+    if scores.len() < MIN_MATCHES_FOR_DISTRIBUTION {
         return Err(StatsError::NotEnoughData);
     }
 
-    let scores: Vec<_> = player_data.iter().map(|d| d.score).collect();
     let mean = scores.iter().sum::<i32>() as f64 / scores.len() as f64;
     let variance = scores
         .iter()
@@ -102,6 +134,7 @@ pub fn get_player_distribution(
         min_score: *min,
         max_score: *max,
         distribution: dist,
+        score_direction,
     })
 }
 
@@ -114,6 +147,7 @@ pub fn build_scoreboard_entries(
     }
 
     let most_recent_match = raw_data.first().expect("No most recent game").match_id;
+    let ratings = calculate_ratings(&raw_data);
 
     // Group by user
     let mut user_groups: HashMap<Uuid, Vec<RawMatchStats>> = HashMap::new();
@@ -124,74 +158,32 @@ pub fn build_scoreboard_entries(
     let mut entries = Vec::new();
     let mut prev_entries = Vec::new();
 
-    let thresholds = MedalsThresholds {
-        star: game.star_threshold,
-        gold: game.gold_threshold,
-        silver: game.silver_threshold,
-        bronze: game.bronze_threshold,
-    };
+    let thresholds = MedalsThresholds::from(game);
 
     // Calculate stats (prev and current)
     for (user_id, matches) in user_groups {
-        let user_name = matches[0].name.clone();
-        let user_avatar = matches[0].avatar.clone();
-        let user_avatar_colour = matches[0].avatar_colour.clone();
-
         let current_stats = calculate_stats(&matches, &thresholds);
+        let rating = ratings.current(user_id);
 
-        entries.push(ScoreboardEntry {
-            user_id,
-            user_name: user_name.clone(),
-            user_avatar: user_avatar.clone(),
-            user_avatar_colour: user_avatar_colour.clone(),
-            matches_played: current_stats.matches_played,
-            average_score: current_stats.average_score,
-            best_score: current_stats.best_score,
-            wins: current_stats.wins,
-            win_rate: current_stats.win_rate,
-            star_medals: current_stats.medals.star,
-            gold_medals: current_stats.medals.gold,
-            silver_medals: current_stats.medals.silver,
-            bronze_medals: current_stats.medals.bronze,
-            rank: 0,
-            rank_diff: 0,
-            average_score_diff: 0.0,
-            win_rate_diff: 0.0,
-        });
+        entries.push(new_scoreboard_entry(&matches[0], &current_stats, rating));
 
-        // Calculate previous stats
-        let prev_matches: Vec<_> = matches
-            .into_iter()
-            .filter(|m| m.match_id != most_recent_match)
-            .collect();
+        // Calculate previous stats. Matches are ordered from most recent, so only the first one
+        // can be the most recent match
+        let prev_matches = match matches.first() {
+            Some(m) if m.match_id == most_recent_match => &matches[1..],
+            _ => &matches[..],
+        };
 
-        let prev_stats = calculate_stats(&prev_matches, &thresholds);
+        let prev_stats = calculate_stats(prev_matches, &thresholds);
+        let prev_rating = rating - ratings.change_in_match(most_recent_match, user_id);
 
-        prev_entries.push(ScoreboardEntry {
-            user_id,
-            user_name,
-            user_avatar,
-            user_avatar_colour,
-            matches_played: prev_stats.matches_played,
-            average_score: prev_stats.average_score,
-            best_score: prev_stats.best_score,
-            wins: prev_stats.wins,
-            win_rate: prev_stats.win_rate,
-            star_medals: prev_stats.medals.star,
-            gold_medals: prev_stats.medals.gold,
-            silver_medals: prev_stats.medals.silver,
-            bronze_medals: prev_stats.medals.bronze,
-            rank: 0,
-            rank_diff: 0,
-            average_score_diff: 0.0,
-            win_rate_diff: 0.0,
-        });
+        prev_entries.push(new_scoreboard_entry(&matches[0], &prev_stats, prev_rating));
     }
 
     // Sort using your comparator
     let metric_ordering: OrderBy = game.metric.into();
-    entries.sort_by(|a, b| get_comparator(metric_ordering, a, b));
-    prev_entries.sort_by(|a, b| get_comparator(metric_ordering, a, b));
+    entries.sort_by(|a, b| get_comparator(metric_ordering, game.score_direction, a, b));
+    prev_entries.sort_by(|a, b| get_comparator(metric_ordering, game.score_direction, a, b));
 
     let prev_lookup: HashMap<Uuid, _> = prev_entries
         .into_iter()
@@ -199,7 +191,7 @@ pub fn build_scoreboard_entries(
         .map(|(idx, stats)| {
             (
                 stats.user_id,
-                (idx + 1, stats.average_score, stats.win_rate),
+                (idx + 1, stats.average_score, stats.win_rate, stats.rating),
             )
         })
         .collect();
@@ -208,15 +200,351 @@ pub fn build_scoreboard_entries(
     for (index, entry) in entries.iter_mut().enumerate() {
         let current_rank = index + 1;
 
-        if let Some((prev_rank, prev_average_score, prev_win_rate)) =
+        if let Some((prev_rank, prev_average_score, prev_win_rate, prev_rating)) =
             prev_lookup.get(&entry.user_id)
         {
             entry.rank = current_rank as i32;
             entry.rank_diff = *prev_rank as i32 - current_rank as i32;
-            entry.average_score_diff = entry.average_score - prev_average_score;
+            entry.average_score_diff = entry
+                .average_score
+                .zip(*prev_average_score)
+                .map(|(current, prev)| current - prev);
             entry.win_rate_diff = entry.win_rate - prev_win_rate;
+            entry.rating_diff = entry.rating - prev_rating;
         }
     }
 
     entries
 }
+
+/// Creates an unranked entry for the player of the given match
+fn new_scoreboard_entry(
+    player_match: &RawMatchStats,
+    stats: &PlayerStats,
+    rating: f64,
+) -> ScoreboardEntry {
+    ScoreboardEntry {
+        user_id: player_match.user_id,
+        user_name: player_match.name.clone(),
+        user_avatar: player_match.avatar.clone(),
+        user_avatar_colour: player_match.avatar_colour.clone(),
+        matches_played: stats.matches_played,
+        average_score: stats.average_score,
+        best_score: stats.best_score,
+        wins: stats.wins,
+        win_rate: stats.win_rate,
+        rating,
+        current_streak: stats.current_streak,
+        star_medals: stats.medals.star,
+        gold_medals: stats.medals.gold,
+        silver_medals: stats.medals.silver,
+        bronze_medals: stats.medals.bronze,
+        rank: 0,
+        rank_diff: 0,
+        average_score_diff: None,
+        win_rate_diff: 0.0,
+        rating_diff: 0.0,
+    }
+}
+
+/// Replays matches from oldest to newest, ranking everyone who has played so far after each one in
+/// the same way as the scoreboard. Matches must be ordered from most recent
+pub fn build_standings_history(raw_data: Vec<RawMatchStats>, game: &GameDb) -> StandingsHistory {
+    let ratings = calculate_ratings(&raw_data);
+    let thresholds = MedalsThresholds::from(game);
+    let metric_ordering: OrderBy = game.metric.into();
+
+    let mut match_order = Vec::new();
+    let mut match_rows: HashMap<Uuid, Vec<RawMatchStats>> = HashMap::new();
+    for row in raw_data {
+        match_rows
+            .entry(row.match_id)
+            .or_insert_with(|| {
+                match_order.push(row.match_id);
+                Vec::new()
+            })
+            .push(row);
+    }
+
+    let mut players = Vec::new();
+    let mut snapshots = Vec::with_capacity(match_order.len());
+
+    // User ID -> their matches so far, ordered from most recent
+    let mut user_matches: HashMap<Uuid, VecDeque<RawMatchStats>> = HashMap::new();
+    let mut entries: HashMap<Uuid, ScoreboardEntry> = HashMap::new();
+
+    for match_id in match_order.into_iter().rev() {
+        let rows = match_rows.remove(&match_id).unwrap_or_default();
+        let Some(played_at) = rows.first().map(|r| r.played_at) else {
+            continue;
+        };
+
+        for row in rows {
+            let user_id = row.user_id;
+            let rating = match ratings.per_match.get(&(match_id, user_id)) {
+                Some(r) => r.rating,
+                None => entries.get(&user_id).map_or(INITIAL_RATING, |e| e.rating),
+            };
+
+            let matches = user_matches.entry(user_id).or_insert_with(|| {
+                players.push(Player {
+                    id: user_id,
+                    name: row.name.clone(),
+                    avatar: row.avatar.clone(),
+                    avatar_colour: row.avatar_colour.clone(),
+                });
+
+                VecDeque::new()
+            });
+
+            matches.push_front(row);
+            let matches = matches.make_contiguous();
+            let stats = calculate_stats(matches, &thresholds);
+
+            entries.insert(user_id, new_scoreboard_entry(&matches[0], &stats, rating));
+        }
+
+        let mut ranked: Vec<&ScoreboardEntry> = entries.values().collect();
+        ranked.sort_by(|a, b| get_comparator(metric_ordering, game.score_direction, a, b));
+
+        snapshots.push(StandingsSnapshot {
+            match_id,
+            played_at,
+            standings: ranked
+                .into_iter()
+                .enumerate()
+                .map(|(index, entry)| Standing {
+                    user_id: entry.user_id,
+                    rank: index as i32 + 1,
+                    win_rate: entry.win_rate,
+                    average_score: entry.average_score,
+                    rating: entry.rating,
+                })
+                .collect(),
+        });
+    }
+
+    StandingsHistory { players, snapshots }
+}
+
+/// Compares two players across every match they played against each other, leaving out matches
+/// where they were on the same team. Matches must be ordered from most recent
+pub fn calculate_head_to_head(
+    all_matches: &[RawMatchStats],
+    players: [Player; 2],
+) -> HeadToHeadStats {
+    let ids = [players[0].id, players[1].id];
+
+    // Match ID -> each player's result, keeping the order matches were first seen in
+    let mut match_order = Vec::new();
+    let mut results: HashMap<Uuid, [Option<&RawMatchStats>; 2]> = HashMap::new();
+    for m in all_matches {
+        let Some(index) = ids.iter().position(|id| *id == m.user_id) else {
+            continue;
+        };
+
+        let entry = results.entry(m.match_id).or_insert_with(|| {
+            match_order.push(m.match_id);
+            [None, None]
+        });
+        entry[index] = Some(m);
+    }
+
+    let meetings: Vec<[&RawMatchStats; 2]> = match_order
+        .iter()
+        .filter_map(|match_id| match results[match_id] {
+            // Teammates aren't opponents
+            [Some(a), Some(b)] if a.team.is_none() || a.team != b.team => Some([a, b]),
+            _ => None,
+        })
+        .collect();
+
+    let mut times_ahead = [0, 0];
+    let mut ties = 0;
+    let mut margins = Vec::new();
+    for [a, b] in &meetings {
+        match a.rank_in_match.cmp(&b.rank_in_match) {
+            Ordering::Less => times_ahead[0] += 1,
+            Ordering::Greater => times_ahead[1] += 1,
+            Ordering::Equal => ties += 1,
+        }
+
+        if let (Some(a_score), Some(b_score)) = (a.score, b.score) {
+            margins.push((a_score - b_score) as f64);
+        }
+    }
+
+    let average_score_margin =
+        (!margins.is_empty()).then(|| margins.iter().sum::<f64>() / margins.len() as f64);
+
+    let recent_meetings = meetings
+        .iter()
+        .take(HEAD_TO_HEAD_RECENT_MEETINGS)
+        .map(|[a, b]| HeadToHeadMeeting {
+            match_id: a.match_id,
+            played_at: a.played_at,
+            scores: [a.score, b.score],
+            ranks: [a.rank_in_match, b.rank_in_match],
+        })
+        .collect();
+
+    let [first, second] = players;
+    HeadToHeadStats {
+        players: [
+            HeadToHeadPlayer {
+                player: first,
+                times_ahead: times_ahead[0],
+            },
+            HeadToHeadPlayer {
+                player: second,
+                times_ahead: times_ahead[1],
+            },
+        ],
+        matches_played: meetings.len() as i64,
+        ties,
+        average_score_margin,
+        recent_meetings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        game::{MatchResultType, ScoringMetric, SeasonAlignment},
+        user::{Avatar, AvatarColour},
+    };
+    use chrono::{Duration, Utc};
+
+    fn row(
+        match_id: Uuid,
+        user_id: Uuid,
+        score: i32,
+        rank: i64,
+        minutes_ago: i64,
+    ) -> RawMatchStats {
+        RawMatchStats {
+            user_id,
+            name: String::new(),
+            avatar: Avatar::Helmet,
+            avatar_colour: AvatarColour::Slate,
+            match_id,
+            score: Some(score),
+            team: None,
+            played_at: Utc::now() - Duration::minutes(minutes_ago),
+            rank_in_match: rank,
+        }
+    }
+
+    fn player(id: Uuid) -> Player {
+        Player {
+            id,
+            name: String::new(),
+            avatar: Avatar::Helmet,
+            avatar_colour: AvatarColour::Slate,
+        }
+    }
+
+    fn game(metric: ScoringMetric) -> GameDb {
+        GameDb {
+            id: Uuid::new_v4(),
+            group_id: Uuid::new_v4(),
+            name: String::new(),
+            created_at: Utc::now(),
+            min_players_per_match: 1,
+            max_players_per_match: 10,
+            min_players_per_team: 1,
+            max_players_per_team: 1,
+            metric,
+            score_direction: ScoreDirection::HigherIsBetter,
+            result_type: MatchResultType::Score,
+            season_duration: None,
+            season_alignment: SeasonAlignment::None,
+            season_timezone: "UTC".to_string(),
+            star_threshold: None,
+            gold_threshold: None,
+            silver_threshold: None,
+            bronze_threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_head_to_head_only_counts_matches_both_played() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (m1, m2, m3) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        // Ordered from most recent
+        let matches = vec![
+            row(m3, a, 5, 1, 0),
+            row(m3, c, 3, 2, 0),
+            row(m2, b, 12, 1, 10),
+            row(m2, a, 8, 2, 10),
+            row(m1, a, 10, 1, 20),
+            row(m1, b, 6, 2, 20),
+        ];
+
+        let stats = calculate_head_to_head(&matches, [player(a), player(b)]);
+
+        assert_eq!(stats.matches_played, 2);
+        assert_eq!(stats.players[0].times_ahead, 1);
+        assert_eq!(stats.players[1].times_ahead, 1);
+        assert_eq!(stats.ties, 0);
+        assert_eq!(stats.average_score_margin, Some(0.0));
+        assert_eq!(stats.recent_meetings[0].match_id, m2);
+    }
+
+    #[test]
+    fn test_head_to_head_leaves_out_teammates() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (teammates, opponents) = (Uuid::new_v4(), Uuid::new_v4());
+        let on_team = |row: RawMatchStats, team| RawMatchStats {
+            team: Some(team),
+            ..row
+        };
+
+        let matches = vec![
+            on_team(row(teammates, a, 10, 1, 0), 1),
+            on_team(row(teammates, b, 10, 1, 0), 1),
+            on_team(row(teammates, c, 4, 2, 0), 2),
+            on_team(row(opponents, a, 10, 1, 10), 1),
+            on_team(row(opponents, b, 4, 2, 10), 2),
+        ];
+
+        let stats = calculate_head_to_head(&matches, [player(a), player(b)]);
+
+        assert_eq!(stats.matches_played, 1);
+        assert_eq!(stats.players[0].times_ahead, 1);
+        assert_eq!(stats.ties, 0);
+        assert_eq!(stats.recent_meetings[0].match_id, opponents);
+    }
+
+    #[test]
+    fn test_standings_history_has_a_snapshot_per_match_from_oldest() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let matches = vec![
+            row(second, b, 9, 1, 0),
+            row(second, a, 1, 2, 0),
+            row(first, a, 5, 1, 10),
+        ];
+
+        let history = build_standings_history(matches, &game(ScoringMetric::AverageScore));
+
+        assert_eq!(history.players.len(), 2);
+        assert_eq!(history.snapshots.len(), 2);
+
+        // Only the first player had played after the first match
+        assert_eq!(history.snapshots[0].match_id, first);
+        assert_eq!(history.snapshots[0].standings.len(), 1);
+        assert_eq!(history.snapshots[0].standings[0].user_id, a);
+
+        // Averages are now 9 against 3
+        let latest = &history.snapshots[1].standings;
+        assert_eq!(history.snapshots[1].match_id, second);
+        assert_eq!(latest[0].user_id, b);
+        assert_eq!(latest[0].rank, 1);
+        assert_eq!(latest[1].user_id, a);
+        assert_eq!(latest[1].average_score, Some(3.0));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod cache;
 pub mod db;
 mod logic;
+mod rating;
 
 use crate::{
     AppState,
@@ -8,7 +9,8 @@ use crate::{
     models::{
         game::OrderBy,
         stats::{
-            DistributionWithMaxMin, OrderDir, PlayerHighlightStats, PlayerMatchDb, Scoreboard,
+            DistributionWithMaxMin, HeadToHeadStats, OrderDir, PlayerHighlightStats, PlayerMatchDb,
+            PlayerPair, Scoreboard, StandingsHistory,
         },
         user::UserDb,
     },
@@ -54,6 +56,23 @@ pub trait StatsProvider: Send + Sync {
         game_id: Uuid,
         season_id: Option<Uuid>,
     ) -> Result<HashMap<Uuid, DistributionWithMaxMin>, AppError>;
+
+    async fn get_head_to_head(
+        &self,
+        state: &AppState,
+        user_id: Uuid,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+        players: PlayerPair,
+    ) -> Result<HeadToHeadStats, AppError>;
+
+    async fn get_standings_history(
+        &self,
+        state: &AppState,
+        user_id: Uuid,
+        game_id: Uuid,
+        season_id: Option<Uuid>,
+    ) -> Result<StandingsHistory, AppError>;
 }
 
 #[async_trait]
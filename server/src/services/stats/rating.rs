@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::models::stats::RawMatchStats;
+
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Maximum rating a player can gain or lose in a single match
+pub const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchRating {
+    /// Rating after the match
+    pub rating: f64,
+    pub change: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Ratings {
+    /// Rating of each player after all matches
+    pub current: HashMap<Uuid, f64>,
+
+    /// Rating of each player after each match they played, keyed by (match ID, user ID)
+    pub per_match: HashMap<(Uuid, Uuid), MatchRating>,
+}
+
+impl Ratings {
+    pub fn current(&self, user_id: Uuid) -> f64 {
+        self.current
+            .get(&user_id)
+            .copied()
+            .unwrap_or(INITIAL_RATING)
+    }
+
+    pub fn change_in_match(&self, match_id: Uuid, user_id: Uuid) -> f64 {
+        self.per_match
+            .get(&(match_id, user_id))
+            .map(|r| r.change)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Replays all matches from oldest to newest, calculating Elo ratings.
+///
+/// Matches with more than two players are treated as a set of 1v1s between every pair of opponents,
+/// with the K factor split between them so a match is worth the same regardless of size. Players
+/// in the same team are not opponents.
+pub fn calculate_ratings(matches: &[RawMatchStats]) -> Ratings {
+    let mut ratings = Ratings::default();
+
+    // Rows are ordered newest first, so group them by match and replay in reverse
+    let mut match_order: Vec<Uuid> = Vec::new();
+    let mut match_rows: HashMap<Uuid, Vec<&RawMatchStats>> = HashMap::new();
+    for row in matches {
+        match_rows
+            .entry(row.match_id)
+            .or_insert_with(|| {
+                match_order.push(row.match_id);
+                Vec::new()
+            })
+            .push(row);
+    }
+
+    for match_id in match_order.into_iter().rev() {
+        let rows = &match_rows[&match_id];
+        let before: Vec<f64> = rows.iter().map(|r| ratings.current(r.user_id)).collect();
+
+        for (i, row) in rows.iter().enumerate() {
+            let mut expected = 0.0;
+            let mut actual = 0.0;
+            let mut opponents = 0;
+
+            for (j, other) in rows.iter().enumerate() {
+                if i == j || (row.team.is_some() && row.team == other.team) {
+                    continue;
+                }
+
+                opponents += 1;
+                expected += 1.0 / (1.0 + 10f64.powf((before[j] - before[i]) / 400.0));
+                actual += match row.rank_in_match.cmp(&other.rank_in_match) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+            }
+
+            let change = if opponents > 0 {
+                K_FACTOR / opponents as f64 * (actual - expected)
+            } else {
+                0.0
+            };
+            let rating = before[i] + change;
+
+            ratings.current.insert(row.user_id, rating);
+            ratings
+                .per_match
+                .insert((match_id, row.user_id), MatchRating { rating, change });
+        }
+    }
+
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::{Avatar, AvatarColour};
+    use chrono::{Duration, Utc};
+
+    fn row(match_id: Uuid, user_id: Uuid, rank: i64, minutes_ago: i64) -> RawMatchStats {
+        RawMatchStats {
+            user_id,
+            name: String::new(),
+            avatar: Avatar::Helmet,
+            avatar_colour: AvatarColour::Slate,
+            match_id,
+            score: None,
+            team: None,
+            played_at: Utc::now() - Duration::minutes(minutes_ago),
+            rank_in_match: rank,
+        }
+    }
+
+    #[test]
+    fn test_winner_gains_what_loser_loses() {
+        let (a, b, m) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let ratings = calculate_ratings(&[row(m, a, 1, 0), row(m, b, 2, 0)]);
+
+        assert_eq!(ratings.current(a), INITIAL_RATING + K_FACTOR / 2.0);
+        assert_eq!(ratings.current(b), INITIAL_RATING - K_FACTOR / 2.0);
+        assert_eq!(ratings.change_in_match(m, a), K_FACTOR / 2.0);
+    }
+
+    #[test]
+    fn test_draw_between_equals_changes_nothing() {
+        let (a, b, m) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let ratings = calculate_ratings(&[row(m, a, 1, 0), row(m, b, 1, 0)]);
+
+        assert_eq!(ratings.current(a), INITIAL_RATING);
+        assert_eq!(ratings.current(b), INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_upset_is_worth_more() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (m1, m2) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // Newest first: b beats a after a won the first match
+        let ratings = calculate_ratings(&[
+            row(m2, b, 1, 0),
+            row(m2, a, 2, 0),
+            row(m1, a, 1, 10),
+            row(m1, b, 2, 10),
+        ]);
+
+        assert!(ratings.change_in_match(m2, b) > ratings.change_in_match(m1, a));
+    }
+
+    #[test]
+    fn test_multiplayer_is_zero_sum() {
+        let players: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let m = Uuid::new_v4();
+        let rows: Vec<_> = players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| row(m, *p, i as i64 + 1, 0))
+            .collect();
+
+        let ratings = calculate_ratings(&rows);
+        let total: f64 = players.iter().map(|p| ratings.current(*p)).sum();
+
+        assert!((total - INITIAL_RATING * 4.0).abs() < 1e-9);
+        assert!(ratings.current(players[0]) > ratings.current(players[1]));
+        assert!(ratings.current(players[2]) > ratings.current(players[3]));
+    }
+
+    #[test]
+    fn test_teammates_share_result() {
+        let players: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let m = Uuid::new_v4();
+        let mut rows: Vec<_> = players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| row(m, *p, i as i64 / 2 + 1, 0))
+            .collect();
+
+        for (i, r) in rows.iter_mut().enumerate() {
+            r.team = Some(i as i32 / 2);
+        }
+
+        let ratings = calculate_ratings(&rows);
+
+        assert_eq!(ratings.current(players[0]), ratings.current(players[1]));
+        assert_eq!(ratings.current(players[0]), INITIAL_RATING + K_FACTOR / 2.0);
+        assert_eq!(ratings.current(players[2]), INITIAL_RATING - K_FACTOR / 2.0);
+    }
+
+    #[test]
+    fn test_solo_match_changes_nothing() {
+        let (a, m) = (Uuid::new_v4(), Uuid::new_v4());
+        let ratings = calculate_ratings(&[row(m, a, 1, 0)]);
+
+        assert_eq!(ratings.current(a), INITIAL_RATING);
+    }
+}
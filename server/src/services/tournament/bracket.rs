@@ -0,0 +1,715 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::models::tournament::{
+    TournamentBracket, TournamentFormat, TournamentSlotDb, TournamentSlotStatus, TournamentStanding,
+};
+
+pub const POINTS_FOR_WIN: u32 = 3;
+pub const POINTS_FOR_DRAW: u32 = 1;
+
+/// Creates every slot of a tournament, given its players in seed order. Byes are resolved straight
+/// away, so top seeds in a bracket that isn't full move on without playing
+pub fn generate(
+    tournament_id: Uuid,
+    format: TournamentFormat,
+    player_ids: &[Uuid],
+) -> Vec<TournamentSlotDb> {
+    let mut slots = match format {
+        TournamentFormat::SingleElimination => {
+            let mut slots = Vec::new();
+            winners_bracket(&mut slots, tournament_id, player_ids);
+            slots
+        }
+        TournamentFormat::DoubleElimination => double_elimination(tournament_id, player_ids),
+        TournamentFormat::RoundRobin => round_robin(tournament_id, player_ids),
+    };
+
+    resolve(&mut slots);
+    slots
+}
+
+/// Records the result of a slot's match, moving players on to their next slots. A missing winner
+/// is a draw, which only round robins allow
+pub fn complete_slot(
+    slots: &mut [TournamentSlotDb],
+    slot_id: Uuid,
+    match_id: Uuid,
+    winner_id: Option<Uuid>,
+) {
+    let Some(index) = slots.iter().position(|s| s.id == slot_id) else {
+        return;
+    };
+
+    let slot = &mut slots[index];
+    slot.status = TournamentSlotStatus::Completed;
+    slot.match_id = Some(match_id);
+    slot.winner_id = winner_id;
+
+    advance(slots, index);
+    resolve(slots);
+}
+
+/// Gets the overall winner, once the tournament is over
+pub fn champion(
+    format: TournamentFormat,
+    slots: &[TournamentSlotDb],
+    standings: &[TournamentStanding],
+) -> Option<Uuid> {
+    match format {
+        TournamentFormat::RoundRobin => {
+            let finished = slots.iter().all(|s| {
+                matches!(
+                    s.status,
+                    TournamentSlotStatus::Completed | TournamentSlotStatus::Void
+                )
+            });
+
+            if finished {
+                standings.first().map(|s| s.user_id)
+            } else {
+                None
+            }
+        }
+        TournamentFormat::SingleElimination => slots
+            .iter()
+            .find(|s| s.winner_next_slot_id.is_none())
+            .and_then(|s| s.winner_id),
+        TournamentFormat::DoubleElimination => {
+            let grand_final = |round: i32| {
+                slots
+                    .iter()
+                    .find(|s| s.bracket == TournamentBracket::GrandFinal && s.round == round)
+            };
+
+            // The reset is void when the first grand final settled it
+            match grand_final(2) {
+                Some(reset) if reset.status != TournamentSlotStatus::Void => reset.winner_id,
+                _ => grand_final(1).and_then(|s| s.winner_id),
+            }
+        }
+    }
+}
+
+/// Ranks the players of a round robin by points, then wins, then seed
+pub fn standings(player_ids: &[Uuid], slots: &[TournamentSlotDb]) -> Vec<TournamentStanding> {
+    let mut standings: Vec<TournamentStanding> = player_ids
+        .iter()
+        .map(|&user_id| TournamentStanding {
+            user_id,
+            played: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points: 0,
+        })
+        .collect();
+
+    let seeds: HashMap<Uuid, usize> = player_ids
+        .iter()
+        .enumerate()
+        .map(|(i, &id)| (id, i))
+        .collect();
+
+    for slot in slots {
+        if slot.status != TournamentSlotStatus::Completed {
+            continue;
+        }
+
+        for player_id in [slot.player_1_id, slot.player_2_id].into_iter().flatten() {
+            let Some(&seed) = seeds.get(&player_id) else {
+                continue;
+            };
+
+            let standing = &mut standings[seed];
+            standing.played += 1;
+
+            match slot.winner_id {
+                Some(winner_id) if winner_id == player_id => {
+                    standing.wins += 1;
+                    standing.points += POINTS_FOR_WIN;
+                }
+                Some(_) => standing.losses += 1,
+                None => {
+                    standing.draws += 1;
+                    standing.points += POINTS_FOR_DRAW;
+                }
+            }
+        }
+    }
+
+    // Sorting is stable, so players level on points and wins stay in seed order
+    standings.sort_by(|a, b| b.points.cmp(&a.points).then(b.wins.cmp(&a.wins)));
+    standings
+}
+
+fn new_slot(
+    tournament_id: Uuid,
+    bracket: TournamentBracket,
+    round: i32,
+    position: i32,
+) -> TournamentSlotDb {
+    TournamentSlotDb {
+        id: Uuid::new_v4(),
+        tournament_id,
+        bracket,
+        round,
+        position,
+        status: TournamentSlotStatus::Pending,
+        player_1_id: None,
+        player_2_id: None,
+        match_id: None,
+        winner_id: None,
+        winner_next_slot_id: None,
+        winner_next_position: None,
+        loser_next_slot_id: None,
+        loser_next_position: None,
+    }
+}
+
+/// Order of seeds down a bracket, so the top seeds can only meet in the later rounds
+fn seed_order(bracket_size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < bracket_size {
+        let size = order.len() * 2;
+        order = order
+            .iter()
+            .flat_map(|&seed| [seed, size + 1 - seed])
+            .collect();
+    }
+
+    order
+}
+
+fn link_winner(slots: &mut [TournamentSlotDb], from: usize, to: usize, position: i32) {
+    slots[from].winner_next_slot_id = Some(slots[to].id);
+    slots[from].winner_next_position = Some(position);
+}
+
+fn link_loser(slots: &mut [TournamentSlotDb], from: usize, to: usize, position: i32) {
+    slots[from].loser_next_slot_id = Some(slots[to].id);
+    slots[from].loser_next_position = Some(position);
+}
+
+/// Adds a knockout bracket, returning the indexes of the slots in each round
+fn winners_bracket(
+    slots: &mut Vec<TournamentSlotDb>,
+    tournament_id: Uuid,
+    player_ids: &[Uuid],
+) -> Vec<Vec<usize>> {
+    let bracket_size = player_ids.len().next_power_of_two().max(2);
+    let mut rounds: Vec<Vec<usize>> = Vec::new();
+
+    let mut round_size = bracket_size / 2;
+    let mut round = 1;
+    while round_size >= 1 {
+        let indexes = (0..round_size)
+            .map(|position| {
+                slots.push(new_slot(
+                    tournament_id,
+                    TournamentBracket::Winners,
+                    round,
+                    position as i32 + 1,
+                ));
+                slots.len() - 1
+            })
+            .collect();
+
+        rounds.push(indexes);
+        round_size /= 2;
+        round += 1;
+    }
+
+    // Seeds without a player are byes
+    let order = seed_order(bracket_size);
+    for (position, &index) in rounds[0].iter().enumerate() {
+        let player = |seed: usize| player_ids.get(seed - 1).copied();
+        slots[index].player_1_id = player(order[position * 2]);
+        slots[index].player_2_id = player(order[position * 2 + 1]);
+    }
+
+    for round in 1..rounds.len() {
+        for (position, &index) in rounds[round - 1].iter().enumerate() {
+            let next = rounds[round][position / 2];
+            link_winner(slots, index, next, position as i32 % 2 + 1);
+        }
+    }
+
+    rounds
+}
+
+/// Adds a losers bracket alongside the knockout bracket, where players are knocked out after
+/// their second loss. The winners of each bracket meet in a grand final, which is played again
+/// if the winners bracket player loses it, so they are also only knocked out after two losses
+fn double_elimination(tournament_id: Uuid, player_ids: &[Uuid]) -> Vec<TournamentSlotDb> {
+    let mut slots = Vec::new();
+    let winners = winners_bracket(&mut slots, tournament_id, player_ids);
+
+    slots.push(new_slot(tournament_id, TournamentBracket::GrandFinal, 1, 1));
+    let grand_final = slots.len() - 1;
+
+    slots.push(new_slot(tournament_id, TournamentBracket::GrandFinal, 2, 1));
+    let reset = slots.len() - 1;
+    link_winner(&mut slots, grand_final, reset, 1);
+    link_loser(&mut slots, grand_final, reset, 2);
+
+    let winners_final = winners[winners.len() - 1][0];
+    link_winner(&mut slots, winners_final, grand_final, 1);
+
+    // With only one round, the loser goes straight to the grand final for a rematch
+    if winners.len() == 1 {
+        link_loser(&mut slots, winners_final, grand_final, 2);
+        return slots;
+    }
+
+    // Losers rounds alternate between dropping in losers from the winners bracket, and halving
+    let mut losers: Vec<Vec<usize>> = Vec::new();
+    for round in 0..2 * (winners.len() - 1) {
+        let size = winners[round / 2 + 1].len();
+        let indexes = (0..size)
+            .map(|position| {
+                slots.push(new_slot(
+                    tournament_id,
+                    TournamentBracket::Losers,
+                    round as i32 + 1,
+                    position as i32 + 1,
+                ));
+                slots.len() - 1
+            })
+            .collect();
+
+        losers.push(indexes);
+    }
+
+    // First round losers face each other
+    for (position, &index) in winners[0].iter().enumerate() {
+        link_loser(
+            &mut slots,
+            index,
+            losers[0][position / 2],
+            position as i32 % 2 + 1,
+        );
+    }
+
+    for (round, indexes) in losers.iter().enumerate() {
+        let is_drop_in_round = round % 2 == 1;
+
+        if is_drop_in_round {
+            // Reversed, so players are less likely to meet someone they've just played
+            let dropping = &winners[round / 2 + 1];
+            for (position, &index) in dropping.iter().enumerate() {
+                link_loser(&mut slots, index, indexes[dropping.len() - 1 - position], 2);
+            }
+        }
+
+        let Some(next_round) = losers.get(round + 1) else {
+            break;
+        };
+
+        for (position, &index) in indexes.iter().enumerate() {
+            if is_drop_in_round {
+                link_winner(
+                    &mut slots,
+                    index,
+                    next_round[position / 2],
+                    position as i32 % 2 + 1,
+                );
+            } else {
+                link_winner(&mut slots, index, next_round[position], 1);
+            }
+        }
+    }
+
+    let losers_final = losers[losers.len() - 1][0];
+    link_winner(&mut slots, losers_final, grand_final, 2);
+
+    slots
+}
+
+/// Pairs every player with every other player once, using the circle method
+fn round_robin(tournament_id: Uuid, player_ids: &[Uuid]) -> Vec<TournamentSlotDb> {
+    let mut circle: Vec<Option<Uuid>> = player_ids.iter().copied().map(Some).collect();
+
+    // With an odd number of players, whoever is paired with nobody sits the round out
+    if circle.len() % 2 == 1 {
+        circle.push(None);
+    }
+
+    let mut slots = Vec::new();
+    let size = circle.len();
+    for round in 0..size - 1 {
+        let mut position = 1;
+        for i in 0..size / 2 {
+            if let (Some(player_1), Some(player_2)) = (circle[i], circle[size - 1 - i]) {
+                let mut slot = new_slot(
+                    tournament_id,
+                    TournamentBracket::RoundRobin,
+                    round as i32 + 1,
+                    position,
+                );
+                slot.player_1_id = Some(player_1);
+                slot.player_2_id = Some(player_2);
+                slots.push(slot);
+                position += 1;
+            }
+        }
+
+        circle[1..].rotate_right(1);
+    }
+
+    slots
+}
+
+/// Moves the players of a finished slot on to their next slots
+fn advance(slots: &mut [TournamentSlotDb], index: usize) {
+    let slot = &slots[index];
+    let Some(winner_id) = slot.winner_id else {
+        return;
+    };
+
+    // Nobody moves on to the reset when the winners bracket player wins the grand final, which
+    // leaves it to be voided
+    if slot.bracket == TournamentBracket::GrandFinal
+        && slot.round == 1
+        && slot.player_1_id == Some(winner_id)
+    {
+        return;
+    }
+
+    let loser_id = [slot.player_1_id, slot.player_2_id]
+        .into_iter()
+        .flatten()
+        .find(|&id| id != winner_id);
+
+    let moves = [
+        (
+            Some(winner_id),
+            slot.winner_next_slot_id,
+            slot.winner_next_position,
+        ),
+        (loser_id, slot.loser_next_slot_id, slot.loser_next_position),
+    ];
+
+    for (player_id, next_slot_id, next_position) in moves {
+        let (Some(player_id), Some(next_slot_id)) = (player_id, next_slot_id) else {
+            continue;
+        };
+
+        if let Some(next) = slots.iter_mut().find(|s| s.id == next_slot_id) {
+            if next_position == Some(2) {
+                next.player_2_id = Some(player_id);
+            } else {
+                next.player_1_id = Some(player_id);
+            }
+        }
+    }
+}
+
+/// Settles every slot that no longer depends on a match being played. A slot becomes ready once
+/// both players arrive, is a walkover if only one can, and is void if neither can
+fn resolve(slots: &mut [TournamentSlotDb]) {
+    // (slot ID, position) -> index of the slot that sends a player there
+    let mut feeders: HashMap<(Uuid, i32), usize> = HashMap::new();
+    for (index, slot) in slots.iter().enumerate() {
+        let links = [
+            (slot.winner_next_slot_id, slot.winner_next_position),
+            (slot.loser_next_slot_id, slot.loser_next_position),
+        ];
+
+        for (next_slot_id, next_position) in links {
+            if let (Some(next_slot_id), Some(next_position)) = (next_slot_id, next_position) {
+                feeders.insert((next_slot_id, next_position), index);
+            }
+        }
+    }
+
+    loop {
+        let mut changed = false;
+
+        for index in 0..slots.len() {
+            let slot = &slots[index];
+            if slot.status != TournamentSlotStatus::Pending {
+                continue;
+            }
+
+            // Players are moved on as soon as a slot finishes, so an empty position whose feeder
+            // has finished will never be filled
+            let is_empty_for_good = |position: i32, player_id: Option<Uuid>| {
+                player_id.is_none()
+                    && feeders
+                        .get(&(slot.id, position))
+                        .is_none_or(|&feeder| slots[feeder].status.is_finished())
+            };
+
+            let empty_1 = is_empty_for_good(1, slot.player_1_id);
+            let empty_2 = is_empty_for_good(2, slot.player_2_id);
+
+            let (status, winner_id) = match (slot.player_1_id, slot.player_2_id) {
+                (Some(_), Some(_)) => (TournamentSlotStatus::Ready, None),
+                (Some(player_id), None) if empty_2 => {
+                    (TournamentSlotStatus::Walkover, Some(player_id))
+                }
+                (None, Some(player_id)) if empty_1 => {
+                    (TournamentSlotStatus::Walkover, Some(player_id))
+                }
+                (None, None) if empty_1 && empty_2 => (TournamentSlotStatus::Void, None),
+                _ => continue,
+            };
+
+            let slot = &mut slots[index];
+            slot.status = status;
+            slot.winner_id = winner_id;
+            advance(slots, index);
+            changed = true;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+}
+
+impl TournamentSlotStatus {
+    fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TournamentSlotStatus::Completed
+                | TournamentSlotStatus::Walkover
+                | TournamentSlotStatus::Void
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players(count: usize) -> Vec<Uuid> {
+        (0..count).map(|_| Uuid::new_v4()).collect()
+    }
+
+    fn find(
+        slots: &[TournamentSlotDb],
+        bracket: TournamentBracket,
+        round: i32,
+        position: i32,
+    ) -> &TournamentSlotDb {
+        slots
+            .iter()
+            .find(|s| s.bracket == bracket && s.round == round && s.position == position)
+            .unwrap()
+    }
+
+    /// Plays every ready slot, with the better seed winning, until nothing is left to play
+    fn play_out(slots: &mut [TournamentSlotDb], player_ids: &[Uuid]) {
+        let seed = |id: Uuid| player_ids.iter().position(|&p| p == id).unwrap();
+
+        while let Some(slot) = slots
+            .iter()
+            .find(|s| s.status == TournamentSlotStatus::Ready)
+        {
+            let (player_1, player_2) = (slot.player_1_id.unwrap(), slot.player_2_id.unwrap());
+            let winner = if seed(player_1) < seed(player_2) {
+                player_1
+            } else {
+                player_2
+            };
+
+            complete_slot(slots, slot.id, Uuid::new_v4(), Some(winner));
+        }
+    }
+
+    #[test]
+    fn test_seed_order_keeps_top_seeds_apart() {
+        assert_eq!(seed_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn test_single_elimination_gives_top_seeds_byes() {
+        let player_ids = players(6);
+        let slots = generate(
+            Uuid::new_v4(),
+            TournamentFormat::SingleElimination,
+            &player_ids,
+        );
+
+        // 4 + 2 + 1 slots for a bracket of 8
+        assert_eq!(slots.len(), 7);
+
+        let first = find(&slots, TournamentBracket::Winners, 1, 1);
+        assert_eq!(first.status, TournamentSlotStatus::Walkover);
+        assert_eq!(first.winner_id, Some(player_ids[0]));
+
+        let second_round = find(&slots, TournamentBracket::Winners, 2, 1);
+        assert_eq!(second_round.player_1_id, Some(player_ids[0]));
+        assert_eq!(second_round.status, TournamentSlotStatus::Pending);
+    }
+
+    #[test]
+    fn test_single_elimination_crowns_the_final_winner() {
+        let player_ids = players(5);
+        let mut slots = generate(
+            Uuid::new_v4(),
+            TournamentFormat::SingleElimination,
+            &player_ids,
+        );
+
+        assert_eq!(
+            champion(TournamentFormat::SingleElimination, &slots, &[]),
+            None
+        );
+
+        play_out(&mut slots, &player_ids);
+
+        assert_eq!(
+            champion(TournamentFormat::SingleElimination, &slots, &[]),
+            Some(player_ids[0])
+        );
+    }
+
+    #[test]
+    fn test_double_elimination_sends_losers_to_losers_bracket() {
+        let player_ids = players(4);
+        let mut slots = generate(
+            Uuid::new_v4(),
+            TournamentFormat::DoubleElimination,
+            &player_ids,
+        );
+
+        // Winners 2 + 1, losers 1 + 1, grand final and reset 2
+        assert_eq!(slots.len(), 7);
+
+        let first = find(&slots, TournamentBracket::Winners, 1, 1).clone();
+        complete_slot(&mut slots, first.id, Uuid::new_v4(), first.player_1_id);
+
+        let losers_first = find(&slots, TournamentBracket::Losers, 1, 1);
+        assert_eq!(losers_first.player_1_id, first.player_2_id);
+    }
+
+    #[test]
+    fn test_double_elimination_finishes_with_byes() {
+        for count in 2..=9 {
+            let player_ids = players(count);
+            let mut slots = generate(
+                Uuid::new_v4(),
+                TournamentFormat::DoubleElimination,
+                &player_ids,
+            );
+
+            play_out(&mut slots, &player_ids);
+
+            assert!(slots.iter().all(|s| s.status.is_finished()));
+            assert_eq!(
+                champion(TournamentFormat::DoubleElimination, &slots, &[]),
+                Some(player_ids[0])
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_robin_pairs_everyone_once() {
+        let player_ids = players(5);
+        let slots = generate(Uuid::new_v4(), TournamentFormat::RoundRobin, &player_ids);
+
+        assert_eq!(slots.len(), 10);
+        assert!(
+            slots
+                .iter()
+                .all(|s| s.status == TournamentSlotStatus::Ready)
+        );
+
+        for (i, &a) in player_ids.iter().enumerate() {
+            for &b in &player_ids[i + 1..] {
+                let meetings = slots
+                    .iter()
+                    .filter(|s| {
+                        let pair = [s.player_1_id, s.player_2_id];
+                        pair.contains(&Some(a)) && pair.contains(&Some(b))
+                    })
+                    .count();
+
+                assert_eq!(meetings, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_robin_standings_count_draws() {
+        let player_ids = players(3);
+        let mut slots = generate(Uuid::new_v4(), TournamentFormat::RoundRobin, &player_ids);
+
+        let ids: Vec<Uuid> = slots.iter().map(|s| s.id).collect();
+        let winner = slots[0].player_1_id;
+        complete_slot(&mut slots, ids[0], Uuid::new_v4(), winner);
+        complete_slot(&mut slots, ids[1], Uuid::new_v4(), None);
+
+        let table = standings(&player_ids, &slots);
+        assert_eq!(table[0].user_id, winner.unwrap());
+        assert_eq!(table[0].points, POINTS_FOR_WIN);
+        assert_eq!(
+            table.iter().map(|s| s.draws).sum::<u32>(),
+            2,
+            "Both players should be given a draw"
+        );
+        assert_eq!(champion(TournamentFormat::RoundRobin, &slots, &table), None);
+
+        complete_slot(&mut slots, ids[2], Uuid::new_v4(), None);
+        let table = standings(&player_ids, &slots);
+        assert!(champion(TournamentFormat::RoundRobin, &slots, &table).is_some());
+    }
+
+    #[test]
+    fn test_double_elimination_skips_reset_when_winners_player_wins() {
+        let player_ids = players(4);
+        let mut slots = generate(
+            Uuid::new_v4(),
+            TournamentFormat::DoubleElimination,
+            &player_ids,
+        );
+
+        play_out(&mut slots, &player_ids);
+
+        let reset = find(&slots, TournamentBracket::GrandFinal, 2, 1);
+        assert_eq!(reset.status, TournamentSlotStatus::Void);
+        assert_eq!(
+            champion(TournamentFormat::DoubleElimination, &slots, &[]),
+            Some(player_ids[0])
+        );
+    }
+
+    #[test]
+    fn test_double_elimination_plays_reset_when_losers_player_wins() {
+        let player_ids = players(2);
+        let mut slots = generate(
+            Uuid::new_v4(),
+            TournamentFormat::DoubleElimination,
+            &player_ids,
+        );
+
+        let first = find(&slots, TournamentBracket::Winners, 1, 1).clone();
+        complete_slot(&mut slots, first.id, Uuid::new_v4(), Some(player_ids[0]));
+
+        // The losers bracket player takes the first grand final, so it has to be played again
+        let grand_final = find(&slots, TournamentBracket::GrandFinal, 1, 1).clone();
+        complete_slot(
+            &mut slots,
+            grand_final.id,
+            Uuid::new_v4(),
+            Some(player_ids[1]),
+        );
+
+        let reset = find(&slots, TournamentBracket::GrandFinal, 2, 1).clone();
+        assert_eq!(reset.status, TournamentSlotStatus::Ready);
+        assert_eq!(
+            champion(TournamentFormat::DoubleElimination, &slots, &[]),
+            None
+        );
+
+        complete_slot(&mut slots, reset.id, Uuid::new_v4(), Some(player_ids[1]));
+        assert_eq!(
+            champion(TournamentFormat::DoubleElimination, &slots, &[]),
+            Some(player_ids[1])
+        );
+    }
+}
@@ -0,0 +1,284 @@
+mod bracket;
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::{AppError, GroupError, TournamentError},
+    models::{
+        game::GameDb,
+        game_match::CreateMatchReq,
+        group::GroupMemberDb,
+        stats::{MatchChange, MatchChangedEvent},
+        tournament::{
+            CreateTournamentReq, TournamentDb, TournamentDetails, TournamentFormat,
+            TournamentSlotStatus, TournamentStatus,
+        },
+    },
+    policies::GroupAction,
+    services::{game::fetch_game_guarded, game_match::record_match},
+};
+
+pub async fn create_tournament(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    payload: CreateTournamentReq,
+) -> Result<TournamentDetails, AppError> {
+    let (game, member) = fetch_game_guarded(state, game_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::CreateTournament) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    // Every bracket match is one player against another
+    if game.min_players_per_match > 2
+        || game.max_players_per_match < 2
+        || game.min_players_per_team > 1
+    {
+        return Err(TournamentError::UnsupportedGame.into());
+    }
+
+    let mut seen = HashSet::new();
+    if !payload.player_ids.iter().all(|id| seen.insert(*id)) {
+        return Err(TournamentError::DuplicatePlayer.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let all_members = state
+        .group_repo
+        .are_members(&mut *tx, game.group_id, &payload.player_ids)
+        .await?;
+
+    if !all_members {
+        return Err(TournamentError::PlayerNotMember.into());
+    }
+
+    let tournament = state
+        .tournament_repo
+        .create(&mut tx, game.id, &payload.name, payload.format, user_id)
+        .await?;
+
+    state
+        .tournament_repo
+        .add_participants(&mut tx, tournament.id, &payload.player_ids)
+        .await?;
+
+    let slots = bracket::generate(tournament.id, tournament.format, &payload.player_ids);
+    state.tournament_repo.create_slots(&mut tx, &slots).await?;
+
+    tx.commit().await?;
+
+    get_details(state, tournament).await
+}
+
+pub async fn get_tournaments(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+) -> Result<Vec<TournamentDb>, AppError> {
+    let (game, _) = fetch_game_guarded(state, game_id, user_id).await?;
+
+    let tournaments = state
+        .tournament_repo
+        .get_for_game(&state.pool, game.id)
+        .await?;
+
+    Ok(tournaments)
+}
+
+pub async fn get_tournament(
+    state: &AppState,
+    user_id: Uuid,
+    tournament_id: Uuid,
+) -> Result<TournamentDetails, AppError> {
+    let (tournament, _, _) = fetch_tournament_guarded(state, tournament_id, user_id).await?;
+
+    get_details(state, tournament).await
+}
+
+pub async fn delete_tournament(
+    state: &AppState,
+    user_id: Uuid,
+    tournament_id: Uuid,
+) -> Result<(), AppError> {
+    let (tournament, _, member) = fetch_tournament_guarded(state, tournament_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::DeleteTournament) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    // Matches already played are kept, as they still count towards the game's stats
+    state
+        .tournament_repo
+        .delete(&state.pool, tournament.id)
+        .await?;
+
+    Ok(())
+}
+
+/// Records the match for a slot, then moves the winner (and loser) on through the bracket
+pub async fn record_slot_match(
+    state: &AppState,
+    user_id: Uuid,
+    tournament_id: Uuid,
+    slot_id: Uuid,
+    payload: CreateMatchReq,
+) -> Result<TournamentDetails, AppError> {
+    let (_, game, member) = fetch_tournament_guarded(state, tournament_id, user_id).await?;
+    if !member.role.can_perform(GroupAction::CreateMatch) {
+        return Err(GroupError::Forbidden.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Locked so two results for the same bracket can't be recorded at once
+    let tournament = state
+        .tournament_repo
+        .get_for_update(&mut tx, tournament_id)
+        .await?
+        .ok_or(TournamentError::NotFound)?;
+
+    if tournament.status == TournamentStatus::Completed {
+        return Err(TournamentError::AlreadyCompleted.into());
+    }
+
+    let original_slots = state
+        .tournament_repo
+        .get_slots(&mut *tx, tournament.id)
+        .await?;
+
+    let slot = original_slots
+        .iter()
+        .find(|s| s.id == slot_id)
+        .ok_or(TournamentError::SlotNotFound)?;
+
+    if slot.status != TournamentSlotStatus::Ready {
+        return Err(TournamentError::SlotNotReady.into());
+    }
+
+    let slot_players: HashSet<Uuid> = [slot.player_1_id, slot.player_2_id]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let match_players: HashSet<Uuid> = payload.scores.iter().map(|s| s.user_id).collect();
+    if payload.scores.len() != 2 || slot_players != match_players {
+        return Err(TournamentError::PlayersMismatch.into());
+    }
+
+    let game_match = record_match(state, &mut tx, &game, user_id, payload).await?;
+
+    let leaderboard = state
+        .match_repo
+        .get_leaderboards(&mut *tx, &[game_match.id])
+        .await?;
+
+    // Ordered by rank, so the first player won unless both share the top rank
+    let winner_id = match leaderboard.as_slice() {
+        [first, second] if first.rank < second.rank => Some(first.user_id),
+        _ => None,
+    };
+
+    if winner_id.is_none() && tournament.format != TournamentFormat::RoundRobin {
+        return Err(TournamentError::DrawNotAllowed.into());
+    }
+
+    let mut slots = original_slots.clone();
+    bracket::complete_slot(&mut slots, slot_id, game_match.id, winner_id);
+
+    let changed_slots: Vec<_> = slots
+        .iter()
+        .filter(|s| !original_slots.contains(s))
+        .cloned()
+        .collect();
+
+    state
+        .tournament_repo
+        .update_slots(&mut tx, &changed_slots)
+        .await?;
+
+    let participants = state
+        .tournament_repo
+        .get_participants(&mut *tx, tournament.id)
+        .await?;
+    let player_ids: Vec<Uuid> = participants.iter().map(|p| p.user_id).collect();
+    let standings = bracket::standings(&player_ids, &slots);
+
+    if let Some(champion_id) = bracket::champion(tournament.format, &slots, &standings) {
+        state
+            .tournament_repo
+            .complete(&mut tx, tournament.id, champion_id)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    // Invalidate cache
+    state
+        .stats_cache_invalidator
+        .invalidate_game_stats(game.id)
+        .await?;
+
+    state.live_updates.publish(MatchChangedEvent {
+        change: MatchChange::Recorded,
+        game_id: game.id,
+        match_id: game_match.id,
+        season_id: game_match.season_id,
+    });
+
+    let tournament = state
+        .tournament_repo
+        .get(&state.pool, tournament.id)
+        .await?
+        .ok_or(TournamentError::NotFound)?;
+
+    get_details(state, tournament).await
+}
+
+async fn get_details(
+    state: &AppState,
+    tournament: TournamentDb,
+) -> Result<TournamentDetails, AppError> {
+    let participants = state
+        .tournament_repo
+        .get_participants(&state.pool, tournament.id)
+        .await?;
+
+    let slots = state
+        .tournament_repo
+        .get_slots(&state.pool, tournament.id)
+        .await?;
+
+    // Only round robins are decided by a table
+    let standings = if tournament.format == TournamentFormat::RoundRobin {
+        let player_ids: Vec<Uuid> = participants.iter().map(|p| p.user_id).collect();
+        bracket::standings(&player_ids, &slots)
+    } else {
+        Vec::new()
+    };
+
+    Ok(TournamentDetails {
+        tournament,
+        participants,
+        slots,
+        standings,
+    })
+}
+
+pub async fn fetch_tournament_guarded(
+    state: &AppState,
+    tournament_id: Uuid,
+    user_id: Uuid,
+) -> Result<(TournamentDb, GameDb, GroupMemberDb), AppError> {
+    let tournament = state
+        .tournament_repo
+        .get(&state.pool, tournament_id)
+        .await?
+        .ok_or(TournamentError::NotFound)?;
+
+    let (game, member) = fetch_game_guarded(state, tournament.game_id, user_id).await?;
+
+    Ok((tournament, game, member))
+}
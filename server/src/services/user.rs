@@ -1,11 +1,18 @@
+use chrono::Utc;
 use sha256::digest;
 use uuid::Uuid;
 
 use crate::{
     AppState,
-    errors::{AppError, UserError},
-    models::user::{CreateUserReq, UserDb},
-    services::{self},
+    errors::{AppError, GroupError, UserError},
+    models::{
+        export::PersonalDataResponse,
+        user::{CreateUserReq, DeleteAccountReq, ScoreRetention, UserDb},
+    },
+    services::{
+        self,
+        season::{refresh_season_results, seasons_by_game},
+    },
 };
 
 pub async fn update_password(
@@ -121,6 +128,113 @@ pub async fn update_email(
     Ok(user)
 }
 
+/// Gathers everything stored about a user
+pub async fn export_personal_data(
+    state: &AppState,
+    user: UserDb,
+) -> Result<PersonalDataResponse, AppError> {
+    let memberships = state
+        .group_repo
+        .get_memberships(&state.pool, user.id)
+        .await
+        .map_err(GroupError::Database)?;
+
+    let scores = state
+        .match_repo
+        .get_user_scores(&state.pool, user.id)
+        .await?;
+
+    Ok(PersonalDataResponse {
+        exported_at: Utc::now(),
+        profile: user.into(),
+        memberships,
+        scores,
+    })
+}
+
+/// Deletes a user's account once they confirm their password. Groups they own are handed over
+/// rather than left without an owner
+pub async fn delete_account(
+    state: &AppState,
+    user: UserDb,
+    payload: DeleteAccountReq,
+) -> Result<(), AppError> {
+    if !services::auth::verify_password(&user.password_hash, &payload.password) {
+        return Err(UserError::InvalidCurrentPassword.into());
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    services::group::hand_over_groups(state, &mut tx, user.id, &payload.new_owners).await?;
+
+    // Scores in groups that were just deleted are already gone
+    let scores = state.match_repo.get_user_scores(&mut *tx, user.id).await?;
+
+    if payload.scores == ScoreRetention::Remove {
+        state
+            .match_repo
+            .delete_user_scores(&mut tx, user.id)
+            .await?;
+    }
+
+    state
+        .group_repo
+        .remove_memberships(&mut *tx, user.id)
+        .await
+        .map_err(GroupError::Database)?;
+
+    state.fixture_repo.remove_player(&mut *tx, user.id).await?;
+    state
+        .invite_repo
+        .delete_created_by(&mut *tx, user.id)
+        .await?;
+
+    state
+        .password_resets_repo
+        .delete_all_tokens_for_user_id(&mut *tx, &user.id)
+        .await?;
+
+    state
+        .verification_repo
+        .delete_all_tokens_for_email(&mut *tx, &user.email)
+        .await?;
+
+    // Also signs the user out everywhere
+    state
+        .user_repo
+        .anonymise(&mut *tx, user.id)
+        .await
+        .map_err(UserError::Database)?;
+
+    let seasons_by_game = seasons_by_game(&scores);
+    if payload.scores == ScoreRetention::Remove {
+        for (game_id, season_ids) in &seasons_by_game {
+            refresh_season_results(state, &mut tx, *game_id, season_ids).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    for game_id in seasons_by_game.into_keys() {
+        // Invalidate cache, as it holds the user's name as well as their scores
+        state
+            .stats_cache_invalidator
+            .invalidate_game_stats(game_id)
+            .await?;
+    }
+
+    // The account is already gone, so a failed email shouldn't report the deletion as failed
+    if let Err(e) = state
+        .email_service
+        .send_account_deleted_email(&user.email, &user.name)
+        .await
+    {
+        eprintln!("Failed to send account deleted email: {}", e);
+    }
+
+    Ok(())
+}
+
 pub async fn resend_verification(state: &AppState, user: UserDb) -> Result<(), AppError> {
     if user.email_verified {
         return Err(UserError::EmailAlreadyVerified.into());